@@ -1,6 +1,6 @@
 use crate::{
-    opcodes::Opcode,
-    executor::ExecutionResult,
+    opcodes::{instructions, Opcode},
+    executor::{ExecutionOutcome, ExecutionResult},
 };
 use std::collections::HashMap;
 use std::fmt;
@@ -57,6 +57,12 @@ pub struct GasOptimization {
     pub stack_optimization: bool,
 }
 
+impl Default for GasOptimization {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl GasOptimization {
     pub fn new() -> Self {
         GasOptimization {
@@ -95,14 +101,13 @@ impl GasOptimization {
         let mut i = 0;
         while i < bytecode.len().saturating_sub(1) {
             // Optimize PUSH1 0; DUP1 -> PUSH1 0; PUSH1 0
-            if i + 1 < bytecode.len() {
-                if bytecode[i] == 0x60 && bytecode[i + 1] == 0x00 && i + 2 < bytecode.len() && bytecode[i + 2] == 0x80 {
+            if i + 1 < bytecode.len()
+                && bytecode[i] == 0x60 && bytecode[i + 1] == 0x00 && i + 2 < bytecode.len() && bytecode[i + 2] == 0x80 {
                     // Replace with PUSH1 0; PUSH1 0
                     bytecode[i + 2] = 0x60;
                     bytecode.insert(i + 3, 0x00);
                     i += 2;
                 }
-            }
             i += 1;
         }
         bytecode
@@ -148,7 +153,7 @@ impl GasOptimization {
             // Handle PUSH opcodes
             if let Ok(opcode) = Opcode::from_byte(bytecode[i]) {
                 if opcode.is_push() {
-                    let push_size = opcode.get_push_size();
+                    let push_size = opcode.push_size();
                     for j in 1..=push_size {
                         if i + j < bytecode.len() {
                             result.push(bytecode[i + j]);
@@ -183,6 +188,12 @@ pub struct PerformanceMonitor {
     pub memory_usage: Vec<usize>,
 }
 
+impl Default for PerformanceMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PerformanceMonitor {
     pub fn new() -> Self {
         PerformanceMonitor {
@@ -202,10 +213,11 @@ impl PerformanceMonitor {
         let execution_time = start_time.elapsed();
 
         let metrics = PerformanceMetrics {
-            execution_time_us: execution_time.as_micros() as u64,
+            execution_time_us: execution_time.as_nanos().div_ceil(1000) as u64,
             gas_consumed: result.gas_used,
             gas_remaining: result.gas_remaining,
             success: result.success,
+            outcome: result.outcome,
             memory_peak: 0, // Would need to track this during execution
             opcode_count: 0, // Would need to track this during execution
         };
@@ -233,18 +245,20 @@ pub struct PerformanceMetrics {
     pub gas_consumed: u64,
     pub gas_remaining: u64,
     pub success: bool,
+    pub outcome: ExecutionOutcome,
     pub memory_peak: usize,
     pub opcode_count: usize,
 }
 
 impl fmt::Display for PerformanceMetrics {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Performance Metrics:\n")?;
-        write!(f, "  Execution Time: {}μs\n", self.execution_time_us)?;
-        write!(f, "  Gas Consumed: {}\n", self.gas_consumed)?;
-        write!(f, "  Gas Remaining: {}\n", self.gas_remaining)?;
-        write!(f, "  Success: {}\n", self.success)?;
-        write!(f, "  Memory Peak: {} bytes\n", self.memory_peak)?;
+        writeln!(f, "Performance Metrics:")?;
+        writeln!(f, "  Execution Time: {}μs", self.execution_time_us)?;
+        writeln!(f, "  Gas Consumed: {}", self.gas_consumed)?;
+        writeln!(f, "  Gas Remaining: {}", self.gas_remaining)?;
+        writeln!(f, "  Success: {}", self.success)?;
+        writeln!(f, "  Outcome: {}", self.outcome)?;
+        writeln!(f, "  Memory Peak: {} bytes", self.memory_peak)?;
         write!(f, "  Opcode Count: {}", self.opcode_count)
     }
 }
@@ -259,9 +273,9 @@ pub struct PerformanceStats {
 
 impl fmt::Display for PerformanceStats {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Performance Statistics:\n")?;
-        write!(f, "  Average Execution Time: {:.2}μs\n", self.avg_execution_time)?;
-        write!(f, "  Average Gas Consumption: {:.2}\n", self.avg_gas_consumption)?;
+        writeln!(f, "Performance Statistics:")?;
+        writeln!(f, "  Average Execution Time: {:.2}μs", self.avg_execution_time)?;
+        writeln!(f, "  Average Gas Consumption: {:.2}", self.avg_gas_consumption)?;
         write!(f, "  Total Executions: {}", self.total_executions)
     }
 }
@@ -273,6 +287,12 @@ pub struct ContractAnalyzer {
     pub function_selectors: HashMap<[u8; 4], String>,
 }
 
+impl Default for ContractAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ContractAnalyzer {
     pub fn new() -> Self {
         let mut analyzer = ContractAnalyzer {
@@ -296,88 +316,43 @@ impl ContractAnalyzer {
         }
     }
 
+    /// Decode the opcodes in bytecode, skipping push data and undefined bytes
+    fn decode(bytecode: &[u8]) -> impl Iterator<Item = (usize, Opcode)> + '_ {
+        instructions(bytecode).filter_map(|(pc, _, info)| info.map(|info| (pc, info.opcode)))
+    }
+
     /// Analyze opcode frequency
     fn analyze_opcode_frequency(&self, bytecode: &[u8]) -> HashMap<Opcode, usize> {
         let mut frequency = HashMap::new();
-        let mut i = 0;
-        
-        while i < bytecode.len() {
-            if let Ok(opcode) = Opcode::from_byte(bytecode[i]) {
-                *frequency.entry(opcode).or_insert(0) += 1;
-                
-                if opcode.is_push() {
-                    i += opcode.get_push_size() + 1;
-                } else {
-                    i += 1;
-                }
-            } else {
-                i += 1;
-            }
+        for (_, opcode) in Self::decode(bytecode) {
+            *frequency.entry(opcode).or_insert(0) += 1;
         }
-        
         frequency
     }
 
     /// Estimate gas cost
     fn estimate_gas_cost(&self, bytecode: &[u8]) -> u64 {
-        let mut total_gas = 0;
-        let mut i = 0;
-        
-        while i < bytecode.len() {
-            if let Ok(opcode) = Opcode::from_byte(bytecode[i]) {
-                total_gas += self.get_opcode_gas_cost(&opcode);
-                
-                if opcode.is_push() {
-                    i += opcode.get_push_size() + 1;
-                } else {
-                    i += 1;
-                }
-            } else {
-                i += 1;
-            }
-        }
-        
-        total_gas
+        Self::decode(bytecode)
+            .map(|(_, opcode)| self.get_opcode_gas_cost(&opcode))
+            .sum()
     }
 
     /// Get gas cost for an opcode
     fn get_opcode_gas_cost(&self, opcode: &Opcode) -> u64 {
-        match opcode {
-            Opcode::Stop => 0,
-            Opcode::Add | Opcode::Sub | Opcode::Mul | Opcode::Div | Opcode::Mod => 3,
-            Opcode::Exp => 10,
-            Opcode::Sstore => 100,
-            Opcode::Sha3 => 30,
-            _ => 2, // Base cost for most opcodes
-        }
+        opcode.info().base_gas
     }
 
     /// Calculate complexity score
     fn calculate_complexity(&self, bytecode: &[u8]) -> f64 {
-        let mut complexity = 0.0;
-        let mut i = 0;
-        
-        while i < bytecode.len() {
-            if let Ok(opcode) = Opcode::from_byte(bytecode[i]) {
-                match opcode {
-                    Opcode::Jump | Opcode::Jumpi => complexity += 2.0,
-                    Opcode::Call | Opcode::Delegatecall | Opcode::Staticcall => complexity += 3.0,
-                    Opcode::Sstore => complexity += 1.5,
-                    Opcode::Exp => complexity += 1.2,
-                    _ => complexity += 0.1,
-                }
-                
-                if opcode.is_push() {
-                    i += opcode.get_push_size() + 1;
-                } else {
-                    i += 1;
-                }
-            } else {
-                i += 1;
-            }
-        }
-        
-        complexity
+        Self::decode(bytecode)
+            .map(|(_, opcode)| match opcode {
+                Opcode::Jump | Opcode::Jumpi => 2.0,
+                Opcode::Call | Opcode::Delegatecall | Opcode::Staticcall => 3.0,
+                Opcode::Sstore => 1.5,
+                Opcode::Exp => 1.2,
+                _ => 0.1,
+            })
+            .sum()
     }
 
     /// Detect potential issues
@@ -385,31 +360,18 @@ impl ContractAnalyzer {
         let mut issues = Vec::new();
         
         // Check for expensive opcodes
-        let mut expensive_count = 0;
-        for &byte in bytecode {
-            if let Ok(opcode) = Opcode::from_byte(byte) {
-                match opcode {
-                    Opcode::Exp | Opcode::Sstore | Opcode::Sha3 => {
-                        expensive_count += 1;
-                    }
-                    _ => {}
-                }
-            }
-        }
+        let expensive_count = Self::decode(bytecode)
+            .filter(|(_, opcode)| matches!(opcode, Opcode::Exp | Opcode::Sstore | Opcode::Sha3))
+            .count();
         
         if expensive_count > bytecode.len() / 10 {
             issues.push("High usage of expensive opcodes detected".to_string());
         }
         
         // Check for potential infinite loops
-        let mut jump_count = 0;
-        for &byte in bytecode {
-            if let Ok(opcode) = Opcode::from_byte(byte) {
-                if opcode == Opcode::Jump || opcode == Opcode::Jumpi {
-                    jump_count += 1;
-                }
-            }
-        }
+        let jump_count = Self::decode(bytecode)
+            .filter(|(_, opcode)| matches!(opcode, Opcode::Jump | Opcode::Jumpi))
+            .count();
         
         if jump_count > bytecode.len() / 5 {
             issues.push("Potential infinite loop detected".to_string());
@@ -420,26 +382,11 @@ impl ContractAnalyzer {
 
     /// Extract function selectors
     fn extract_function_selectors(&self, bytecode: &[u8]) -> Vec<[u8; 4]> {
-        let mut selectors = Vec::new();
-        
-        // Look for PUSH4 patterns that might be function selectors
-        let mut i = 0;
-        while i < bytecode.len().saturating_sub(4) {
-            if bytecode[i] == 0x63 { // PUSH4
-                let selector = [
-                    bytecode[i + 1],
-                    bytecode[i + 2],
-                    bytecode[i + 3],
-                    bytecode[i + 4],
-                ];
-                selectors.push(selector);
-                i += 5;
-            } else {
-                i += 1;
-            }
-        }
-        
-        selectors
+        // PUSH4 immediates are likely function selectors
+        Self::decode(bytecode)
+            .filter(|(pc, opcode)| *opcode == Opcode::Push4 && pc + 4 < bytecode.len())
+            .map(|(pc, _)| [bytecode[pc + 1], bytecode[pc + 2], bytecode[pc + 3], bytecode[pc + 4]])
+            .collect()
     }
 
     /// Add common function selectors
@@ -467,25 +414,25 @@ pub struct ContractAnalysis {
 
 impl fmt::Display for ContractAnalysis {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Contract Analysis:\n")?;
-        write!(f, "  Size: {} bytes\n", self.size)?;
-        write!(f, "  Gas Estimate: {}\n", self.gas_estimate)?;
-        write!(f, "  Complexity Score: {:.2}\n", self.complexity_score)?;
-        write!(f, "  Function Selectors: {}\n", self.function_selectors.len())?;
+        writeln!(f, "Contract Analysis:")?;
+        writeln!(f, "  Size: {} bytes", self.size)?;
+        writeln!(f, "  Gas Estimate: {}", self.gas_estimate)?;
+        writeln!(f, "  Complexity Score: {:.2}", self.complexity_score)?;
+        writeln!(f, "  Function Selectors: {}", self.function_selectors.len())?;
         
         if !self.potential_issues.is_empty() {
-            write!(f, "  Potential Issues:\n")?;
+            writeln!(f, "  Potential Issues:")?;
             for issue in &self.potential_issues {
-                write!(f, "    - {}\n", issue)?;
+                writeln!(f, "    - {}", issue)?;
             }
         }
         
-        write!(f, "  Top Opcodes:\n")?;
+        writeln!(f, "  Top Opcodes:")?;
         let mut sorted_opcodes: Vec<_> = self.opcode_frequency.iter().collect();
         sorted_opcodes.sort_by(|a, b| b.1.cmp(a.1));
         
         for (opcode, count) in sorted_opcodes.iter().take(5) {
-            write!(f, "    {}: {}\n", opcode, count)?;
+            writeln!(f, "    {}: {}", opcode, count)?;
         }
         
         Ok(())
@@ -501,6 +448,7 @@ impl Default for AdvancedEVM {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::Bytes;
 
     #[test]
     fn test_gas_optimization() {
@@ -524,8 +472,10 @@ mod tests {
         let mut monitor = PerformanceMonitor::new();
         let result = monitor.monitor(|| ExecutionResult {
             success: true,
+            outcome: ExecutionOutcome::Success,
             gas_used: 100,
             gas_remaining: 900,
+            gas_refund: 0,
             return_data: Bytes::empty(),
             logs: vec![],
         });
@@ -0,0 +1,98 @@
+use crate::{
+    types::Hash,
+    opcodes::{instructions, Opcode},
+};
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Number of analyses kept before the cache is flushed
+const CACHE_CAPACITY: usize = 4096;
+
+/// Bitmap of the valid JUMPDEST positions in a piece of code
+///
+/// A 0x5b byte only counts when it is an instruction, not part of PUSH data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JumpDestinations {
+    bits: Vec<u64>,
+}
+
+impl JumpDestinations {
+    /// Scan code once, skipping PUSH immediates
+    pub fn analyze(code: &[u8]) -> Self {
+        let mut bits = vec![0u64; code.len().div_ceil(64)];
+        for (pc, _, info) in instructions(code) {
+            if info.is_some_and(|info| info.opcode == Opcode::Jumpdest) {
+                bits[pc / 64] |= 1 << (pc % 64);
+            }
+        }
+        JumpDestinations { bits }
+    }
+
+    /// Check whether a jump to `pc` lands on a JUMPDEST instruction
+    pub fn is_valid(&self, pc: usize) -> bool {
+        self.bits
+            .get(pc / 64)
+            .is_some_and(|word| word & (1 << (pc % 64)) != 0)
+    }
+
+    /// Number of valid jump destinations
+    pub fn count(&self) -> usize {
+        self.bits.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Positions of the valid jump destinations in ascending order
+    pub fn positions(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.bits.len() * 64).filter(|&pc| self.is_valid(pc))
+    }
+}
+
+/// Get the jump destinations for code, reusing the analysis of identical code
+pub fn jump_destinations(code: &[u8]) -> Arc<JumpDestinations> {
+    static CACHE: OnceLock<Mutex<HashMap<Hash, Arc<JumpDestinations>>>> = OnceLock::new();
+
+    let code_hash = Hash::new(Keccak256::digest(code).into());
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    // A poisoned cache only ever holds complete analyses, so keep using it
+    let mut cache = cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(analysis) = cache.get(&code_hash) {
+        return Arc::clone(analysis);
+    }
+    if cache.len() >= CACHE_CAPACITY {
+        cache.clear();
+    }
+    let analysis = Arc::new(JumpDestinations::analyze(code));
+    cache.insert(code_hash, Arc::clone(&analysis));
+    analysis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jumpdest_bitmap() {
+        // JUMPDEST PUSH1 0x5b JUMPDEST PUSH2 0x5b5b
+        let code = [0x5b, 0x60, 0x5b, 0x5b, 0x61, 0x5b, 0x5b];
+        let analysis = JumpDestinations::analyze(&code);
+
+        assert!(analysis.is_valid(0));
+        assert!(!analysis.is_valid(2));
+        assert!(analysis.is_valid(3));
+        assert!(!analysis.is_valid(5));
+        assert!(!analysis.is_valid(6));
+        assert!(!analysis.is_valid(1000));
+        assert_eq!(analysis.positions().collect::<Vec<_>>(), vec![0, 3]);
+        assert_eq!(analysis.count(), 2);
+    }
+
+    #[test]
+    fn test_analysis_is_cached_by_code_hash() {
+        let code = vec![0x60, 0x04, 0x56, 0x00, 0x5b];
+        let first = jump_destinations(&code);
+        let second = jump_destinations(&code.clone());
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(first.is_valid(4));
+    }
+}
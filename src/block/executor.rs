@@ -0,0 +1,233 @@
+use crate::{
+    types::Hash,
+    hardfork::Hardfork,
+    storage::Storage,
+    trie::ordered_trie_root,
+    transaction::{TransactionError, TransactionProcessor, TransactionResult, TxEnvelope},
+};
+use super::{BlockContext, Bloom, Receipt};
+use thiserror::Error;
+
+/// Reasons a block is rejected; the state is left as it was before the block
+#[derive(Error, Debug)]
+pub enum BlockError {
+    #[error("Transaction {index} gas limit {gas_limit} exceeds the {available} gas left in the block")]
+    GasLimitReached { index: usize, gas_limit: u64, available: u64 },
+    #[error("Transaction {index} is invalid: {source}")]
+    Transaction {
+        index: usize,
+        #[source]
+        source: TransactionError,
+    },
+}
+
+/// Outcome of an executed block
+#[derive(Debug, Clone)]
+pub struct BlockResult {
+    /// Per-transaction results, in block order
+    pub results: Vec<TransactionResult>,
+    /// Per-transaction receipts, in block order
+    pub receipts: Vec<Receipt>,
+    /// Total gas used by the block
+    pub gas_used: u64,
+    /// Union of every receipt's bloom
+    pub logs_bloom: Bloom,
+    /// Root of the trie of EIP-2718 encoded transactions keyed by index
+    pub transactions_root: Hash,
+    /// Root of the trie of encoded receipts keyed by index
+    pub receipts_root: Hash,
+}
+
+/// Applies an ordered list of transactions to a world state as one block
+pub struct BlockExecutor<'a> {
+    storage: &'a mut Storage,
+    block: &'a BlockContext,
+    fork: Hardfork,
+}
+
+impl<'a> BlockExecutor<'a> {
+    /// Create an executor over the given state and block
+    pub fn new(storage: &'a mut Storage, block: &'a BlockContext) -> Self {
+        BlockExecutor { storage, block, fork: Hardfork::default() }
+    }
+
+    /// Apply the block under the rules of `fork`
+    pub fn with_fork(mut self, fork: Hardfork) -> Self {
+        self.fork = fork;
+        self
+    }
+
+    /// Apply every transaction in order
+    ///
+    /// The block gas limit caps the sum of the gas used so far and each
+    /// transaction's gas limit. Any invalid transaction rejects the whole block.
+    /// From Prague the parent hash is stored in the EIP-2935 history contract first.
+    pub fn execute(&mut self, transactions: &[TxEnvelope]) -> Result<BlockResult, BlockError> {
+        let checkpoint = self.storage.checkpoint();
+        let result = self.apply(transactions);
+        match result {
+            Ok(_) => self.storage.commit(checkpoint),
+            Err(_) => self.storage.revert_to(checkpoint),
+        }
+        result
+    }
+
+    fn apply(&mut self, transactions: &[TxEnvelope]) -> Result<BlockResult, BlockError> {
+        self.block.store_parent_hash(self.storage, self.fork);
+        // The system write is not part of the first transaction
+        self.storage.end_transaction();
+        let block_gas_limit = self.block.gas_limit.to_u64_safe().unwrap_or(u64::MAX);
+        let mut gas_used = 0u64;
+        let mut logs_bloom = Bloom::zero();
+        let mut results = Vec::with_capacity(transactions.len());
+        let mut receipts = Vec::with_capacity(transactions.len());
+
+        for (index, envelope) in transactions.iter().enumerate() {
+            let available = block_gas_limit - gas_used;
+            if envelope.gas_limit() > available {
+                return Err(BlockError::GasLimitReached { index, gas_limit: envelope.gas_limit(), available });
+            }
+
+            let result = TransactionProcessor::new(self.storage, self.block)
+                .with_fork(self.fork)
+                .execute_signed(envelope)
+                .map_err(|source| BlockError::Transaction { index, source })?;
+            gas_used += result.gas_used;
+
+            let receipt = Receipt::new(envelope.tx_type(), result.success(), gas_used, result.logs.clone());
+            logs_bloom |= receipt.bloom;
+            receipts.push(receipt);
+            results.push(result);
+        }
+
+        Ok(BlockResult {
+            transactions_root: ordered_trie_root(transactions.iter().map(TxEnvelope::encode)),
+            receipts_root: ordered_trie_root(receipts.iter().map(Receipt::encode)),
+            results,
+            receipts,
+            gas_used,
+            logs_bloom,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{DynamicFeeTransaction, Signature};
+    use crate::types::{Address, Bytes, Uint256};
+    use secp256k1::{Message, Secp256k1, SecretKey};
+    use sha3::{Digest, Keccak256};
+
+    const ETHER: u64 = 1_000_000_000_000_000_000;
+    const GWEI: u64 = 1_000_000_000;
+
+    fn key() -> SecretKey {
+        SecretKey::from_slice(&[0x33; 32]).unwrap()
+    }
+
+    fn sender() -> Address {
+        let public_key = key().public_key(&Secp256k1::new());
+        let hash = Keccak256::digest(&public_key.serialize_uncompressed()[1..]);
+        Address::new(hash[12..].try_into().unwrap())
+    }
+
+    fn logger() -> Address {
+        Address::from_hex("0x00000000000000000000000000000000000000aa").unwrap()
+    }
+
+    fn signed_tx(nonce: u64, to: Address, gas_limit: u64) -> TxEnvelope {
+        let mut tx = DynamicFeeTransaction {
+            chain_id: 1,
+            nonce,
+            max_priority_fee_per_gas: Uint256::from_u64(GWEI),
+            max_fee_per_gas: Uint256::from_u64(100 * GWEI),
+            gas_limit,
+            to: Some(to),
+            value: Uint256::from_u64(1),
+            data: Bytes::empty(),
+            access_list: Vec::new(),
+            signature: Signature::default(),
+        };
+        let hash = TxEnvelope::DynamicFee(tx.clone()).signing_hash();
+        let message = Message::from_digest_slice(hash.as_bytes()).unwrap();
+        let (recovery_id, compact) = Secp256k1::new().sign_ecdsa_recoverable(&message, &key()).serialize_compact();
+        tx.signature = Signature {
+            v: recovery_id.to_i32() as u64,
+            r: Uint256::from_bytes_be(&compact[..32]),
+            s: Uint256::from_bytes_be(&compact[32..]),
+        };
+        TxEnvelope::DynamicFee(tx)
+    }
+
+    fn test_state() -> (Storage, BlockContext) {
+        let mut storage = Storage::new();
+        storage.set_balance(sender(), Uint256::from_u64(ETHER));
+        // PUSH1 0xaa PUSH1 0 PUSH1 0 LOG1 STOP
+        storage.set_code(logger(), vec![0x60, 0xaa, 0x60, 0x00, 0x60, 0x00, 0xa1, 0x00]);
+        let mut block = BlockContext::new();
+        block.base_fee = Uint256::from_u64(10 * GWEI);
+        block.gas_limit = Uint256::from_u64(100_000);
+        (storage, block)
+    }
+
+    #[test]
+    fn test_block_receipts_and_roots() {
+        let (mut storage, block) = test_state();
+        let recipient = Address::from_hex("0x00000000000000000000000000000000000000bb").unwrap();
+        let transactions = vec![signed_tx(0, recipient, 21_000), signed_tx(1, logger(), 50_000)];
+
+        let result = BlockExecutor::new(&mut storage, &block).execute(&transactions).unwrap();
+        assert_eq!(result.receipts.len(), 2);
+        assert_eq!(result.receipts[0].cumulative_gas_used, 21_000);
+        assert_eq!(result.receipts[1].cumulative_gas_used, 21_000 + result.results[1].gas_used);
+        assert_eq!(result.gas_used, result.receipts[1].cumulative_gas_used);
+        assert!(result.receipts.iter().all(|receipt| receipt.success));
+
+        // Only the call to the logging contract contributes to the bloom
+        assert!(result.receipts[0].bloom.is_zero());
+        let mut topic = [0u8; 32];
+        topic[31] = 0xaa;
+        assert!(result.receipts[1].bloom.contains_input(logger().as_bytes()));
+        assert!(result.logs_bloom.contains_input(&topic));
+
+        assert_eq!(result.transactions_root, ordered_trie_root(transactions.iter().map(TxEnvelope::encode)));
+        assert_eq!(result.receipts_root, ordered_trie_root(result.receipts.iter().map(Receipt::encode)));
+        assert_ne!(result.receipts_root, crate::trie::EMPTY_ROOT);
+        assert_eq!(storage.get_nonce(&sender()), Uint256::from_u64(2));
+    }
+
+    #[test]
+    fn test_block_gas_limit_is_cumulative() {
+        let (mut storage, block) = test_state();
+        // Unused gas goes back to the block, so 50k + 60k fits in 100k...
+        let transactions = vec![signed_tx(0, logger(), 50_000), signed_tx(1, logger(), 60_000)];
+        let (mut fits, _) = test_state();
+        assert!(BlockExecutor::new(&mut fits, &block).execute(&transactions).is_ok());
+
+        // ...but the gas actually used still counts against later transactions
+        let transactions = vec![signed_tx(0, logger(), 50_000), signed_tx(1, logger(), 80_000)];
+        let error = BlockExecutor::new(&mut storage, &block).execute(&transactions).unwrap_err();
+        assert!(matches!(error, BlockError::GasLimitReached { index: 1, gas_limit: 80_000, .. }));
+        // The first transaction is rolled back with the rest of the block
+        assert_eq!(storage.get_nonce(&sender()), Uint256::zero());
+        assert_eq!(storage.get_balance(&sender()), Uint256::from_u64(ETHER));
+    }
+
+    #[test]
+    fn test_invalid_transaction_rejects_block() {
+        let (mut storage, block) = test_state();
+        let transactions = vec![signed_tx(0, logger(), 30_000), signed_tx(5, logger(), 30_000)];
+
+        let error = BlockExecutor::new(&mut storage, &block).execute(&transactions).unwrap_err();
+        assert!(matches!(
+            error,
+            BlockError::Transaction { index: 1, source: TransactionError::NonceMismatch { expected: 1, got: 5 } }
+        ));
+
+        let empty = BlockExecutor::new(&mut storage, &block).execute(&[]).unwrap();
+        assert_eq!(empty.gas_used, 0);
+        assert_eq!(empty.receipts_root, crate::trie::EMPTY_ROOT);
+        assert_eq!(empty.transactions_root, crate::trie::EMPTY_ROOT);
+    }
+}
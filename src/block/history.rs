@@ -0,0 +1,172 @@
+use crate::types::{Address, Hash, Uint256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+/// Number of recent blocks whose hashes BLOCKHASH can see
+pub const BLOCK_HASH_WINDOW: u64 = 256;
+
+/// System contract holding historical block hashes (EIP-2935)
+pub const HISTORY_STORAGE_ADDRESS: Address = Address([
+    0x00, 0x00, 0xf9, 0x08, 0x27, 0xf1, 0xc5, 0x3a, 0x10, 0xcb, 0x7a, 0x02, 0x33, 0x5b, 0x17, 0x53, 0x20, 0x00, 0x29,
+    0x35,
+]);
+
+/// Number of slots in the EIP-2935 ring buffer
+pub const HISTORY_SERVE_WINDOW: u64 = 8191;
+
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid block number {0:?}")]
+    InvalidNumber(String),
+    #[error("Invalid hash for block {number}: {reason}")]
+    InvalidHash { number: u64, reason: String },
+}
+
+/// Where block hashes are kept; BLOCKHASH reads the in-memory history in either mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BlockHashMode {
+    /// Only the in-memory history carried by the block context
+    #[default]
+    Recent,
+    /// Also the storage of the EIP-2935 history contract, written by a system call
+    HistoryContract,
+}
+
+/// A chain store that can supply hashes of past blocks
+pub trait BlockHashProvider {
+    /// Hash of block `number`, if known
+    fn block_hash(&self, number: u64) -> Option<Hash>;
+}
+
+impl<F: Fn(u64) -> Option<Hash>> BlockHashProvider for F {
+    fn block_hash(&self, number: u64) -> Option<Hash> {
+        self(number)
+    }
+}
+
+/// Hashes of past blocks, keyed by block number
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHashes {
+    hashes: BTreeMap<u64, Hash>,
+}
+
+impl BlockHashes {
+    /// Create an empty history
+    pub fn new() -> Self {
+        BlockHashes::default()
+    }
+
+    /// Fetch the hashes of the blocks BLOCKHASH can see from block `current`
+    pub fn from_provider(provider: &impl BlockHashProvider, current: u64) -> Self {
+        let mut history = BlockHashes::new();
+        for number in current.saturating_sub(BLOCK_HASH_WINDOW)..current {
+            if let Some(hash) = provider.block_hash(number) {
+                history.insert(number, hash);
+            }
+        }
+        history
+    }
+
+    /// Load a fixture: a JSON object mapping block numbers (decimal or `0x` hex) to hashes
+    pub fn load_json(json: &str) -> Result<Self, HistoryError> {
+        let entries: BTreeMap<String, String> = serde_json::from_str(json)?;
+        let mut history = BlockHashes::new();
+        for (number, hash) in entries {
+            let number = number
+                .parse::<Uint256>()
+                .ok()
+                .and_then(|number| number.to_u64_safe().ok())
+                .ok_or(HistoryError::InvalidNumber(number))?;
+            let hash = Hash::from_hex(&hash).map_err(|reason| HistoryError::InvalidHash { number, reason })?;
+            history.insert(number, hash);
+        }
+        Ok(history)
+    }
+
+    /// Load a fixture file in the `load_json` format
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, HistoryError> {
+        Self::load_json(&fs::read_to_string(path)?)
+    }
+
+    /// Record the hash of block `number`
+    pub fn insert(&mut self, number: u64, hash: Hash) {
+        self.hashes.insert(number, hash);
+    }
+
+    /// Hash of block `number`, if recorded
+    pub fn get(&self, number: u64) -> Option<Hash> {
+        self.hashes.get(&number).copied()
+    }
+
+    /// Recorded block numbers and hashes, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = (u64, Hash)> + '_ {
+        self.hashes.iter().map(|(&number, &hash)| (number, hash))
+    }
+
+    /// Highest recorded block number
+    pub fn latest(&self) -> Option<u64> {
+        self.hashes.keys().next_back().copied()
+    }
+
+    /// Number of recorded hashes
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Whether no hashes are recorded
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+}
+
+/// Whether BLOCKHASH in block `current` may return the hash of block `number`
+pub fn in_block_hash_window(number: &Uint256, current: &Uint256) -> bool {
+    number < current && *current - *number <= Uint256::from_u64(BLOCK_HASH_WINDOW)
+}
+
+/// EIP-2935 ring buffer slot holding the hash of block `number`
+pub fn history_storage_slot(number: u64) -> Uint256 {
+    Uint256::from_u64(number % HISTORY_SERVE_WINDOW)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_hash_window() {
+        let current = Uint256::from_u64(1000);
+        assert!(in_block_hash_window(&Uint256::from_u64(999), &current));
+        assert!(in_block_hash_window(&Uint256::from_u64(744), &current));
+        assert!(!in_block_hash_window(&Uint256::from_u64(743), &current));
+        assert!(!in_block_hash_window(&current, &current));
+        assert!(!in_block_hash_window(&Uint256::MAX, &current));
+    }
+
+    #[test]
+    fn test_history_from_provider_and_fixture() {
+        let provider = |number: u64| Some(Hash::new([number as u8; 32]));
+        let history = BlockHashes::from_provider(&provider, 300);
+        assert_eq!(history.len(), 256);
+        assert_eq!(history.get(43), None);
+        assert_eq!(history.get(44), Some(Hash::new([44; 32])));
+        assert_eq!(history.latest(), Some(299));
+
+        let fixture = format!(r#"{{ "7": "{}", "0x8": "{}" }}"#, Hash::new([7; 32]), Hash::new([8; 32]));
+        let history = BlockHashes::load_json(&fixture).unwrap();
+        assert_eq!(history.get(7), Some(Hash::new([7; 32])));
+        assert_eq!(history.get(8), Some(Hash::new([8; 32])));
+        assert!(matches!(BlockHashes::load_json(r#"{ "x": "0x00" }"#), Err(HistoryError::InvalidNumber(_))));
+        assert!(matches!(
+            BlockHashes::load_json(r#"{ "1": "0x00" }"#),
+            Err(HistoryError::InvalidHash { number: 1, .. })
+        ));
+    }
+}
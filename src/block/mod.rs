@@ -1,7 +1,19 @@
-use crate::types::{Address, Uint256};
+use crate::{
+    types::{Address, Hash, Uint256},
+    storage::Storage,
+    hardfork::Hardfork,
+};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+pub mod executor;
+pub mod history;
+pub mod receipt;
+
+pub use executor::*;
+pub use history::*;
+pub use receipt::*;
+
 /// Block context containing blockchain information
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BlockContext {
@@ -17,10 +29,12 @@ pub struct BlockContext {
     pub coinbase: Address,
     /// Chain ID
     pub chain_id: Uint256,
-    /// Block hash (for the previous block)
-    pub block_hash: Uint256,
+    /// Hashes of recent blocks, served by BLOCKHASH
+    pub block_hashes: BlockHashes,
     /// Base fee (for EIP-1559)
     pub base_fee: Uint256,
+    /// Blob base fee (for EIP-4844)
+    pub blob_base_fee: Uint256,
 }
 
 impl BlockContext {
@@ -33,12 +47,17 @@ impl BlockContext {
             gas_limit: Uint256::from_u32(30000000), // 30M gas
             coinbase: Address::zero(),
             chain_id: Uint256::from_u32(1), // Mainnet
-            block_hash: Uint256::zero(),
+            block_hashes: BlockHashes::new(),
             base_fee: Uint256::from_u64(20_000_000_000), // 20 gwei
+            blob_base_fee: Uint256::one(), // Minimum blob base fee
         }
     }
 
     /// Create a block context with custom values
+    ///
+    /// `block_hash` is the parent block's hash, the only one BLOCKHASH can then
+    /// return; use `with_history` to supply the full 256-block history.
+    #[allow(clippy::too_many_arguments)]
     pub fn with_values(
         number: Uint256,
         timestamp: Uint256,
@@ -48,6 +67,25 @@ impl BlockContext {
         chain_id: Uint256,
         block_hash: Uint256,
         base_fee: Uint256,
+    ) -> Self {
+        let mut block_hashes = BlockHashes::new();
+        if let Some(parent) = number.to_u64_safe().ok().and_then(|number| number.checked_sub(1)) {
+            block_hashes.insert(parent, Hash::new(block_hash.to_bytes_be()));
+        }
+        BlockContext::with_history(number, timestamp, difficulty, gas_limit, coinbase, chain_id, block_hashes, base_fee)
+    }
+
+    /// Create a block context with custom values and a history of recent block hashes
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_history(
+        number: Uint256,
+        timestamp: Uint256,
+        difficulty: Uint256,
+        gas_limit: Uint256,
+        coinbase: Address,
+        chain_id: Uint256,
+        block_hashes: BlockHashes,
+        base_fee: Uint256,
     ) -> Self {
         BlockContext {
             number,
@@ -56,31 +94,81 @@ impl BlockContext {
             gas_limit,
             coinbase,
             chain_id,
-            block_hash,
+            block_hashes,
             base_fee,
+            blob_base_fee: Uint256::one(),
         }
     }
 
-    /// Get block hash for a given block number
-    /// In a real implementation, this would query the blockchain
+    /// Hash of block `block_number` from the in-memory history, as BLOCKHASH returns it
+    ///
+    /// Every fork reads this history; the EIP-2935 contract does not change BLOCKHASH.
+    /// Zero for the current block, future blocks, blocks more than 256 back and
+    /// blocks missing from the history.
     pub fn get_block_hash(&self, block_number: &Uint256) -> Uint256 {
-        // For demo purposes, return a deterministic hash
-        if block_number == &self.number {
-            self.block_hash.clone()
-        } else {
-            // Generate a deterministic hash based on block number
-            let mut hash_bytes = [0u8; 32];
-            let block_bytes = block_number.to_bytes_be();
-            for (i, &byte) in block_bytes.iter().enumerate() {
-                if i < 32 {
-                    hash_bytes[i] = byte;
-                }
-            }
-            Uint256::from_bytes_be(&hash_bytes)
+        if !in_block_hash_window(block_number, &self.number) {
+            return Uint256::zero();
+        }
+        block_number
+            .to_u64_safe()
+            .ok()
+            .and_then(|number| self.block_hashes.get(number))
+            .map(|hash| Uint256::from_bytes_be(hash.as_bytes()))
+            .unwrap_or_default()
+    }
+
+    /// Hash of the parent block, formerly the `block_hash` field
+    ///
+    /// Zero for the genesis block or when the parent is missing from the history.
+    #[deprecated(note = "the parent hash is now the latest entry of `block_hashes`; use `get_block_hash`")]
+    pub fn block_hash(&self) -> Uint256 {
+        self.number
+            .to_u64_safe()
+            .ok()
+            .and_then(|number| number.checked_sub(1))
+            .map(|parent| self.get_block_hash(&Uint256::from_u64(parent)))
+            .unwrap_or_default()
+    }
+
+    /// Write the parent block's hash into the EIP-2935 history contract
+    ///
+    /// Runs at the start of the block from Prague; a no-op before, when the
+    /// contract has not been deployed, for the genesis block, for block numbers
+    /// that do not fit in 64 bits, or when the parent hash is unknown.
+    pub fn store_parent_hash(&self, storage: &mut Storage, fork: Hardfork) {
+        if !history_contract_active(storage, fork) {
+            return;
+        }
+        let Some(parent) = self.number.to_u64_safe().ok().and_then(|number| number.checked_sub(1)) else {
+            return;
+        };
+        if let Some(hash) = self.block_hashes.get(parent) {
+            let value = Uint256::from_bytes_be(hash.as_bytes());
+            storage.set_storage(HISTORY_STORAGE_ADDRESS, history_storage_slot(parent), value);
+        }
+    }
+
+    /// Write every known hash into the EIP-2935 history contract
+    ///
+    /// Seeds the contract as if the recorded blocks had run under Prague; a
+    /// no-op before Prague or when the contract has not been deployed.
+    pub fn store_history(&self, storage: &mut Storage, fork: Hardfork) {
+        if !history_contract_active(storage, fork) {
+            return;
+        }
+        for (number, hash) in self.block_hashes.iter() {
+            let value = Uint256::from_bytes_be(hash.as_bytes());
+            storage.set_storage(HISTORY_STORAGE_ADDRESS, history_storage_slot(number), value);
         }
     }
 }
 
+/// Whether the EIP-2935 system call runs: from Prague, and only once the
+/// contract's code is deployed, as with any system call to an empty account
+fn history_contract_active(storage: &Storage, fork: Hardfork) -> bool {
+    fork.block_hash_mode() == BlockHashMode::HistoryContract && !storage.get_code(&HISTORY_STORAGE_ADDRESS).is_empty()
+}
+
 impl Default for BlockContext {
     fn default() -> Self {
         BlockContext::new()
@@ -107,6 +195,8 @@ pub struct TransactionContext {
     pub tx_hash: Uint256,
     /// Transaction nonce
     pub nonce: Uint256,
+    /// Versioned hashes of the blobs carried by the transaction (EIP-4844)
+    pub blob_hashes: Vec<Hash>,
 }
 
 impl TransactionContext {
@@ -118,6 +208,7 @@ impl TransactionContext {
             gas_limit: Uint256::from_u32(1000000), // 1M gas
             tx_hash: Uint256::zero(),
             nonce: Uint256::zero(),
+            blob_hashes: Vec::new(),
         }
     }
 
@@ -135,6 +226,7 @@ impl TransactionContext {
             gas_limit,
             tx_hash,
             nonce,
+            blob_hashes: Vec::new(),
         }
     }
 }
@@ -166,14 +258,85 @@ mod tests {
     #[test]
     fn test_transaction_context_creation() {
         let tx = TransactionContext::new();
-        assert_eq!(tx.gas_price, Uint256::from_u32(20_000_000_000));
+        assert_eq!(tx.gas_price, Uint256::from_u64(20_000_000_000));
         assert_eq!(tx.origin, Address::zero());
     }
 
     #[test]
-    fn test_block_hash_generation() {
-        let block = BlockContext::new();
-        let hash = block.get_block_hash(&Uint256::from_u32(1));
-        assert_eq!(hash, block.block_hash);
+    fn test_block_hash_lookup() {
+        let mut block = BlockContext::new();
+        block.number = Uint256::from_u64(300);
+        block.block_hashes.insert(299, Hash::new([0x99; 32]));
+        block.block_hashes.insert(10, Hash::new([0x10; 32]));
+
+        assert_eq!(block.get_block_hash(&Uint256::from_u64(299)), Uint256::from_bytes_be(&[0x99; 32]));
+        // Outside the 256-block window, the current block, and unknown blocks are all zero
+        assert_eq!(block.get_block_hash(&Uint256::from_u64(10)), Uint256::zero());
+        assert_eq!(block.get_block_hash(&Uint256::from_u64(300)), Uint256::zero());
+        assert_eq!(block.get_block_hash(&Uint256::from_u64(298)), Uint256::zero());
+    }
+
+    #[test]
+    fn test_with_values_keeps_parent_hash() {
+        let parent_hash = Uint256::from_bytes_be(&[0x77; 32]);
+        let block = BlockContext::with_values(
+            Uint256::from_u64(10),
+            Uint256::from_u64(1_700_000_000),
+            Uint256::zero(),
+            Uint256::from_u64(30_000_000),
+            Address::zero(),
+            Uint256::one(),
+            parent_hash,
+            Uint256::from_u64(7),
+        );
+        assert_eq!(block.get_block_hash(&Uint256::from_u64(9)), parent_hash);
+        assert_eq!(block.block_hashes.len(), 1);
+        #[allow(deprecated)]
+        let legacy_hash = block.block_hash();
+        assert_eq!(legacy_hash, parent_hash);
+
+        // A block number beyond 64 bits has no parent entry to write
+        let mut storage = history_storage();
+        let mut block = block;
+        block.number = Uint256::from_u64(u64::MAX) + Uint256::from_u64(2);
+        block.store_parent_hash(&mut storage, Hardfork::Prague);
+        assert!(!storage.has_storage(&HISTORY_STORAGE_ADDRESS));
+    }
+
+    /// State with the EIP-2935 contract deployed; its code is never run here
+    fn history_storage() -> Storage {
+        let mut storage = Storage::new();
+        storage.set_code(HISTORY_STORAGE_ADDRESS, vec![0x00]);
+        storage.set_nonce(HISTORY_STORAGE_ADDRESS, Uint256::one());
+        storage
+    }
+
+    #[test]
+    fn test_history_contract_mode() {
+        let mut block = BlockContext::new();
+        block.number = Uint256::from_u64(8200);
+        block.block_hashes.insert(8199, Hash::new([0x42; 32]));
+
+        // Nothing is written while the contract is not deployed
+        let mut storage = Storage::new();
+        block.store_parent_hash(&mut storage, Hardfork::Prague);
+        block.store_history(&mut storage, Hardfork::Prague);
+        assert!(!storage.account_exists(&HISTORY_STORAGE_ADDRESS));
+
+        let mut storage = history_storage();
+
+        // Only written from Prague
+        block.store_parent_hash(&mut storage, Hardfork::Cancun);
+        assert_eq!(storage.get_storage(&HISTORY_STORAGE_ADDRESS, &Uint256::from_u64(8)), Uint256::zero());
+
+        block.store_parent_hash(&mut storage, Hardfork::Prague);
+        let expected = Uint256::from_bytes_be(&[0x42; 32]);
+        assert_eq!(storage.get_storage(&HISTORY_STORAGE_ADDRESS, &Uint256::from_u64(8)), expected);
+
+        // A fixture can seed older entries of the ring buffer
+        block.block_hashes.insert(8000, Hash::new([0x80; 32]));
+        block.store_history(&mut storage, Hardfork::Prague);
+        let expected = Uint256::from_bytes_be(&[0x80; 32]);
+        assert_eq!(storage.get_storage(&HISTORY_STORAGE_ADDRESS, &history_storage_slot(8000)), expected);
     }
 }
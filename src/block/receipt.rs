@@ -0,0 +1,175 @@
+use crate::{
+    events::EventLog,
+    rlp::{self, Encodable},
+};
+use sha3::{Digest, Keccak256};
+use std::fmt;
+use std::ops::BitOrAssign;
+
+/// Size of a logs bloom filter in bytes
+pub const BLOOM_SIZE: usize = 256;
+
+/// 2048-bit bloom filter over log addresses and topics
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Bloom(pub [u8; BLOOM_SIZE]);
+
+impl Bloom {
+    /// An empty filter
+    pub fn zero() -> Self {
+        Bloom([0u8; BLOOM_SIZE])
+    }
+
+    /// Get the raw bytes
+    pub fn as_bytes(&self) -> &[u8; BLOOM_SIZE] {
+        &self.0
+    }
+
+    /// Whether no bits are set
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|&byte| byte == 0)
+    }
+
+    /// Bits set for `input`: three 11-bit indices taken from its keccak256 hash
+    fn bits(input: &[u8]) -> [(usize, u8); 3] {
+        let hash = Keccak256::digest(input);
+        [0, 2, 4].map(|i| {
+            let bit = (usize::from(hash[i]) << 8 | usize::from(hash[i + 1])) & 0x7ff;
+            (BLOOM_SIZE - 1 - bit / 8, 1 << (bit % 8))
+        })
+    }
+
+    /// Add `input` to the filter
+    pub fn accrue(&mut self, input: &[u8]) {
+        for (byte, mask) in Self::bits(input) {
+            self.0[byte] |= mask;
+        }
+    }
+
+    /// Add a log's address and topics to the filter
+    pub fn accrue_log(&mut self, log: &EventLog) {
+        self.accrue(log.address.as_bytes());
+        for topic in &log.topics {
+            self.accrue(topic.as_bytes());
+        }
+    }
+
+    /// Whether `input` may have been added; false positives are possible
+    pub fn contains_input(&self, input: &[u8]) -> bool {
+        Self::bits(input).iter().all(|&(byte, mask)| self.0[byte] & mask != 0)
+    }
+
+    /// Build the filter for a list of logs
+    pub fn from_logs(logs: &[EventLog]) -> Self {
+        let mut bloom = Bloom::zero();
+        for log in logs {
+            bloom.accrue_log(log);
+        }
+        bloom
+    }
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom::zero()
+    }
+}
+
+impl BitOrAssign for Bloom {
+    fn bitor_assign(&mut self, other: Bloom) {
+        for (byte, other) in self.0.iter_mut().zip(other.0) {
+            *byte |= other;
+        }
+    }
+}
+
+impl fmt::Debug for Bloom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Bloom(0x{})", hex::encode(self.0))
+    }
+}
+
+impl Encodable for Bloom {
+    fn encode(&self, out: &mut Vec<u8>) {
+        rlp::encode_bytes(&self.0, out);
+    }
+}
+
+impl Encodable for EventLog {
+    fn encode(&self, out: &mut Vec<u8>) {
+        rlp::encode_list(&[&self.address, &self.topics, &self.data], out);
+    }
+}
+
+/// Consensus receipt of a transaction included in a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    /// EIP-2718 type of the transaction
+    pub tx_type: u8,
+    /// Whether the transaction succeeded (EIP-658)
+    pub success: bool,
+    /// Gas used by this and every earlier transaction in the block
+    pub cumulative_gas_used: u64,
+    /// Logs emitted by the transaction
+    pub logs: Vec<EventLog>,
+    /// Bloom filter over `logs`
+    pub bloom: Bloom,
+}
+
+impl Receipt {
+    /// Create a receipt, computing the bloom from the logs
+    pub fn new(tx_type: u8, success: bool, cumulative_gas_used: u64, logs: Vec<EventLog>) -> Self {
+        let bloom = Bloom::from_logs(&logs);
+        Receipt {
+            tx_type,
+            success,
+            cumulative_gas_used,
+            logs,
+            bloom,
+        }
+    }
+
+    /// Encode as EIP-2718 bytes: `rlp(receipt)` for legacy, `type || rlp(receipt)` otherwise
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        if self.tx_type != 0 {
+            encoded.push(self.tx_type);
+        }
+        rlp::encode_list(&[&self.success, &self.cumulative_gas_used, &self.bloom, &self.logs], &mut encoded);
+        encoded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Address, Bytes, Hash};
+
+    #[test]
+    fn test_bloom_accrue() {
+        let address = Address::from_hex("0x00000000000000000000000000000000000000aa").unwrap();
+        let topic = Hash::new([0x11; 32]);
+        let bloom = Bloom::from_logs(&[EventLog::new(address, vec![topic], Bytes::empty())]);
+
+        assert!(bloom.contains_input(address.as_bytes()));
+        assert!(bloom.contains_input(topic.as_bytes()));
+        // Each input sets at most three bits
+        let set_bits: u32 = bloom.0.iter().map(|byte| byte.count_ones()).sum();
+        assert!((2..=6).contains(&set_bits));
+        assert!(Bloom::zero().is_zero());
+    }
+
+    #[test]
+    fn test_receipt_encoding() {
+        let receipt = Receipt::new(0, true, 21_000, Vec::new());
+        let encoded = receipt.encode();
+        // [0x01, 0x825208, <256 zero bytes>, []]
+        assert_eq!(&encoded[..3], &[0xf9, 0x01, 0x08]);
+        assert_eq!(&encoded[3..8], &[0x01, 0x82, 0x52, 0x08, 0xb9]);
+        assert_eq!(encoded.last(), Some(&0xc0));
+        assert_eq!(encoded.len(), 3 + 0x108);
+
+        let typed = Receipt::new(2, false, 21_000, Vec::new()).encode();
+        assert_eq!(typed[0], 2);
+        assert_eq!(typed[4], 0x80);
+    }
+}
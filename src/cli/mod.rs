@@ -1,14 +1,18 @@
 use clap::{Parser, Subcommand};
 use crate::{
     types::{Address, Uint256, Bytes},
-    executor::{Executor, ExecutionContext},
+    executor::{Executor, ExecutionContext, ExecutionOutcome, ExecutionResult},
     validation::Validator,
     tracing::ExecutionTracer,
     advanced::{AdvancedEVM, GasOptimization, ContractAnalyzer},
+    opcodes::OPCODE_TABLE,
+    storage::{FileBackend, Storage},
+    block::{BlockContext, BlockHashes},
+    gas::GasCosts,
+    hardfork::Hardfork,
+    transaction::{TransactionProcessor, TxEnvelope},
 };
-use std::str::FromStr;
-use num_bigint::BigUint;
-use num_traits::Num;
+use std::collections::BTreeMap;
 
 /// Ethereum Virtual Machine in Rust - Command Line Interface
 #[derive(Parser)]
@@ -81,6 +85,57 @@ pub enum Commands {
         /// Export execution trace to file (JSON/CSV format)
         #[arg(long)]
         export_trace: Option<String>,
+        
+        /// State file to load accounts from and save them back to
+        #[arg(long)]
+        db: Option<String>,
+        
+        /// Genesis `alloc` JSON file to seed accounts from before execution
+        #[arg(long)]
+        state: Option<String>,
+        
+        /// Write the post-execution state to this file as genesis `alloc` JSON
+        #[arg(long)]
+        dump_state: Option<String>,
+        
+        /// JSON fixture mapping block numbers to hashes, served by BLOCKHASH
+        #[arg(long)]
+        block_hashes: Option<String>,
+        
+        /// Number of the executing block (defaults to one past the latest fixture hash)
+        #[arg(long)]
+        block_number: Option<u64>,
+        
+        /// Hardfork whose gas schedule and rules apply (e.g. "berlin", "tangerine-whistle")
+        #[arg(long, default_value = "cancun")]
+        fork: Hardfork,
+    },
+    
+    /// Apply a signed raw transaction; the sender is recovered from its signature
+    Transaction {
+        /// Hex-encoded EIP-2718 transaction (legacy RLP or typed)
+        #[arg(short, long)]
+        raw: String,
+        
+        /// Hex-encoded code to install at the recipient address
+        #[arg(short, long)]
+        code: Option<String>,
+        
+        /// Chain id of the block the transaction is applied in
+        #[arg(long, default_value = "1")]
+        chain_id: u64,
+        
+        /// Add enough balance to the sender to apply the transaction and set the right nonce
+        #[arg(long)]
+        fund: bool,
+        
+        /// State file to load accounts from and save them back to
+        #[arg(long)]
+        db: Option<String>,
+        
+        /// Hardfork whose gas schedule and rules apply (e.g. "berlin", "tangerine-whistle")
+        #[arg(long, default_value = "cancun")]
+        fork: Hardfork,
     },
     
     /// Run predefined examples
@@ -195,9 +250,18 @@ impl Cli {
                 input,
                 no_validate,
                 detailed_trace,
-                export_trace
+                export_trace,
+                db,
+                state,
+                dump_state,
+                block_hashes,
+                block_number,
+                fork
             } => {
-                Self::execute_bytecode_static(code, gas_limit, debug, trace, caller, address, value, input, no_validate, detailed_trace, export_trace)
+                Self::execute_bytecode_static(code, gas_limit, debug, trace, caller, address, value, input, no_validate, detailed_trace, export_trace, db, state, dump_state, block_hashes, block_number, fork)
+            }
+            Commands::Transaction { raw, code, chain_id, fund, db, fork } => {
+                Self::apply_transaction_static(raw, code, chain_id, fund, db, fork)
             }
             Commands::Examples { number, list } => {
                 Self::run_examples_static(number, list)
@@ -215,6 +279,7 @@ impl Cli {
     }
     
     /// Execute bytecode
+    #[allow(clippy::too_many_arguments)]
     fn execute_bytecode_static(
         code: String,
         gas_limit: u64,
@@ -227,16 +292,22 @@ impl Cli {
         no_validate: bool,
         detailed_trace: bool,
         export_trace: Option<String>,
+        db: Option<String>,
+        state: Option<String>,
+        dump_state: Option<String>,
+        block_hashes: Option<String>,
+        block_number: Option<u64>,
+        fork: Hardfork,
     ) -> Result<(), Box<dyn std::error::Error>> {
         println!("🚀 EVM Execution");
         println!("===============");
         
         // Create validator
-        let validator = Validator::new();
+        let validator = Validator::for_fork(fork);
         
         // Parse and validate hex code
-        let code_bytes = if code.starts_with("0x") {
-            hex::decode(&code[2..])?
+        let code_bytes = if let Some(stripped) = code.strip_prefix("0x") {
+            hex::decode(stripped)?
         } else {
             hex::decode(&code)?
         };
@@ -262,13 +333,7 @@ impl Cli {
         
         // Parse and validate value
         let call_value = if no_validate {
-            if value.starts_with("0x") {
-                let big_uint = BigUint::from_str_radix(&value[2..], 16)?;
-                Uint256::new(big_uint)
-            } else {
-                let big_uint = BigUint::from_str(&value)?;
-                Uint256::new(big_uint)
-            }
+            value.parse::<Uint256>()?
         } else {
             validator.validate_value(&value)?
         };
@@ -277,8 +342,8 @@ impl Cli {
         let input_data = if no_validate {
             if input.is_empty() {
                 Bytes::empty()
-            } else if input.starts_with("0x") {
-                Bytes::from(hex::decode(&input[2..])?)
+            } else if let Some(stripped) = input.strip_prefix("0x") {
+                Bytes::from(hex::decode(stripped)?)
             } else {
                 Bytes::from(hex::decode(&input)?)
             }
@@ -297,12 +362,13 @@ impl Cli {
             println!("  Caller: {}", caller_addr);
             println!("  Value: {} wei", call_value);
             println!("  Gas Limit: {}", gas_limit);
+            println!("  Hardfork: {}", fork);
             println!("  Code Size: {} bytes", code_bytes.len());
             println!();
         }
         
         // Create execution context
-        let context = ExecutionContext::new(
+        let mut context = ExecutionContext::new(
             contract_addr,
             caller_addr,
             call_value,
@@ -310,24 +376,60 @@ impl Cli {
             Bytes::from(code_bytes),
             gas_limit,
         );
+        context.set_fork(fork);
+        context.set_memory_limit(validator.max_memory_size());
+        if let Some(db) = &db {
+            context.storage = Storage::with_backend(FileBackend::open(db)?);
+        }
+        if let Some(state) = &state {
+            context.storage.load_alloc_file(state)?;
+            println!("📥 Loaded {} accounts from: {}", context.storage.account_count(), state);
+        }
+        if let Some(block_hashes) = &block_hashes {
+            let history = BlockHashes::load_file(block_hashes)?;
+            if let Some(latest) = history.latest() {
+                context.block_context.number = Uint256::from_u64(latest + 1);
+            }
+            println!("📥 Loaded {} block hashes from: {}", history.len(), block_hashes);
+            context.block_context.block_hashes = history;
+        }
+        if let Some(block_number) = block_number {
+            context.block_context.number = Uint256::from_u64(block_number);
+        }
+        // From Prague BLOCKHASH reads the EIP-2935 history contract
+        context.block_context.store_history(&mut context.storage, fork);
          
         // Execute with or without tracer
-        let (result, execution_trace) = if detailed_trace || export_trace.is_some() {
+        let (result, execution_trace, mut storage) = if detailed_trace || export_trace.is_some() {
             let tracer = ExecutionTracer::new();
             let mut executor = Executor::new_with_tracer(context, tracer);
             let result = executor.execute()?;
+            executor.apply_selfdestructs();
             let tracer = executor.take_tracer().unwrap();
             let execution_trace = tracer.finalize(result.success, result.gas_used);
-            (result, Some(execution_trace))
+            (result, Some(execution_trace), executor.into_context().storage)
         } else {
             let mut executor = Executor::new(context);
             let result = executor.execute()?;
-            (result, None)
+            executor.apply_selfdestructs();
+            (result, None, executor.into_context().storage)
         };
         
+        if let Some(db) = &db {
+            storage.flush()?;
+            println!("💾 State saved to: {}", db);
+        }
+        if let Some(dump_state) = &dump_state {
+            storage.dump_alloc_file(dump_state)?;
+            println!("📤 State dumped to: {}", dump_state);
+        }
+        
         // Display results
         println!("📊 Execution Results:");
         println!("  Success: {}", result.success);
+        if let ExecutionOutcome::Halt(reason) = &result.outcome {
+            println!("  Halt Reason: {}", reason);
+        }
         println!("  Gas Used: {}", result.gas_used);
         println!("  Gas Remaining: {}", result.gas_remaining);
         
@@ -379,6 +481,76 @@ impl Cli {
         Ok(())
     }
     
+    /// Apply a signed raw transaction to an empty or saved state
+    fn apply_transaction_static(
+        raw: String,
+        code: Option<String>,
+        chain_id: u64,
+        fund: bool,
+        db: Option<String>,
+        fork: Hardfork,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        println!("🧾 Transaction Execution");
+        println!("=======================");
+        
+        let raw_bytes = hex::decode(raw.strip_prefix("0x").unwrap_or(&raw))?;
+        let envelope = TxEnvelope::decode(&raw_bytes)?;
+        envelope.check_chain_id(chain_id)?;
+        let sender = envelope.recover_sender()?;
+        
+        let mut block = BlockContext::new();
+        block.chain_id = Uint256::from_u64(chain_id);
+        let mut storage = match &db {
+            Some(db) => Storage::with_backend(FileBackend::open(db)?),
+            None => Storage::new(),
+        };
+        if let (Some(code), Some(to)) = (code, envelope.to()) {
+            storage.set_code(to, hex::decode(code.strip_prefix("0x").unwrap_or(&code))?);
+        }
+        
+        let tx = envelope.clone().into_transaction(sender);
+        if fund {
+            let max_cost = Uint256::from_u64(tx.gas_limit) * tx.max_fee_per_gas
+                + Uint256::from_u64(tx.blob_gas(&GasCosts::for_fork(fork))) * tx.max_fee_per_blob_gas;
+            storage.add_balance(sender, max_cost + tx.value);
+            storage.set_nonce(sender, Uint256::from_u64(tx.nonce));
+        }
+        
+        println!("  Hardfork: {}", fork);
+        println!("  Type: 0x{:02x}", envelope.tx_type());
+        println!("  Hash: {}", envelope.tx_hash());
+        println!("  Sender: {}", sender);
+        match envelope.to() {
+            Some(to) => println!("  To: {}", to),
+            None => println!("  To: (contract creation)"),
+        }
+        
+        let result = TransactionProcessor::new(&mut storage, &block)
+            .with_fork(fork)
+            .execute(&tx)?;
+        println!("\n📊 Transaction Results:");
+        println!("  Outcome: {}", result.outcome);
+        println!("  Gas Used: {}", result.gas_used);
+        println!("  Gas Refunded: {}", result.gas_refunded);
+        println!("  Effective Gas Price: {}", result.effective_gas_price);
+        if let Some(address) = result.contract_address {
+            println!("  Contract Address: {}", address);
+        }
+        if !result.return_data.is_empty() {
+            println!("  Return Data: 0x{}", hex::encode(result.return_data.as_slice()));
+        }
+        if !result.logs.is_empty() {
+            println!("  Event Logs: {}", result.logs.len());
+        }
+        
+        if let Some(db) = &db {
+            storage.flush()?;
+            println!("\n💾 State saved to: {}", db);
+        }
+        
+        Ok(())
+    }
+    
     /// Run examples
     fn run_examples_static(number: Option<u8>, list: bool) -> Result<(), Box<dyn std::error::Error>> {
         if list {
@@ -765,8 +937,8 @@ impl Cli {
         }
         
         // Parse bytecode
-        let code_bytes = if code.starts_with("0x") {
-            hex::decode(&code[2..])?
+        let code_bytes = if let Some(stripped) = code.strip_prefix("0x") {
+            hex::decode(stripped)?
         } else {
             hex::decode(code)?
         };
@@ -787,7 +959,7 @@ impl Cli {
         
         // Display results
         println!("📊 Execution Results:");
-        println!("  Success: {}", result.success);
+        println!("  Outcome: {}", result.outcome);
         println!("  Gas Used: {}", result.gas_used);
         println!("  Gas Remaining: {}", result.gas_remaining);
         
@@ -809,8 +981,8 @@ impl Cli {
             return Ok(());
         }
         
-        let bytecode = if code.starts_with("0x") {
-            hex::decode(&code[2..])?
+        let bytecode = if let Some(stripped) = code.strip_prefix("0x") {
+            hex::decode(stripped)?
         } else {
             hex::decode(code)?
         };
@@ -829,8 +1001,8 @@ impl Cli {
             return Ok(());
         }
         
-        let bytecode = if code.starts_with("0x") {
-            hex::decode(&code[2..])?
+        let bytecode = if let Some(stripped) = code.strip_prefix("0x") {
+            hex::decode(stripped)?
         } else {
             hex::decode(code)?
         };
@@ -877,18 +1049,14 @@ impl Cli {
     fn show_opcodes() {
         println!("📋 Supported Opcodes:");
         println!("====================");
-        println!("Arithmetic: ADD, SUB, MUL, DIV, MOD, EXP, SDIV, SMOD, ADDMOD, MULMOD, SIGNEXTEND");
-        println!("Comparison: LT, GT, SLT, SGT, EQ, ISZERO");
-        println!("Bitwise: AND, OR, XOR, NOT, BYTE, SHL, SHR");
-        println!("Cryptographic: SHA3");
-        println!("Stack: PUSH1-32, POP, DUP1-16, SWAP1-16");
-        println!("Memory: MLOAD, MSTORE, MSTORE8, MSIZE");
-        println!("Storage: SLOAD, SSTORE");
-        println!("Environmental: ADDRESS, CALLER, CALLVALUE, BALANCE, etc.");
-        println!("Block Info: TIMESTAMP, NUMBER, CHAINID, COINBASE, etc.");
-        println!("Logging: LOG0, LOG1, LOG2, LOG3, LOG4");
-        println!("Control Flow: JUMP, JUMPI, PC, JUMPDEST");
-        println!("System: STOP, RETURN, REVERT");
+        println!("{:<6} {:<16} {:>3} {:>3} {:>5}  Since", "Byte", "Mnemonic", "In", "Out", "Gas");
+        for info in OPCODE_TABLE.iter().flatten() {
+            println!(
+                "0x{:02x}   {:<16} {:>3} {:>3} {:>5}  {}",
+                info.opcode as u8, info.mnemonic, info.inputs, info.outputs, info.base_gas, info.introduced
+            );
+        }
+        println!("Total: {} opcodes", OPCODE_TABLE.iter().flatten().count());
     }
     
     /// Show gas costs
@@ -940,8 +1108,8 @@ impl Cli {
         println!("========================");
         
         // Read input bytecode
-        let bytecode = if input.starts_with("0x") {
-            hex::decode(&input[2..])?
+        let bytecode = if let Some(stripped) = input.strip_prefix("0x") {
+            hex::decode(stripped)?
         } else {
             hex::decode(&input)?
         };
@@ -980,8 +1148,8 @@ impl Cli {
         println!("===================");
         
         // Parse bytecode
-        let bytecode = if code.starts_with("0x") {
-            hex::decode(&code[2..])?
+        let bytecode = if let Some(stripped) = code.strip_prefix("0x") {
+            hex::decode(stripped)?
         } else {
             hex::decode(&code)?
         };
@@ -1025,8 +1193,8 @@ impl Cli {
         println!("========================");
         
         // Parse bytecode
-        let bytecode = if code.starts_with("0x") {
-            hex::decode(&code[2..])?
+        let bytecode = if let Some(stripped) = code.strip_prefix("0x") {
+            hex::decode(stripped)?
         } else {
             hex::decode(&code)?
         };
@@ -1039,8 +1207,10 @@ impl Cli {
         // Run benchmark
         let mut total_time = 0u64;
         let mut successful_executions = 0;
+        let mut failure_reasons: BTreeMap<String, usize> = BTreeMap::new();
         
         for i in 0..iterations {
+            let mut internal_error = None;
             let metrics = advanced_evm.monitor_execution(|| {
                 let context = ExecutionContext::new(
                     Address::zero(),
@@ -1052,18 +1222,28 @@ impl Cli {
                 );
                 
                 let mut executor = Executor::new(context);
-                executor.execute().unwrap_or_else(|_| crate::executor::ExecutionResult {
-                    success: false,
-                    gas_used: 0,
-                    gas_remaining: gas_limit,
-                    return_data: Bytes::empty(),
-                    logs: vec![],
+                executor.execute().unwrap_or_else(|e| {
+                    internal_error = Some(e);
+                    ExecutionResult {
+                        success: false,
+                        outcome: ExecutionOutcome::Revert(Bytes::empty()),
+                        gas_used: 0,
+                        gas_remaining: gas_limit,
+                        gas_refund: 0,
+                        return_data: Bytes::empty(),
+                        logs: vec![],
+                    }
                 })
             });
+            if let Some(e) = internal_error {
+                return Err(e.into());
+            }
             
             total_time += metrics.execution_time_us;
             if metrics.success {
                 successful_executions += 1;
+            } else {
+                *failure_reasons.entry(metrics.outcome.to_string()).or_default() += 1;
             }
             
             if (i + 1) % 10 == 0 {
@@ -1077,6 +1257,9 @@ impl Cli {
         println!("  Total Iterations: {}", iterations);
         println!("  Successful: {}", successful_executions);
         println!("  Failed: {}", iterations - successful_executions);
+        for (reason, count) in &failure_reasons {
+            println!("    {}: {}", reason, count);
+        }
         println!("  Average Time: {:.2}μs", total_time as f64 / iterations as f64);
         println!("  Total Time: {:.2}ms", total_time as f64 / 1000.0);
         
@@ -159,7 +159,7 @@ impl GasAnalyzer {
 
     /// Record gas usage for an opcode
     pub fn record_gas_usage(&mut self, opcode: Opcode, gas_used: u64) {
-        self.opcode_gas.entry(opcode).or_insert_with(Vec::new).push(gas_used);
+        self.opcode_gas.entry(opcode).or_default().push(gas_used);
         self.total_gas += gas_used;
     }
 
@@ -190,6 +190,12 @@ impl GasAnalyzer {
     }
 }
 
+impl Default for GasAnalyzer {
+    fn default() -> Self {
+        GasAnalyzer::new()
+    }
+}
+
 /// Gas usage statistics for an opcode
 #[derive(Debug, Clone)]
 pub struct OpcodeGasStats {
@@ -220,7 +226,7 @@ impl fmt::Display for GasStats {
         writeln!(f, "Opcode Statistics:")?;
         
         let mut sorted_stats: Vec<_> = self.opcode_stats.iter().collect();
-        sorted_stats.sort_by(|a, b| b.1.total.cmp(&a.1.total));
+        sorted_stats.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.total));
         
         for (opcode, stats) in sorted_stats {
             writeln!(f, "  {:?}: {} executions, {} total gas, {} avg gas", 
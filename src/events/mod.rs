@@ -65,6 +65,11 @@ impl EventLogger {
         self.logs.len()
     }
 
+    /// Drop every log emitted after the first `count`, undoing a reverted frame
+    pub fn truncate(&mut self, count: usize) {
+        self.logs.truncate(count);
+    }
+
     /// Get logs for a specific address
     pub fn logs_for_address(&self, address: &Address) -> Vec<&EventLog> {
         self.logs.iter()
@@ -2,15 +2,25 @@ use crate::{
     types::{Address, Uint256, Bytes, Hash},
     stack::{Stack, StackError},
     memory::{Memory, MemoryError},
-    storage::{Storage, StorageError},
-    gas::{GasMeter, GasError},
+    storage::{Checkpoint, Storage, StorageError},
+    gas::{GasCosts, GasMeter, GasError},
     opcodes::{Opcode, OpcodeError},
     events::{EventLogger, EventLog},
     block::{BlockContext, TransactionContext},
     tracing::{ExecutionTracer, ExecutionStep},
+    hardfork::Hardfork,
+    precompiles::Precompile,
+    analysis::{jump_destinations, JumpDestinations},
+    rlp,
 };
 use thiserror::Error;
 use sha3::{Digest, Keccak256};
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+/// Maximum depth of nested message calls
+pub const MAX_CALL_DEPTH: usize = 1024;
 
 #[derive(Error, Debug)]
 pub enum ExecutionError {
@@ -28,6 +38,117 @@ pub enum ExecutionError {
     InvalidInstruction(String),
     #[error("Execution halted: {reason}")]
     Halted { reason: String },
+    #[error("State modification attempted in static context")]
+    StaticCallViolation,
+    #[error("Return data out of bounds: offset {offset}, size {size}, available {available}")]
+    ReturnDataOutOfBounds { offset: usize, size: usize, available: usize },
+    #[error("Invalid jump destination: {destination}")]
+    InvalidJump { destination: usize },
+    #[error("Init code size {size} exceeds limit {limit}")]
+    InitcodeSizeExceeded { size: usize, limit: usize },
+}
+
+impl ExecutionError {
+    /// The exceptional halt this error represents, or `None` for internal failures
+    pub fn halt_reason(&self) -> Option<HaltReason> {
+        match self {
+            ExecutionError::Stack(StackError::Underflow) | ExecutionError::Opcode(OpcodeError::StackUnderflow) => {
+                Some(HaltReason::StackUnderflow)
+            }
+            ExecutionError::Stack(StackError::Overflow) | ExecutionError::Opcode(OpcodeError::StackOverflow) => {
+                Some(HaltReason::StackOverflow)
+            }
+            ExecutionError::Gas(_) => Some(HaltReason::OutOfGas),
+            ExecutionError::Memory(_) => Some(HaltReason::MemoryLimit),
+            ExecutionError::Opcode(OpcodeError::InvalidOpcode { opcode }) => Some(HaltReason::InvalidOpcode(*opcode)),
+            ExecutionError::InvalidJump { .. } => Some(HaltReason::InvalidJump),
+            ExecutionError::StaticCallViolation => Some(HaltReason::StaticViolation),
+            ExecutionError::ReturnDataOutOfBounds { .. } => Some(HaltReason::ReturnDataOutOfBounds),
+            ExecutionError::InitcodeSizeExceeded { .. } => Some(HaltReason::InitcodeSizeLimit),
+            _ => None,
+        }
+    }
+}
+
+/// Reason for an exceptional halt
+///
+/// Exceptional halts are an ordinary execution outcome: the frame fails,
+/// its state changes are reverted and all of its gas is consumed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HaltReason {
+    OutOfGas,
+    StackUnderflow,
+    StackOverflow,
+    InvalidJump,
+    InvalidOpcode(u8),
+    StaticViolation,
+    ReturnDataOutOfBounds,
+    MemoryLimit,
+    CreateCollision,
+    CodeSizeLimit,
+    InitcodeSizeLimit,
+    InvalidCodePrefix,
+    PrecompileFailure,
+}
+
+impl fmt::Display for HaltReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HaltReason::OutOfGas => write!(f, "out of gas"),
+            HaltReason::StackUnderflow => write!(f, "stack underflow"),
+            HaltReason::StackOverflow => write!(f, "stack overflow"),
+            HaltReason::InvalidJump => write!(f, "invalid jump destination"),
+            HaltReason::InvalidOpcode(opcode) => write!(f, "invalid opcode 0x{:02x}", opcode),
+            HaltReason::StaticViolation => write!(f, "state modification in static context"),
+            HaltReason::ReturnDataOutOfBounds => write!(f, "return data out of bounds"),
+            HaltReason::MemoryLimit => write!(f, "memory limit exceeded"),
+            HaltReason::CreateCollision => write!(f, "contract address collision"),
+            HaltReason::CodeSizeLimit => write!(f, "contract code size limit exceeded"),
+            HaltReason::InitcodeSizeLimit => write!(f, "init code size limit exceeded"),
+            HaltReason::InvalidCodePrefix => write!(f, "contract code starts with 0xef"),
+            HaltReason::PrecompileFailure => write!(f, "precompile failed"),
+        }
+    }
+}
+
+/// How an execution ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionOutcome {
+    /// Stopped or returned normally
+    Success,
+    /// Reverted with the given data; unused gas is returned
+    Revert(Bytes),
+    /// Exceptional halt; all gas is consumed
+    Halt(HaltReason),
+}
+
+impl fmt::Display for ExecutionOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionOutcome::Success => write!(f, "success"),
+            ExecutionOutcome::Revert(data) => write!(f, "reverted ({} bytes of data)", data.len()),
+            ExecutionOutcome::Halt(reason) => write!(f, "halted: {}", reason),
+        }
+    }
+}
+
+/// Transaction-wide state accrued across call frames
+#[derive(Debug, Clone, Default)]
+pub struct Substate {
+    /// Accounts scheduled for deletion at the end of the transaction
+    pub selfdestructs: HashSet<Address>,
+    /// Contracts created during the transaction (EIP-6780)
+    pub created_contracts: HashSet<Address>,
+    /// Gas refund counter; may dip below zero mid-transaction (EIP-2200)
+    pub refund: i64,
+}
+
+/// Journal positions of the world state and the event log, and the substate to restore
+#[derive(Debug, Clone)]
+struct StateCheckpoint {
+    storage: Checkpoint,
+    log_count: usize,
+    substate: Substate,
 }
 
 /// Execution context for EVM
@@ -58,12 +179,24 @@ pub struct ExecutionContext {
     pub input_data: Bytes,
     /// Code to execute
     pub code: Bytes,
+    /// JUMPDEST analysis of `code`, computed on the first jump
+    jump_destinations: Option<Arc<JumpDestinations>>,
     /// Return data
     pub return_data: Bytes,
+    /// Output of the most recent sub-call, read by RETURNDATASIZE and RETURNDATACOPY
+    pub return_data_buffer: Bytes,
     /// Whether execution should continue
     pub should_continue: bool,
     /// Whether execution was successful
     pub success: bool,
+    /// Why the frame halted exceptionally, if it did
+    pub halt_reason: Option<HaltReason>,
+    /// Call depth of this frame (0 for the outermost call)
+    pub depth: usize,
+    /// Whether state modifications are forbidden (STATICCALL)
+    pub is_static: bool,
+    /// Transaction-wide substate shared by all frames
+    pub substate: Substate,
 }
 
 impl ExecutionContext {
@@ -90,12 +223,56 @@ impl ExecutionContext {
             call_value,
             input_data,
             code,
+            jump_destinations: None,
             return_data: Bytes::empty(),
+            return_data_buffer: Bytes::empty(),
             should_continue: true,
             success: false,
+            halt_reason: None,
+            depth: 0,
+            is_static: false,
+            substate: Substate::default(),
         }
     }
 
+    /// Create the context for a nested call frame
+    ///
+    /// Block and transaction information is copied from this frame; world state
+    /// (storage and logs) is handed over when the child frame is run.
+    pub fn child(
+        &self,
+        address: Address,
+        caller: Address,
+        call_value: Uint256,
+        input_data: Bytes,
+        code: Bytes,
+        gas_limit: u64,
+    ) -> Self {
+        let mut child = ExecutionContext::new(address, caller, call_value, input_data, code, gas_limit);
+        child.gas_meter = self.gas_meter.child(gas_limit);
+        child.block_context = self.block_context.clone();
+        child.transaction_context = self.transaction_context.clone();
+        child.memory = Memory::with_limit(self.memory.limit());
+        child.depth = self.depth + 1;
+        child.is_static = self.is_static;
+        child
+    }
+
+    /// Hardfork whose rules this frame follows
+    pub fn fork(&self) -> Hardfork {
+        self.gas_meter.fork()
+    }
+
+    /// Follow the gas schedule and rules of `fork`; call before execution starts
+    pub fn set_fork(&mut self, fork: Hardfork) {
+        self.gas_meter = GasMeter::for_fork(self.gas_meter.limit(), fork);
+    }
+
+    /// Cap memory growth at `limit` bytes in this frame and its children; call before execution starts
+    pub fn set_memory_limit(&mut self, limit: usize) {
+        self.memory = Memory::with_limit(limit);
+    }
+
     /// Get the current instruction
     pub fn current_instruction(&self) -> Result<u8, ExecutionError> {
         if self.pc >= self.code.len() {
@@ -110,14 +287,30 @@ impl ExecutionContext {
     }
 
     /// Set the program counter (for jumps)
+    ///
+    /// The target must be a JUMPDEST instruction, not a 0x5b byte inside PUSH data.
     pub fn set_pc(&mut self, pc: usize) -> Result<(), ExecutionError> {
-        if pc >= self.code.len() {
-            return Err(ExecutionError::InvalidInstruction("Jump destination out of bounds".to_string()));
+        let code = &self.code;
+        let jump_destinations = self
+            .jump_destinations
+            .get_or_insert_with(|| jump_destinations(code.as_slice()));
+        if !jump_destinations.is_valid(pc) {
+            return Err(ExecutionError::InvalidJump { destination: pc });
         }
         self.pc = pc;
         Ok(())
     }
 
+    /// Halt exceptionally: the frame fails, returns no data and consumes all its gas
+    pub fn halt_exceptionally(&mut self, reason: HaltReason) {
+        log::debug!("Exceptional halt at pc {}: {}", self.pc, reason);
+        self.should_continue = false;
+        self.success = false;
+        self.halt_reason = Some(reason);
+        self.return_data = Bytes::empty();
+        self.gas_meter.consume_all();
+    }
+
     /// Halt execution
     pub fn halt(&mut self, success: bool, reason: String) {
         self.should_continue = false;
@@ -155,24 +348,85 @@ impl Executor {
 
     /// Execute the EVM code
     pub fn execute(&mut self) -> Result<ExecutionResult, ExecutionError> {
-        while self.context.should_continue && self.context.pc < self.context.code.len() {
-            self.step()?;
+        if self.context.depth == 0 {
+            let fork = self.context.fork();
+            let storage = &mut self.context.storage;
+            storage.warm_address(self.context.transaction_context.origin);
+            storage.warm_address(self.context.caller);
+            storage.warm_address(self.context.address);
+            if fork >= Hardfork::Shanghai {
+                // The coinbase starts warm too (EIP-3651)
+                storage.warm_address(self.context.block_context.coinbase);
+            }
+            for precompile in Precompile::ALL.into_iter().filter(|precompile| precompile.introduced() <= fork) {
+                storage.warm_address(precompile.address());
+            }
         }
 
-        // If we reached the end of code without explicit halt, consider it successful
-        if self.context.should_continue && self.context.pc >= self.context.code.len() {
-            self.context.success = true;
+        let checkpoint = self.checkpoint();
+        let outcome = self.run();
+        if outcome.is_ok() && self.context.success {
+            self.commit(checkpoint);
+        } else {
+            self.revert_to(checkpoint);
+        }
+
+        if self.context.depth == 0 {
+            // Transient storage and warm sets never outlive the transaction (EIP-1153, EIP-2929)
+            self.context.storage.end_transaction();
         }
+        outcome?;
+
+        let outcome = if self.context.success {
+            ExecutionOutcome::Success
+        } else if let Some(reason) = self.context.halt_reason {
+            ExecutionOutcome::Halt(reason)
+        } else {
+            ExecutionOutcome::Revert(self.context.return_data.clone())
+        };
 
         Ok(ExecutionResult {
             success: self.context.success,
+            outcome,
             return_data: self.context.return_data.clone(),
             gas_used: self.context.gas_meter.used(),
             gas_remaining: self.context.gas_meter.available(),
+            gas_refund: if self.context.success { self.context.substate.refund.max(0) as u64 } else { 0 },
             logs: self.context.event_logger.logs().to_vec(),
         })
     }
 
+    /// Run the current frame until it halts or runs off the end of the code
+    ///
+    /// Exceptional halts end the frame normally; only internal failures are errors.
+    fn run(&mut self) -> Result<(), ExecutionError> {
+        while self.context.should_continue && self.context.pc < self.context.code.len() {
+            if let Err(error) = self.step() {
+                match error.halt_reason() {
+                    Some(reason) => self.context.halt_exceptionally(reason),
+                    None => return Err(error),
+                }
+            }
+        }
+
+        // If we reached the end of code without explicit halt, consider it successful
+        if self.context.should_continue && self.context.pc >= self.context.code.len() {
+            self.context.success = true;
+        }
+
+        Ok(())
+    }
+
+    /// Get the execution context
+    pub fn context(&self) -> &ExecutionContext {
+        &self.context
+    }
+
+    /// Consume the executor, returning its context (and with it the world state)
+    pub fn into_context(self) -> ExecutionContext {
+        self.context
+    }
+
     /// Take the tracer from the executor (consumes the tracer)
     pub fn take_tracer(&mut self) -> Option<ExecutionTracer> {
         self.tracer.take()
@@ -187,6 +441,9 @@ impl Executor {
 
         let opcode_byte = self.context.current_instruction()?;
         let opcode = Opcode::from_byte(opcode_byte)?;
+        if opcode.info().introduced > self.context.fork() {
+            return Err(OpcodeError::InvalidOpcode { opcode: opcode_byte }.into());
+        }
 
         // Capture stack state before execution for tracing
         let stack_before = if self.tracer.is_some() {
@@ -195,7 +452,10 @@ impl Executor {
             Vec::new()
         };
 
+        let pc = self.context.pc;
         let gas_before = self.context.gas_meter.available();
+        // Sub-calls record their own steps while this one is executing
+        let trace_index = self.tracer.as_ref().map(|tracer| tracer.step_count());
 
         // Validate stack requirements
         self.validate_stack_requirements(&opcode)?;
@@ -208,22 +468,22 @@ impl Executor {
         }
 
         // Record execution step if tracer is present
-        if let Some(ref mut tracer) = self.tracer {
+        if let (Some(tracer), Some(index)) = (self.tracer.as_mut(), trace_index) {
             let stack_after = self.context.stack.items().to_vec();
             let gas_after = self.context.gas_meter.available();
-            let gas_consumed = gas_before - gas_after;
+            let gas_consumed = gas_before.saturating_sub(gas_after);
 
             let step = ExecutionStep::new(
-                self.context.pc.saturating_sub(1), // PC was advanced during execution
+                pc,
                 opcode,
                 stack_before,
                 stack_after,
                 gas_consumed,
                 gas_after,
-                0, // depth - we'll implement this later for calls
+                self.context.depth,
             );
 
-            tracer.add_step(step);
+            tracer.insert_step(index, step);
         }
 
         Ok(())
@@ -237,7 +497,7 @@ impl Executor {
         }
         
         let max_stack_size = self.context.stack.max_size();
-        if opcode.is_push() && self.context.stack.size() >= max_stack_size {
+        if self.context.stack.size() - required_items + opcode.push_count() > max_stack_size {
             return Err(ExecutionError::Stack(StackError::Overflow));
         }
         
@@ -261,10 +521,10 @@ impl Executor {
             Opcode::Mulmod => Ok(costs.mulmod),
             Opcode::Signextend => Ok(costs.signextend),
             Opcode::Exp => {
-                // EXP gas cost is dynamic based on exponent
-                if let Ok(exponent) = self.context.stack.peek_at(0) {
-                    let exp_bits = exponent.as_biguint().bits();
-                    Ok(costs.exp + (exp_bits * 10) as u64)
+                // EXP charges for every byte of the exponent
+                if let Ok(exponent) = self.context.stack.peek_at(1) {
+                    let exp_bytes = exponent.bits().div_ceil(8);
+                    Ok(costs.exp + exp_bytes * costs.exp_byte)
                 } else {
                     Ok(costs.exp)
                 }
@@ -286,21 +546,14 @@ impl Executor {
             Opcode::Byte => Ok(costs.byte),
             Opcode::Shl => Ok(costs.shl),
             Opcode::Shr => Ok(costs.shr),
+            Opcode::Sar => Ok(costs.sar),
             
-            // SHA3 operation
-            Opcode::Sha3 => {
-                // Dynamic gas cost based on data size
-                if let Ok(size) = self.context.stack.peek_at(0) {
-                    let size_usize = size.to_u64() as usize;
-                    let words = (size_usize + 31) / 32;
-                    Ok(costs.keccak256 + (words as u64 * costs.keccak256_word))
-                } else {
-                    Ok(costs.keccak256)
-                }
-            },
+            // SHA3 operation; the per-word cost is charged with the memory access
+            Opcode::Sha3 => Ok(costs.keccak256),
             
             // Stack operations
             Opcode::Pop => Ok(costs.pop),
+            Opcode::Push0 => Ok(costs.push0),
             _ if opcode.is_dup() => Ok(costs.dup),
             _ if opcode.is_swap() => Ok(costs.swap),
             
@@ -309,34 +562,14 @@ impl Executor {
             Opcode::Mstore => Ok(costs.mstore),
             Opcode::Mstore8 => Ok(costs.mstore8),
             Opcode::Msize => Ok(costs.msize),
+            Opcode::Mcopy => Ok(costs.mcopy),
             
             // Storage operations
             Opcode::Sload => Ok(costs.sload),
-            Opcode::Sstore => {
-                // Dynamic gas cost for SSTORE
-                if let (Ok(key), Ok(value)) = (self.context.stack.peek_at(0), self.context.stack.peek_at(1)) {
-                    let current_value = self.context.storage.get_storage(&self.context.address, key);
-                    if current_value == *value {
-                        // No change
-                        if current_value.is_zero() {
-                            Ok(costs.sstore_clear)
-                        } else {
-                            Ok(costs.sstore_reset)
-                        }
-                    } else {
-                        // Value is changing
-                        if current_value.is_zero() {
-                            Ok(costs.sstore_set)
-                        } else if value.is_zero() {
-                            Ok(costs.sstore_clear)
-                        } else {
-                            Ok(costs.sstore_reset)
-                        }
-                    }
-                } else {
-                    Ok(costs.sstore)
-                }
-            },
+            Opcode::Tload => Ok(costs.tload),
+            Opcode::Tstore => Ok(costs.tstore),
+            // Charged in full by the handler, after the stipend sentry check (EIP-2200)
+            Opcode::Sstore => Ok(0),
             
             // Environmental information
             Opcode::Address => Ok(costs.address),
@@ -346,6 +579,12 @@ impl Executor {
             Opcode::Calldataload => Ok(costs.calldataload),
             Opcode::Codesize => Ok(costs.codesize),
             Opcode::Codecopy => Ok(costs.codecopy),
+            Opcode::Calldatacopy => Ok(costs.calldatacopy),
+            Opcode::Extcodesize => Ok(costs.extcodesize),
+            Opcode::Extcodecopy => Ok(costs.extcodecopy),
+            Opcode::Extcodehash => Ok(costs.extcodehash),
+            Opcode::Returndatasize => Ok(costs.returndatasize),
+            Opcode::Returndatacopy => Ok(costs.returndatacopy),
             Opcode::Balance => Ok(costs.balance),
             
             // Block information
@@ -357,6 +596,9 @@ impl Executor {
             Opcode::Gaslimit => Ok(costs.gaslimit),
             Opcode::Chainid => Ok(costs.chainid),
             Opcode::Selfbalance => Ok(costs.selfbalance),
+            Opcode::Basefee => Ok(costs.basefee),
+            Opcode::Blobhash => Ok(costs.blobhash),
+            Opcode::Blobbasefee => Ok(costs.blobbasefee),
             
             // Transaction information
             Opcode::Gasprice => Ok(costs.gasprice),
@@ -366,6 +608,7 @@ impl Executor {
             Opcode::Jump => Ok(costs.jump),
             Opcode::Jumpi => Ok(costs.jumpi),
             Opcode::Pc => Ok(costs.pc),
+            Opcode::Gas => Ok(costs.gas),
             Opcode::Jumpdest => Ok(costs.jumpdest),
             
             // Logging operations
@@ -376,6 +619,12 @@ impl Executor {
             Opcode::Log4 => Ok(costs.log4),
             
             // System operations
+            Opcode::Create | Opcode::Create2 => Ok(costs.create),
+            Opcode::Selfdestruct => Ok(costs.selfdestruct),
+            Opcode::Call => Ok(costs.call),
+            Opcode::Callcode => Ok(costs.callcode),
+            Opcode::Delegatecall => Ok(costs.delegatecall),
+            Opcode::Staticcall => Ok(costs.staticcall),
             Opcode::Return => Ok(costs.return_),
             Opcode::Revert => Ok(costs.revert),
             
@@ -383,23 +632,44 @@ impl Executor {
         }
     }
 
+    /// Pop `dest_offset, offset, size` and copy that slice of `source` into memory
+    ///
+    /// Bytes past the end of `source` are zero-filled.
+    fn execute_copy(&mut self, source: &[u8]) -> Result<(), ExecutionError> {
+        let dest_offset = self.context.stack.pop()?;
+        let offset = self.context.stack.pop()?;
+        let size = self.context.stack.pop()?;
+        let (dest_offset, size) = self.expand_memory(dest_offset, size)?;
+        if size == 0 {
+            return Ok(());
+        }
+        self.charge_words(size, self.context.gas_meter.costs().copy_word)?;
+
+        let mut data = vec![0u8; size];
+        let offset = Self::saturating_usize(&offset);
+        if offset < source.len() {
+            let len = (source.len() - offset).min(size);
+            data[..len].copy_from_slice(&source[offset..offset + len]);
+        }
+        self.context.memory.write_bytes(dest_offset, &data)?;
+        Ok(())
+    }
+
     /// Execute a push opcode
     fn execute_push(&mut self, opcode: Opcode) -> Result<(), ExecutionError> {
         // Consume gas for push operation
         let gas_cost = self.context.gas_meter.costs().push;
         self.context.gas_meter.consume(gas_cost)?;
         
-        let push_size = opcode.get_push_size();
+        let push_size = opcode.push_size();
         self.context.advance_pc(1);
 
-        if self.context.pc + push_size > self.context.code.len() {
-            return Err(ExecutionError::InvalidInstruction("Push data extends beyond code".to_string()));
-        }
-
+        // Immediates running past the end of the code read as zeros
         let mut value_bytes = [0u8; 32];
-        let start = self.context.pc;
-        let end = start + push_size;
-        value_bytes[32 - push_size..].copy_from_slice(&self.context.code.as_slice()[start..end]);
+        let start = self.context.pc.min(self.context.code.len());
+        let end = (start + push_size).min(self.context.code.len());
+        let offset = 32 - push_size;
+        value_bytes[offset..offset + (end - start)].copy_from_slice(&self.context.code.as_slice()[start..end]);
 
         let value = Uint256::from_bytes_be(&value_bytes);
         self.context.stack.push(value)?;
@@ -410,6 +680,11 @@ impl Executor {
 
     /// Execute an opcode
     fn execute_opcode(&mut self, opcode: Opcode) -> Result<(), ExecutionError> {
+        // State-modifying opcodes are not allowed inside STATICCALL
+        if self.context.is_static && Self::modifies_state(opcode) {
+            return Err(ExecutionError::StaticCallViolation);
+        }
+
         // Calculate and consume gas for this opcode
         let gas_cost = self.calculate_gas_cost(&opcode)?;
         self.context.gas_meter.consume(gas_cost)?;
@@ -442,121 +717,47 @@ impl Executor {
             Opcode::Div => {
                 let a = self.context.stack.pop()?;
                 let b = self.context.stack.pop()?;
-                let result = if b.is_zero() { Uint256::zero() } else { a / b };
+                let result = a / b;
                 self.context.stack.push(result)?;
             }
             Opcode::Mod => {
                 let a = self.context.stack.pop()?;
                 let b = self.context.stack.pop()?;
-                let result = if b.is_zero() { Uint256::zero() } else { a % b };
+                let result = a % b;
                 self.context.stack.push(result)?;
             }
             Opcode::Exp => {
                 let base = self.context.stack.pop()?;
                 let exponent = self.context.stack.pop()?;
-                // For simplicity, we'll use a basic implementation
-                // In a real EVM, this would be more complex
-                let result = if exponent.is_zero() {
-                    Uint256::one()
-                } else if base.is_zero() {
-                    Uint256::zero()
-                } else {
-                    // This is a simplified implementation
-                    // A real EVM would handle large exponents more efficiently
-                    let mut result = Uint256::one();
-                    let mut exp = exponent;
-                    let mut base_val = base;
-                    while !exp.is_zero() {
-                        if exp.as_biguint() % 2u32 == 1u32.into() {
-                            result = result * base_val.clone();
-                        }
-                        base_val = base_val.clone() * base_val.clone();
-                        exp = exp / Uint256::from_u32(2);
-                    }
-                    result
-                };
-                self.context.stack.push(result)?;
+                self.context.stack.push(base.wrapping_pow(exponent))?;
             }
             Opcode::Sdiv => {
                 let a = self.context.stack.pop()?;
                 let b = self.context.stack.pop()?;
-                if b.is_zero() {
-                    self.context.stack.push(Uint256::zero())?;
-                } else {
-                    // Signed division: convert to signed, divide, convert back
-                    let a_signed = self.uint256_to_signed(&a);
-                    let b_signed = self.uint256_to_signed(&b);
-                    let result_signed = a_signed / b_signed;
-                    let result = self.signed_to_uint256(result_signed);
-                    self.context.stack.push(result)?;
-                }
+                self.context.stack.push(a.signed_div(b))?;
             }
             Opcode::Smod => {
                 let a = self.context.stack.pop()?;
                 let b = self.context.stack.pop()?;
-                if b.is_zero() {
-                    self.context.stack.push(Uint256::zero())?;
-                } else {
-                    // Signed modulo: convert to signed, modulo, convert back
-                    let a_signed = self.uint256_to_signed(&a);
-                    let b_signed = self.uint256_to_signed(&b);
-                    let result_signed = a_signed % b_signed;
-                    let result = self.signed_to_uint256(result_signed);
-                    self.context.stack.push(result)?;
-                }
+                self.context.stack.push(a.signed_rem(b))?;
             }
             Opcode::Addmod => {
                 let a = self.context.stack.pop()?;
                 let b = self.context.stack.pop()?;
                 let m = self.context.stack.pop()?;
-                if m.is_zero() {
-                    self.context.stack.push(Uint256::zero())?;
-                } else {
-                    // (a + b) mod m
-                    let sum = a.as_biguint() + b.as_biguint();
-                    let result = sum % m.as_biguint();
-                    self.context.stack.push(Uint256::new(result))?;
-                }
+                self.context.stack.push(a.add_mod(b, m))?;
             }
             Opcode::Mulmod => {
                 let a = self.context.stack.pop()?;
                 let b = self.context.stack.pop()?;
                 let m = self.context.stack.pop()?;
-                if m.is_zero() {
-                    self.context.stack.push(Uint256::zero())?;
-                } else {
-                    // (a * b) mod m
-                    let product = a.as_biguint() * b.as_biguint();
-                    let result = product % m.as_biguint();
-                    self.context.stack.push(Uint256::new(result))?;
-                }
+                self.context.stack.push(a.mul_mod(b, m))?;
             }
             Opcode::Signextend => {
                 let b = self.context.stack.pop()?;
                 let x = self.context.stack.pop()?;
-                
-                if b >= Uint256::from_u32(31) {
-                    // If b >= 31, return x unchanged
-                    self.context.stack.push(x)?;
-                } else {
-                    let b_usize = b.to_u32() as usize;
-                    let x_bytes = x.to_bytes_be();
-                    let sign_bit = (x_bytes[31 - b_usize] & 0x80) != 0;
-                    
-                    let mut result_bytes = [0u8; 32];
-                    if sign_bit {
-                        // Sign extend with 1s
-                        for i in 0..(31 - b_usize) {
-                            result_bytes[i] = 0xFF;
-                        }
-                    }
-                    // Copy the significant bytes
-                    for i in (31 - b_usize)..32 {
-                        result_bytes[i] = x_bytes[i];
-                    }
-                    
-                    self.context.stack.push(Uint256::from_bytes_be(&result_bytes))?;
-                }
+                let byte = b.to_u64_safe().map_or(usize::MAX, |b| b as usize);
+                self.context.stack.push(x.sign_extend(byte))?;
             }
 
             // Comparison operations
@@ -573,17 +774,15 @@ impl Executor {
                 self.context.stack.push(result)?;
             }
             Opcode::Slt => {
-                // Signed less than (simplified implementation)
                 let a = self.context.stack.pop()?;
                 let b = self.context.stack.pop()?;
-                let result = if a < b { Uint256::one() } else { Uint256::zero() };
+                let result = if a.signed_cmp(&b).is_lt() { Uint256::one() } else { Uint256::zero() };
                 self.context.stack.push(result)?;
             }
             Opcode::Sgt => {
-                // Signed greater than (simplified implementation)
                 let a = self.context.stack.pop()?;
                 let b = self.context.stack.pop()?;
-                let result = if a > b { Uint256::one() } else { Uint256::zero() };
+                let result = if a.signed_cmp(&b).is_gt() { Uint256::one() } else { Uint256::zero() };
                 self.context.stack.push(result)?;
             }
             Opcode::Eq => {
@@ -620,60 +819,38 @@ impl Executor {
             Opcode::Byte => {
                 let i = self.context.stack.pop()?;
                 let x = self.context.stack.pop()?;
-                let result = if i >= Uint256::from_u32(32) {
-                    Uint256::zero()
-                } else {
-                    let byte_index = i.to_u32() as usize;
-                    let bytes = x.to_bytes_be();
-                    Uint256::from_u8(bytes[byte_index])
-                };
-                self.context.stack.push(result)?;
+                let index = i.to_u64_safe().map_or(usize::MAX, |i| i as usize);
+                self.context.stack.push(Uint256::from_u8(x.byte(index)))?;
             }
             Opcode::Shl => {
                 let shift = self.context.stack.pop()?;
                 let value = self.context.stack.pop()?;
-                let result = if shift >= Uint256::from_u32(256) {
-                    Uint256::zero()
-                } else {
-                    value << (shift.to_u32() as usize)
-                };
-                self.context.stack.push(result)?;
+                self.context.stack.push(value << Self::shift_amount(&shift))?;
             }
             Opcode::Shr => {
                 let shift = self.context.stack.pop()?;
                 let value = self.context.stack.pop()?;
-                let result = if shift >= Uint256::from_u32(256) {
-                    Uint256::zero()
-                } else {
-                    value >> (shift.to_u32() as usize)
-                };
-                self.context.stack.push(result)?;
+                self.context.stack.push(value >> Self::shift_amount(&shift))?;
+            }
+            Opcode::Sar => {
+                let shift = self.context.stack.pop()?;
+                let value = self.context.stack.pop()?;
+                self.context.stack.push(value.sar(Self::shift_amount(&shift)))?;
             }
             Opcode::Not => {
                 let a = self.context.stack.pop()?;
-                // NOT operation on 256-bit value (bitwise complement)
-                let max_uint256 = Uint256::from_bytes_be(&[0xFF; 32]);
-                let result = a ^ max_uint256;
-                self.context.stack.push(result)?;
+                self.context.stack.push(!a)?;
             }
 
             // SHA3 operation
             Opcode::Sha3 => {
                 let offset = self.context.stack.pop()?;
                 let size = self.context.stack.pop()?;
-                let offset_usize = offset.to_u64() as usize;
-                let size_usize = size.to_u64() as usize;
-                
-                // Calculate memory expansion cost
-                let new_size = offset_usize + size_usize;
-                let expansion_cost = self.context.gas_meter.memory_expansion_cost(
-                    self.context.memory.size(), 
-                    new_size
-                );
-                self.context.gas_meter.consume(expansion_cost)?;
+                let (offset, size) = self.expand_memory(offset, size)?;
+                self.charge_words(size, self.context.gas_meter.costs().keccak256_word)?;
                 
                 // Read data from memory
-                let data = self.context.memory.read_bytes(offset_usize, size_usize)?;
+                let data = self.context.memory.read_bytes(offset, size)?;
                 
                 // Calculate Keccak256 hash
                 let hash = Keccak256::digest(&data);
@@ -685,6 +862,9 @@ impl Executor {
             Opcode::Pop => {
                 self.context.stack.pop()?;
             }
+            Opcode::Push0 => {
+                self.context.stack.push(Uint256::zero())?;
+            }
             _ if opcode.is_dup() => {
                 let depth = opcode.dup_depth();
                 self.context.stack.dup(depth)?;
@@ -697,65 +877,84 @@ impl Executor {
             // Memory operations
             Opcode::Mload => {
                 let offset = self.context.stack.pop()?;
-                let offset_usize = offset.to_u64() as usize;
-                
-                // Calculate memory expansion cost
-                let new_size = offset_usize + 32;
-                let expansion_cost = self.context.gas_meter.memory_expansion_cost(
-                    self.context.memory.size(), 
-                    new_size
-                );
-                self.context.gas_meter.consume(expansion_cost)?;
-                
-                let value = self.context.memory.read_word(offset_usize)?;
+                let (offset, _) = self.expand_memory(offset, Uint256::from_u64(32))?;
+                let value = self.context.memory.read_word(offset)?;
                 self.context.stack.push(value)?;
             }
             Opcode::Mstore => {
                 let offset = self.context.stack.pop()?;
                 let value = self.context.stack.pop()?;
-                let offset_usize = offset.to_u64() as usize;
-                
-                // Calculate memory expansion cost
-                let new_size = offset_usize + 32;
-                let expansion_cost = self.context.gas_meter.memory_expansion_cost(
-                    self.context.memory.size(), 
-                    new_size
-                );
-                self.context.gas_meter.consume(expansion_cost)?;
-                
-                self.context.memory.write_word(offset_usize, value)?;
+                let (offset, _) = self.expand_memory(offset, Uint256::from_u64(32))?;
+                self.context.memory.write_word(offset, value)?;
             }
             Opcode::Mstore8 => {
                 let offset = self.context.stack.pop()?;
                 let value = self.context.stack.pop()?;
-                let offset_usize = offset.to_u64() as usize;
-                
-                // Calculate memory expansion cost
-                let new_size = offset_usize + 1;
-                let expansion_cost = self.context.gas_meter.memory_expansion_cost(
-                    self.context.memory.size(), 
-                    new_size
-                );
-                self.context.gas_meter.consume(expansion_cost)?;
-                
-                let byte_value = value.to_u8();
-                self.context.memory.write_byte(offset_usize, byte_value)?;
+                let (offset, _) = self.expand_memory(offset, Uint256::one())?;
+                self.context.memory.write_byte(offset, value.to_u8())?;
             }
             Opcode::Msize => {
                 let size = Uint256::from_u32(self.context.memory.size() as u32);
                 self.context.stack.push(size)?;
             }
+            Opcode::Mcopy => {
+                let dest_offset = self.context.stack.pop()?;
+                let offset = self.context.stack.pop()?;
+                let size = self.context.stack.pop()?;
+                // Memory expands to cover both the source and the destination
+                let (dest_offset, size) = self.expand_memory(dest_offset, size)?;
+                let (offset, _) = self.expand_memory(offset, Uint256::from_u64(size as u64))?;
+                if size > 0 {
+                    self.charge_words(size, self.context.gas_meter.costs().copy_word)?;
+                    self.context.memory.copy(dest_offset, offset, size)?;
+                }
+            }
 
             // Storage operations
             Opcode::Sload => {
                 let key = self.context.stack.pop()?;
+                let cold = !self.context.storage.warm_slot(self.context.address, key);
+                if cold && self.context.fork() >= Hardfork::Berlin {
+                    let costs = self.context.gas_meter.costs();
+                    let surcharge = costs.cold_storage_read - costs.warm_storage_read;
+                    self.context.gas_meter.consume(surcharge)?;
+                }
                 let value = self.context.storage.get_storage(&self.context.address, &key);
                 self.context.stack.push(value)?;
             }
+            Opcode::Tload => {
+                let key = self.context.stack.pop()?;
+                let value = self.context.storage.get_transient(&self.context.address, &key);
+                self.context.stack.push(value)?;
+            }
+            Opcode::Tstore => {
+                let key = self.context.stack.pop()?;
+                let value = self.context.stack.pop()?;
+                self.context.storage.set_transient(self.context.address, key, value);
+            }
             Opcode::Sstore => {
+                // Never allowed with only the call stipend left (EIP-2200)
+                let available = self.context.gas_meter.available();
+                let sentry = self.context.gas_meter.costs().sstore_sentry;
+                if self.context.fork() >= Hardfork::Istanbul && available <= sentry {
+                    return Err(GasError::OutOfGas { required: sentry + 1, available }.into());
+                }
+
                 let key = self.context.stack.pop()?;
                 let value = self.context.stack.pop()?;
-                self.context.storage.set_storage(self.context.address, key, value);
+                let address = self.context.address;
+                let current = self.context.storage.get_storage(&address, &key);
+                let original = self.context.storage.get_original_storage(&address, &key);
+                let meter = &self.context.gas_meter;
+                let mut cost = meter.sstore_cost(&current, &value, &original);
+                let refund = meter.sstore_refund(&current, &value, &original);
+                let cold = !self.context.storage.warm_slot(address, key);
+                if cold && meter.fork() >= Hardfork::Berlin {
+                    cost += meter.costs().cold_storage_read;
+                }
+                self.context.gas_meter.consume(cost)?;
+                self.context.substate.refund += refund;
+                self.context.storage.set_storage(address, key, value);
             }
 
             // Environmental information
@@ -766,15 +965,14 @@ impl Executor {
                 self.context.stack.push(Uint256::from_bytes_be(self.context.caller.as_bytes()))?;
             }
             Opcode::Callvalue => {
-                self.context.stack.push(self.context.call_value.clone())?;
+                self.context.stack.push(self.context.call_value)?;
             }
             Opcode::Calldatasize => {
                 let size = Uint256::from_u32(self.context.input_data.len() as u32);
                 self.context.stack.push(size)?;
             }
             Opcode::Calldataload => {
-                let offset = self.context.stack.pop()?;
-                let offset_usize = offset.to_u64() as usize;
+                let offset_usize = Self::saturating_usize(&self.context.stack.pop()?);
                 if offset_usize >= self.context.input_data.len() {
                     self.context.stack.push(Uint256::zero())?;
                 } else {
@@ -791,34 +989,53 @@ impl Executor {
                 self.context.stack.push(size)?;
             }
             Opcode::Codecopy => {
-                let dest_offset = self.context.stack.pop()?;
-                let offset = self.context.stack.pop()?;
-                let size = self.context.stack.pop()?;
-                let dest_offset_usize = dest_offset.to_u64() as usize;
-                let offset_usize = offset.to_u64() as usize;
-                let size_usize = size.to_u64() as usize;
-                
-                // Calculate memory expansion cost
-                let new_size = dest_offset_usize + size_usize;
-                let expansion_cost = self.context.gas_meter.memory_expansion_cost(
-                    self.context.memory.size(), 
-                    new_size
-                );
-                self.context.gas_meter.consume(expansion_cost)?;
-                
-                if offset_usize < self.context.code.len() {
-                    let end = (offset_usize + size_usize).min(self.context.code.len());
-                    let data = &self.context.code.as_slice()[offset_usize..end];
-                    self.context.memory.write_bytes(dest_offset_usize, data)?;
+                let code = self.context.code.clone();
+                self.execute_copy(code.as_slice())?;
+            }
+            Opcode::Calldatacopy => {
+                let input = self.context.input_data.clone();
+                self.execute_copy(input.as_slice())?;
+            }
+            Opcode::Extcodesize => {
+                let address = Self::uint256_to_address(&self.context.stack.pop()?);
+                self.access_account(address)?;
+                let size = self.context.storage.get_code(&address).len();
+                self.context.stack.push(Uint256::from_u64(size as u64))?;
+            }
+            Opcode::Extcodecopy => {
+                let address = Self::uint256_to_address(&self.context.stack.pop()?);
+                self.access_account(address)?;
+                let code = self.context.storage.get_code(&address);
+                self.execute_copy(&code)?;
+            }
+            Opcode::Extcodehash => {
+                let address = Self::uint256_to_address(&self.context.stack.pop()?);
+                self.access_account(address)?;
+                // Non-existent and empty accounts hash to zero (EIP-1052)
+                let hash = match self.context.storage.get_account_info(&address) {
+                    Some(info) if !info.is_empty() => Uint256::from_bytes_be(info.code_hash.as_bytes()),
+                    _ => Uint256::zero(),
+                };
+                self.context.stack.push(hash)?;
+            }
+            Opcode::Returndatasize => {
+                let size = self.context.return_data_buffer.len();
+                self.context.stack.push(Uint256::from_u64(size as u64))?;
+            }
+            Opcode::Returndatacopy => {
+                let offset = Self::saturating_usize(self.context.stack.peek_at(1)?);
+                let size = Self::saturating_usize(self.context.stack.peek_at(2)?);
+                let available = self.context.return_data_buffer.len();
+                // Reading past the buffer is an exceptional halt (EIP-211)
+                if offset.checked_add(size).is_none_or(|end| end > available) {
+                    return Err(ExecutionError::ReturnDataOutOfBounds { offset, size, available });
                 }
+                let buffer = self.context.return_data_buffer.clone();
+                self.execute_copy(buffer.as_slice())?;
             }
             Opcode::Balance => {
-                let address_bytes = self.context.stack.pop()?;
-                let address_bytes_array = address_bytes.to_bytes_be();
-                let address_slice = &address_bytes_array[12..]; // Take last 20 bytes
-                let mut address_array = [0u8; 20];
-                address_array.copy_from_slice(address_slice);
-                let address = Address::new(address_array);
+                let address = Self::uint256_to_address(&self.context.stack.pop()?);
+                self.access_account(address)?;
                 let balance = self.context.storage.get_balance(&address);
                 self.context.stack.push(balance)?;
             }
@@ -835,28 +1052,44 @@ impl Executor {
                 self.context.stack.push(coinbase_uint)?;
             }
             Opcode::Timestamp => {
-                self.context.stack.push(self.context.block_context.timestamp.clone())?;
+                self.context.stack.push(self.context.block_context.timestamp)?;
             }
             Opcode::Number => {
-                self.context.stack.push(self.context.block_context.number.clone())?;
+                self.context.stack.push(self.context.block_context.number)?;
             }
             Opcode::Difficulty => {
-                self.context.stack.push(self.context.block_context.difficulty.clone())?;
+                self.context.stack.push(self.context.block_context.difficulty)?;
             }
             Opcode::Gaslimit => {
-                self.context.stack.push(self.context.block_context.gas_limit.clone())?;
+                self.context.stack.push(self.context.block_context.gas_limit)?;
             }
             Opcode::Chainid => {
-                self.context.stack.push(self.context.block_context.chain_id.clone())?;
+                self.context.stack.push(self.context.block_context.chain_id)?;
             }
             Opcode::Selfbalance => {
                 let balance = self.context.storage.get_balance(&self.context.address);
                 self.context.stack.push(balance)?;
             }
+            Opcode::Basefee => {
+                self.context.stack.push(self.context.block_context.base_fee)?;
+            }
+            Opcode::Blobhash => {
+                let index = self.context.stack.pop()?;
+                let hash = index
+                    .to_u64_safe()
+                    .ok()
+                    .and_then(|i| self.context.transaction_context.blob_hashes.get(i as usize))
+                    .map(|hash| Uint256::from_bytes_be(hash.as_bytes()))
+                    .unwrap_or_else(Uint256::zero);
+                self.context.stack.push(hash)?;
+            }
+            Opcode::Blobbasefee => {
+                self.context.stack.push(self.context.block_context.blob_base_fee)?;
+            }
 
             // Transaction information opcodes
             Opcode::Gasprice => {
-                self.context.stack.push(self.context.transaction_context.gas_price.clone())?;
+                self.context.stack.push(self.context.transaction_context.gas_price)?;
             }
             Opcode::Origin => {
                 let origin_bytes = *self.context.transaction_context.origin.as_bytes();
@@ -867,14 +1100,14 @@ impl Executor {
             // Control flow
             Opcode::Jump => {
                 let dest = self.context.stack.pop()?;
-                let dest_usize = dest.to_u64() as usize;
+                let dest_usize = dest.to_u64_safe().map_or(usize::MAX, |dest| dest as usize);
                 self.context.set_pc(dest_usize)?;
             }
             Opcode::Jumpi => {
                 let dest = self.context.stack.pop()?;
                 let condition = self.context.stack.pop()?;
                 if !condition.is_zero() {
-                    let dest_usize = dest.to_u64() as usize;
+                    let dest_usize = dest.to_u64_safe().map_or(usize::MAX, |dest| dest as usize);
                     self.context.set_pc(dest_usize)?;
                 }
             }
@@ -882,241 +1115,615 @@ impl Executor {
                 let pc = Uint256::from_u32(self.context.pc as u32);
                 self.context.stack.push(pc)?;
             }
+            Opcode::Gas => {
+                let remaining = Uint256::from_u64(self.context.gas_meter.available());
+                self.context.stack.push(remaining)?;
+            }
             Opcode::Jumpdest => {
                 // No operation, just a valid jump destination
             }
 
             // Logging operations
-            Opcode::Log0 => {
-                let offset = self.context.stack.pop()?;
-                let size = self.context.stack.pop()?;
-                let offset_usize = offset.to_u64() as usize;
-                let size_usize = size.to_u64() as usize;
-                
-                // Calculate memory expansion cost
-                let new_size = offset_usize + size_usize;
-                let expansion_cost = self.context.gas_meter.memory_expansion_cost(
-                    self.context.memory.size(), 
-                    new_size
-                );
-                self.context.gas_meter.consume(expansion_cost)?;
-                
-                let data = self.context.memory.read_bytes(offset_usize, size_usize)?;
-                self.context.event_logger.log(self.context.address, vec![], Bytes::new(data));
+            Opcode::Log0 | Opcode::Log1 | Opcode::Log2 | Opcode::Log3 | Opcode::Log4 => {
+                self.execute_log(opcode as usize - Opcode::Log0 as usize)?;
             }
-            Opcode::Log1 => {
-                let topic0 = self.context.stack.pop()?;
-                let offset = self.context.stack.pop()?;
-                let size = self.context.stack.pop()?;
-                let offset_usize = offset.to_u64() as usize;
-                let size_usize = size.to_u64() as usize;
-                
-                // Calculate memory expansion cost
-                let new_size = offset_usize + size_usize;
-                let expansion_cost = self.context.gas_meter.memory_expansion_cost(
-                    self.context.memory.size(), 
-                    new_size
-                );
-                self.context.gas_meter.consume(expansion_cost)?;
-                
-                let data = self.context.memory.read_bytes(offset_usize, size_usize)?;
-                let topic0_bytes = topic0.to_bytes_be();
-                let mut topic0_array = [0u8; 32];
-                topic0_array.copy_from_slice(&topic0_bytes[..32]);
-                let topics = vec![Hash::new(topic0_array)];
-                self.context.event_logger.log(self.context.address, topics, Bytes::new(data));
-            }
-            Opcode::Log2 => {
-                let topic1 = self.context.stack.pop()?;
-                let topic0 = self.context.stack.pop()?;
-                let offset = self.context.stack.pop()?;
-                let size = self.context.stack.pop()?;
-                let offset_usize = offset.to_u64() as usize;
-                let size_usize = size.to_u64() as usize;
-                
-                // Calculate memory expansion cost
-                let new_size = offset_usize + size_usize;
-                let expansion_cost = self.context.gas_meter.memory_expansion_cost(
-                    self.context.memory.size(), 
-                    new_size
-                );
-                self.context.gas_meter.consume(expansion_cost)?;
-                
-                let data = self.context.memory.read_bytes(offset_usize, size_usize)?;
-                let topic0_bytes = topic0.to_bytes_be();
-                let topic1_bytes = topic1.to_bytes_be();
-                let mut topic0_array = [0u8; 32];
-                let mut topic1_array = [0u8; 32];
-                topic0_array.copy_from_slice(&topic0_bytes[..32]);
-                topic1_array.copy_from_slice(&topic1_bytes[..32]);
-                let topics = vec![
-                    Hash::new(topic0_array),
-                    Hash::new(topic1_array),
-                ];
-                self.context.event_logger.log(self.context.address, topics, Bytes::new(data));
-            }
-            Opcode::Log3 => {
-                let topic2 = self.context.stack.pop()?;
-                let topic1 = self.context.stack.pop()?;
-                let topic0 = self.context.stack.pop()?;
-                let offset = self.context.stack.pop()?;
-                let size = self.context.stack.pop()?;
-                let offset_usize = offset.to_u64() as usize;
-                let size_usize = size.to_u64() as usize;
-                
-                // Calculate memory expansion cost
-                let new_size = offset_usize + size_usize;
-                let expansion_cost = self.context.gas_meter.memory_expansion_cost(
-                    self.context.memory.size(), 
-                    new_size
-                );
-                self.context.gas_meter.consume(expansion_cost)?;
-                
-                let data = self.context.memory.read_bytes(offset_usize, size_usize)?;
-                let topic0_bytes = topic0.to_bytes_be();
-                let topic1_bytes = topic1.to_bytes_be();
-                let topic2_bytes = topic2.to_bytes_be();
-                let mut topic0_array = [0u8; 32];
-                let mut topic1_array = [0u8; 32];
-                let mut topic2_array = [0u8; 32];
-                topic0_array.copy_from_slice(&topic0_bytes[..32]);
-                topic1_array.copy_from_slice(&topic1_bytes[..32]);
-                topic2_array.copy_from_slice(&topic2_bytes[..32]);
-                let topics = vec![
-                    Hash::new(topic0_array),
-                    Hash::new(topic1_array),
-                    Hash::new(topic2_array),
-                ];
-                self.context.event_logger.log(self.context.address, topics, Bytes::new(data));
-            }
-            Opcode::Log4 => {
-                let topic3 = self.context.stack.pop()?;
-                let topic2 = self.context.stack.pop()?;
-                let topic1 = self.context.stack.pop()?;
-                let topic0 = self.context.stack.pop()?;
-                let offset = self.context.stack.pop()?;
-                let size = self.context.stack.pop()?;
-                let offset_usize = offset.to_u64() as usize;
-                let size_usize = size.to_u64() as usize;
-                
-                // Calculate memory expansion cost
-                let new_size = offset_usize + size_usize;
-                let expansion_cost = self.context.gas_meter.memory_expansion_cost(
-                    self.context.memory.size(), 
-                    new_size
-                );
-                self.context.gas_meter.consume(expansion_cost)?;
-                
-                let data = self.context.memory.read_bytes(offset_usize, size_usize)?;
-                let topic0_bytes = topic0.to_bytes_be();
-                let topic1_bytes = topic1.to_bytes_be();
-                let topic2_bytes = topic2.to_bytes_be();
-                let topic3_bytes = topic3.to_bytes_be();
-                let mut topic0_array = [0u8; 32];
-                let mut topic1_array = [0u8; 32];
-                let mut topic2_array = [0u8; 32];
-                let mut topic3_array = [0u8; 32];
-                topic0_array.copy_from_slice(&topic0_bytes[..32]);
-                topic1_array.copy_from_slice(&topic1_bytes[..32]);
-                topic2_array.copy_from_slice(&topic2_bytes[..32]);
-                topic3_array.copy_from_slice(&topic3_bytes[..32]);
-                let topics = vec![
-                    Hash::new(topic0_array),
-                    Hash::new(topic1_array),
-                    Hash::new(topic2_array),
-                    Hash::new(topic3_array),
-                ];
-                self.context.event_logger.log(self.context.address, topics, Bytes::new(data));
+
+            // Contract creation
+            Opcode::Create | Opcode::Create2 => {
+                self.execute_create(opcode)?;
+            }
+
+            Opcode::Selfdestruct => {
+                self.execute_selfdestruct()?;
+            }
+
+            // Message calls
+            Opcode::Call | Opcode::Callcode | Opcode::Delegatecall | Opcode::Staticcall => {
+                self.execute_call(opcode)?;
             }
 
             // Return operations
             Opcode::Return => {
                 let offset = self.context.stack.pop()?;
                 let size = self.context.stack.pop()?;
-                let offset_usize = offset.to_u64() as usize;
-                let size_usize = size.to_u64() as usize;
-                self.context.return_data = Bytes::new(self.context.memory.read_bytes(offset_usize, size_usize)?);
+                let (offset, size) = self.expand_memory(offset, size)?;
+                self.context.return_data = Bytes::new(self.context.memory.read_bytes(offset, size)?);
                 self.context.halt(true, "RETURN instruction".to_string());
             }
             Opcode::Revert => {
                 let offset = self.context.stack.pop()?;
                 let size = self.context.stack.pop()?;
-                let offset_usize = offset.to_u64() as usize;
-                let size_usize = size.to_u64() as usize;
-                self.context.return_data = Bytes::new(self.context.memory.read_bytes(offset_usize, size_usize)?);
+                let (offset, size) = self.expand_memory(offset, size)?;
+                self.context.return_data = Bytes::new(self.context.memory.read_bytes(offset, size)?);
                 self.context.halt(false, "REVERT instruction".to_string());
             }
+            Opcode::Invalid => {
+                return Err(OpcodeError::InvalidOpcode { opcode: Opcode::Invalid as u8 }.into());
+            }
 
             _ => {
-                return Err(ExecutionError::InvalidInstruction(format!("Unimplemented opcode: {:?}", opcode)));
+                log::debug!("Unimplemented opcode: {:?}", opcode);
+                return Err(OpcodeError::InvalidOpcode { opcode: opcode as u8 }.into());
             }
         }
 
         Ok(())
     }
 
-    /// Convert Uint256 to signed i256
-    fn uint256_to_signed(&self, value: &Uint256) -> i128 {
-        let bytes = value.to_bytes_be();
-        let mut result = 0i128;
-        for &byte in &bytes[16..] {
-            result = (result << 8) | (byte as i128);
-        }
-        result
+    /// Check whether an opcode writes to world state
+    fn modifies_state(opcode: Opcode) -> bool {
+        matches!(
+            opcode,
+            Opcode::Sstore
+                | Opcode::Tstore
+                | Opcode::Log0
+                | Opcode::Log1
+                | Opcode::Log2
+                | Opcode::Log3
+                | Opcode::Log4
+                | Opcode::Create
+                | Opcode::Create2
+                | Opcode::Selfdestruct
+        )
     }
 
-    /// Convert signed i128 to Uint256
-    fn signed_to_uint256(&self, value: i128) -> Uint256 {
-        let mut bytes = [0u8; 32];
-        let value_bytes = value.to_be_bytes();
-        bytes[16..].copy_from_slice(&value_bytes);
-        Uint256::from_bytes_be(&bytes)
+    /// Execute CALL, CALLCODE, DELEGATECALL or STATICCALL
+    fn execute_call(&mut self, opcode: Opcode) -> Result<(), ExecutionError> {
+        let gas = self.context.stack.pop()?;
+        let target = Self::uint256_to_address(&self.context.stack.pop()?);
+        let value = match opcode {
+            Opcode::Call | Opcode::Callcode => self.context.stack.pop()?,
+            _ => Uint256::zero(),
+        };
+        let args_offset = self.context.stack.pop()?;
+        let args_size = self.context.stack.pop()?;
+        let ret_offset = self.context.stack.pop()?;
+        let ret_size = self.context.stack.pop()?;
+
+        if self.context.is_static && opcode == Opcode::Call && !value.is_zero() {
+            return Err(ExecutionError::StaticCallViolation);
+        }
+        self.access_account(target)?;
+
+        // Memory expansion covers both the argument and the return region
+        let (args_offset, args_size) = self.expand_memory(args_offset, args_size)?;
+        let (ret_offset, ret_size) = self.expand_memory(ret_offset, ret_size)?;
+
+        // Value transfer and account creation surcharges
+        let fork = self.context.fork();
+        let costs = self.context.gas_meter.costs();
+        let mut extra_cost = 0;
+        if !value.is_zero() {
+            extra_cost += costs.call_value;
+        }
+        let creates_account = if fork >= Hardfork::SpuriousDragon {
+            // Only value transfers can bring an empty account to life (EIP-161)
+            !value.is_zero() && self.context.storage.is_empty_account(&target)
+        } else {
+            !self.context.storage.account_exists(&target)
+        };
+        if opcode == Opcode::Call && creates_account {
+            extra_cost += costs.new_account;
+        }
+        let stipend = if value.is_zero() { 0 } else { costs.call_stipend };
+        self.context.gas_meter.consume(extra_cost)?;
+
+        // Forward at most all but one 64th of the remaining gas (EIP-150);
+        // before that the requested gas had to be available in full
+        let requested = gas.to_u64_safe().unwrap_or(u64::MAX);
+        let forwarded = if fork >= Hardfork::TangerineWhistle {
+            let available = self.context.gas_meter.available();
+            requested.min(available - available / 64)
+        } else {
+            requested
+        };
+        self.context.gas_meter.consume(forwarded)?;
+        let child_gas = forwarded + stipend;
+
+        let input = Bytes::new(self.context.memory.read_bytes(args_offset, args_size)?);
+
+        // Calls beyond the depth limit or without enough balance fail without running
+        self.context.return_data_buffer = Bytes::empty();
+        if self.context.depth >= MAX_CALL_DEPTH
+            || self.context.storage.get_balance(&self.context.address) < value
+        {
+            self.context.gas_meter.return_gas(child_gas);
+            self.context.stack.push(Uint256::zero())?;
+            return Ok(());
+        }
+
+        let precompile = Precompile::from_address_at(&target, fork);
+        let code = Bytes::new(self.context.storage.get_code(&target));
+        let mut child = match opcode {
+            Opcode::Call => self.context.child(target, self.context.address, value, input, code, child_gas),
+            Opcode::Callcode => self.context.child(self.context.address, self.context.address, value, input, code, child_gas),
+            Opcode::Delegatecall => self.context.child(
+                self.context.address,
+                self.context.caller,
+                self.context.call_value,
+                input,
+                code,
+                child_gas,
+            ),
+            _ => self.context.child(target, self.context.address, Uint256::zero(), input, code, child_gas),
+        };
+        if opcode == Opcode::Staticcall {
+            child.is_static = true;
+        }
+
+        // Value moves to the callee before its code runs
+        let checkpoint = self.checkpoint();
+        if opcode != Opcode::Delegatecall {
+            self.context.storage.transfer(&self.context.address, child.address, value)?;
+        }
+
+        let frame = match precompile {
+            Some(precompile) => {
+                Self::run_precompile(precompile, child.input_data.as_slice(), child_gas, self.context.fork())
+            }
+            None => self.run_child_frame(child)?,
+        };
+
+        // A failed call undoes the value transfer and everything the callee did
+        if frame.success {
+            self.commit(checkpoint);
+        } else {
+            self.revert_to(checkpoint);
+        }
+
+        self.context.gas_meter.return_gas(frame.gas_remaining);
+        let copy_size = ret_size.min(frame.return_data.len());
+        self.context.memory.write_bytes(ret_offset, &frame.return_data.as_slice()[..copy_size])?;
+        self.context.stack.push(if frame.success { Uint256::one() } else { Uint256::zero() })?;
+        self.context.return_data_buffer = frame.return_data;
+
+        Ok(())
     }
-}
 
-/// Result of EVM execution
-#[derive(Debug, Clone)]
-pub struct ExecutionResult {
-    /// Whether execution was successful
-    pub success: bool,
-    /// Return data
-    pub return_data: Bytes,
-    /// Gas used
-    pub gas_used: u64,
-    /// Gas remaining
-    pub gas_remaining: u64,
-    /// Event logs
-    pub logs: Vec<EventLog>,
-}
+    /// Charge for and perform the memory expansion covering `size` bytes at `offset`
+    ///
+    /// Every memory-touching opcode goes through here before accessing memory.
+    /// A zero size touches nothing, whatever the offset. Sizes above the memory
+    /// limit are rejected, and offsets too large to address run out of gas.
+    /// Returns the region as `(offset, size)`.
+    fn expand_memory(&mut self, offset: Uint256, size: Uint256) -> Result<(usize, usize), ExecutionError> {
+        if size.is_zero() {
+            return Ok((0, 0));
+        }
+        let limit = self.context.memory.limit();
+        let size = Self::saturating_usize(&size);
+        if size > limit {
+            return Err(MemoryError::ExpansionFailed { size, limit }.into());
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let out_of_gas = GasError::OutOfGas { required: u64::MAX, available: self.context.gas_meter.available() };
+        let Some(end) = offset.to_u64_safe().ok().and_then(|offset| (offset as usize).checked_add(size)) else {
+            return Err(out_of_gas.into());
+        };
+        let offset = end - size;
 
-    #[test]
-    fn test_simple_arithmetic() {
-        // Code: PUSH1 0x02 PUSH1 0x03 ADD STOP
-        let code = Bytes::from(vec![0x60, 0x02, 0x60, 0x03, 0x01, 0x00]);
-        let context = ExecutionContext::new(
-            Address::zero(),
-            Address::zero(),
-            Uint256::zero(),
-            Bytes::empty(),
-            code,
-            1000,
-        );
-        let mut executor = Executor::new(context);
-        let result = executor.execute().unwrap();
-        
-        assert!(result.success);
-        assert_eq!(result.return_data.len(), 0);
+        let cost = self.context.gas_meter.memory_expansion_cost(self.context.memory.size(), end);
+        self.context.gas_meter.consume(cost)?;
+        // Memory grows in whole words
+        self.context.memory.ensure_size(end.div_ceil(32) * 32)?;
+        Ok((offset, size))
     }
 
-    #[test]
-    fn test_stack_operations() {
-        // Code: PUSH1 0x01 PUSH1 0x02 DUP1 ADD STOP
-        let code = Bytes::from(vec![0x60, 0x01, 0x60, 0x02, 0x80, 0x01, 0x00]);
-        let context = ExecutionContext::new(
+    /// Charge `word_cost` for every 32-byte word of a `size`-byte operand
+    fn charge_words(&mut self, size: usize, word_cost: u64) -> Result<(), ExecutionError> {
+        let words = size.div_ceil(32) as u64;
+        self.context.gas_meter.consume(words.saturating_mul(word_cost))?;
+        Ok(())
+    }
+
+    /// Execute LOG0 to LOG4
+    fn execute_log(&mut self, topic_count: usize) -> Result<(), ExecutionError> {
+        let offset = self.context.stack.pop()?;
+        let size = self.context.stack.pop()?;
+        let topics = (0..topic_count)
+            .map(|_| self.context.stack.pop().map(|topic| Hash::new(topic.to_bytes_be())))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (offset, size) = self.expand_memory(offset, size)?;
+        let data_cost = (size as u64).saturating_mul(self.context.gas_meter.costs().log_data);
+        self.context.gas_meter.consume(data_cost)?;
+
+        let data = self.context.memory.read_bytes(offset, size)?;
+        self.context.event_logger.log(self.context.address, topics, Bytes::new(data));
+        Ok(())
+    }
+
+    /// Mark `address` as accessed, charging the cold surcharge the first time (EIP-2929)
+    ///
+    /// The base cost of BALANCE, EXTCODE* and CALL* already covers a warm access,
+    /// and is the only cost before Berlin.
+    fn access_account(&mut self, address: Address) -> Result<(), ExecutionError> {
+        let cold = !self.context.storage.warm_address(address);
+        if cold && self.context.fork() >= Hardfork::Berlin {
+            let costs = self.context.gas_meter.costs();
+            let surcharge = costs.cold_account_access - costs.warm_storage_read;
+            self.context.gas_meter.consume(surcharge)?;
+        }
+        Ok(())
+    }
+
+    /// Run a precompiled contract in place of bytecode
+    fn run_precompile(precompile: Precompile, input: &[u8], gas_limit: u64, fork: Hardfork) -> FrameResult {
+        match precompile.execute(input, gas_limit, fork) {
+            Ok(output) => FrameResult {
+                success: true,
+                return_data: Bytes::new(output.output),
+                gas_remaining: gas_limit - output.gas_used,
+            },
+            // Failing precompiles consume all forwarded gas
+            Err(_) => FrameResult {
+                success: false,
+                return_data: Bytes::empty(),
+                gas_remaining: 0,
+            },
+        }
+    }
+
+    /// Execute CREATE or CREATE2
+    fn execute_create(&mut self, opcode: Opcode) -> Result<(), ExecutionError> {
+        let value = self.context.stack.pop()?;
+        let offset = self.context.stack.pop()?;
+        let size = self.context.stack.pop()?;
+        let salt = if opcode == Opcode::Create2 {
+            Some(self.context.stack.pop()?)
+        } else {
+            None
+        };
+
+        let (offset, size) = self.expand_memory(offset, size)?;
+        // Init code is capped and metered per word from Shanghai (EIP-3860)
+        if let Some(limit) = self.context.fork().max_initcode_size().filter(|&limit| size > limit) {
+            return Err(ExecutionError::InitcodeSizeExceeded { size, limit });
+        }
+        self.charge_words(size, self.context.gas_meter.costs().initcode_word)?;
+        if salt.is_some() {
+            // CREATE2 pays for hashing the init code
+            self.charge_words(size, self.context.gas_meter.costs().keccak256_word)?;
+        }
+
+        let init_code = self.context.memory.read_bytes(offset, size)?;
+
+        // Depth and balance failures leave the creator's nonce untouched
+        self.context.return_data_buffer = Bytes::empty();
+        let creator = self.context.address;
+        if self.context.depth >= MAX_CALL_DEPTH || self.context.storage.get_balance(&creator) < value {
+            self.context.stack.push(Uint256::zero())?;
+            return Ok(());
+        }
+
+        let nonce = self.context.storage.get_nonce(&creator);
+        self.context.storage.increment_nonce(creator);
+        let new_address = match salt {
+            Some(salt) => create2_address(&creator, &salt, &init_code),
+            None => create_address(&creator, nonce.to_u64()),
+        };
+        self.context.storage.warm_address(new_address);
+
+        // All but one 64th of the remaining gas goes to the init code (EIP-150)
+        let available = self.context.gas_meter.available();
+        let child_gas = if self.context.fork() >= Hardfork::TangerineWhistle {
+            available - available / 64
+        } else {
+            available
+        };
+        self.context.gas_meter.consume(child_gas)?;
+
+        // Deploying over an account with code, a nonce or storage fails and burns the gas
+        if create_collision(&self.context.storage, &new_address) {
+            self.context.stack.push(Uint256::zero())?;
+            return Ok(());
+        }
+
+        // New contracts start with nonce 1 from Spurious Dragon (EIP-161); a failed
+        // creation drops the address from `created_contracts` again on revert
+        let checkpoint = self.checkpoint();
+        self.context.substate.created_contracts.insert(new_address);
+        let nonce = self.context.fork().initial_contract_nonce();
+        self.context.storage.set_nonce(new_address, Uint256::from_u64(nonce));
+        self.context.storage.transfer(&creator, new_address, value)?;
+
+        let child = self.context.child(new_address, creator, value, Bytes::empty(), Bytes::new(init_code), child_gas);
+        let frame = self.run_child_frame(child)?;
+
+        let deployed = if frame.success {
+            self.deposit_code(new_address, &frame)
+        } else {
+            None
+        };
+
+        match deployed {
+            Some(gas_remaining) => {
+                self.commit(checkpoint);
+                self.context.gas_meter.return_gas(gas_remaining);
+                self.context.stack.push(Uint256::from_bytes_be(new_address.as_bytes()))?;
+            }
+            None => {
+                if !frame.success {
+                    // Reverted init code keeps its unused gas and exposes its revert data
+                    self.context.gas_meter.return_gas(frame.gas_remaining);
+                    self.context.return_data_buffer = frame.return_data;
+                }
+                self.revert_to(checkpoint);
+                self.context.stack.push(Uint256::zero())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Execute SELFDESTRUCT
+    ///
+    /// The balance always moves to the beneficiary. Before Cancun the account is
+    /// scheduled for deletion; with EIP-6780 only contracts created in the same
+    /// transaction are deleted.
+    fn execute_selfdestruct(&mut self) -> Result<(), ExecutionError> {
+        let beneficiary = Self::uint256_to_address(&self.context.stack.pop()?);
+        let address = self.context.address;
+        let balance = self.context.storage.get_balance(&address);
+
+        let fork = self.context.fork();
+        let costs = self.context.gas_meter.costs();
+        let mut extra_cost = 0;
+        let cold = !self.context.storage.warm_address(beneficiary);
+        if cold && fork >= Hardfork::Berlin {
+            extra_cost += costs.cold_account_access;
+        }
+        let creates_account = if fork >= Hardfork::SpuriousDragon {
+            !balance.is_zero() && self.context.storage.is_empty_account(&beneficiary)
+        } else {
+            !self.context.storage.account_exists(&beneficiary)
+        };
+        if creates_account && fork >= Hardfork::TangerineWhistle {
+            extra_cost += costs.new_account;
+        }
+        let refund = costs.selfdestruct_refund as i64;
+        self.context.gas_meter.consume(extra_cost)?;
+
+        let destroy = fork < Hardfork::Cancun || self.context.substate.created_contracts.contains(&address);
+        if beneficiary != address {
+            self.context.storage.transfer(&address, beneficiary, balance)?;
+        } else if destroy {
+            // Sending the balance to itself burns it when the account is destroyed
+            self.context.storage.set_balance(address, Uint256::zero());
+        }
+
+        if destroy && self.context.substate.selfdestructs.insert(address) {
+            // Refunded once per account before London (EIP-3529)
+            self.context.substate.refund += refund;
+        }
+
+        self.context.halt(true, "SELFDESTRUCT instruction".to_string());
+        Ok(())
+    }
+
+    /// Delete the accounts that self-destructed during the transaction
+    ///
+    /// Part of transaction finalization, so `execute` leaves it to the caller:
+    /// `TransactionProcessor` runs it after the code deposit and refunds.
+    pub fn apply_selfdestructs(&mut self) {
+        for address in std::mem::take(&mut self.context.substate.selfdestructs) {
+            self.context.storage.delete_account(&address);
+        }
+    }
+
+    /// Store the runtime code returned by init code, returning the gas left after the deposit
+    ///
+    /// Returns `None` if the code cannot be deployed; see [`code_deposit_cost`].
+    fn deposit_code(&mut self, address: Address, frame: &FrameResult) -> Option<u64> {
+        let code = frame.return_data.as_slice();
+        let deposit_cost = code_deposit_cost(code, self.context.gas_meter.costs(), self.context.fork()).ok()?;
+        if deposit_cost > frame.gas_remaining {
+            // Frontier deploys empty code instead of failing the creation
+            return (self.context.fork() < Hardfork::Homestead).then_some(frame.gas_remaining);
+        }
+
+        self.context.storage.set_code(address, code.to_vec());
+        Some(frame.gas_remaining - deposit_cost)
+    }
+
+    /// Open a checkpoint over the world state, the event log and the substate
+    fn checkpoint(&mut self) -> StateCheckpoint {
+        StateCheckpoint {
+            storage: self.context.storage.checkpoint(),
+            log_count: self.context.event_logger.count(),
+            substate: self.context.substate.clone(),
+        }
+    }
+
+    /// Undo every state change, log and substate entry since the checkpoint
+    fn revert_to(&mut self, checkpoint: StateCheckpoint) {
+        self.context.storage.revert_to(checkpoint.storage);
+        self.context.event_logger.truncate(checkpoint.log_count);
+        self.context.substate = checkpoint.substate;
+    }
+
+    /// Keep the state changes and logs made since the checkpoint
+    fn commit(&mut self, checkpoint: StateCheckpoint) {
+        self.context.storage.commit(checkpoint.storage);
+    }
+
+    /// Run a nested call frame that shares world state and the tracer with this one
+    fn run_child_frame(&mut self, mut child: ExecutionContext) -> Result<FrameResult, ExecutionError> {
+        child.storage = std::mem::take(&mut self.context.storage);
+        child.event_logger = std::mem::take(&mut self.context.event_logger);
+        child.substate = self.context.substate.clone();
+
+        let mut executor = Executor {
+            context: child,
+            tracer: self.tracer.take(),
+        };
+        let outcome = executor.run();
+
+        self.tracer = executor.tracer.take();
+        self.context.storage = std::mem::take(&mut executor.context.storage);
+        self.context.event_logger = std::mem::take(&mut executor.context.event_logger);
+        outcome?;
+        if executor.context.success {
+            // A failed frame's substate changes are discarded
+            self.context.substate = std::mem::take(&mut executor.context.substate);
+        }
+
+        // Exceptionally halted frames have already consumed all of their gas
+        Ok(FrameResult {
+            success: executor.context.success,
+            return_data: executor.context.return_data,
+            gas_remaining: executor.context.gas_meter.available(),
+        })
+    }
+
+    /// Interpret the low 20 bytes of a stack word as an address
+    fn uint256_to_address(value: &Uint256) -> Address {
+        let bytes = value.to_bytes_be();
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&bytes[12..]);
+        Address::new(address)
+    }
+
+    /// Convert a stack word to `usize`, saturating values that do not fit
+    fn saturating_usize(value: &Uint256) -> usize {
+        value.to_u64_safe().map_or(usize::MAX, |value| value as usize)
+    }
+
+    /// Clamp a shift operand so that anything of 256 or more shifts everything out
+    fn shift_amount(shift: &Uint256) -> usize {
+        shift.to_u64_safe().map_or(256, |shift| shift.min(256) as usize)
+    }
+}
+
+/// Whether creating a contract at `address` collides with an existing account
+///
+/// An account with code, a non-zero nonce (EIP-684) or non-empty storage
+/// (EIP-7610) cannot be deployed over.
+pub fn create_collision(storage: &Storage, address: &Address) -> bool {
+    storage
+        .get_account_info(address)
+        .is_some_and(|info| info.has_code() || !info.nonce.is_zero())
+        || storage.has_storage(address)
+}
+
+/// Gas charged for storing runtime code returned by init code
+///
+/// Fails if the code breaks the EIP-170 size limit or, from London, starts with
+/// the reserved 0xEF byte (EIP-3541).
+pub fn code_deposit_cost(code: &[u8], costs: &GasCosts, fork: Hardfork) -> Result<u64, HaltReason> {
+    if fork.max_code_size().is_some_and(|max| code.len() > max) {
+        return Err(HaltReason::CodeSizeLimit);
+    }
+    if fork >= Hardfork::London && code.first() == Some(&0xef) {
+        return Err(HaltReason::InvalidCodePrefix);
+    }
+    Ok(code.len() as u64 * costs.create_data)
+}
+
+/// Compute the address of a contract created with CREATE
+///
+/// The address is the last 20 bytes of `keccak256(rlp([sender, nonce]))`.
+pub fn create_address(sender: &Address, nonce: u64) -> Address {
+    let mut encoded = Vec::with_capacity(30);
+    rlp::encode_list(&[sender, &nonce], &mut encoded);
+
+    let hash = Keccak256::digest(&encoded);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    Address::new(address)
+}
+
+/// Compute the address of a contract created with CREATE2
+///
+/// The address is the last 20 bytes of `keccak256(0xff ++ sender ++ salt ++ keccak256(init_code))`.
+pub fn create2_address(sender: &Address, salt: &Uint256, init_code: &[u8]) -> Address {
+    let mut preimage = Vec::with_capacity(85);
+    preimage.push(0xff);
+    preimage.extend_from_slice(sender.as_bytes());
+    preimage.extend_from_slice(&salt.to_bytes_be());
+    preimage.extend_from_slice(&Keccak256::digest(init_code));
+
+    let hash = Keccak256::digest(&preimage);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    Address::new(address)
+}
+
+/// Outcome of a nested call frame
+struct FrameResult {
+    success: bool,
+    return_data: Bytes,
+    gas_remaining: u64,
+}
+
+/// Result of EVM execution
+#[derive(Debug, Clone)]
+pub struct ExecutionResult {
+    /// Whether execution was successful
+    pub success: bool,
+    /// How execution ended
+    pub outcome: ExecutionOutcome,
+    /// Return data
+    pub return_data: Bytes,
+    /// Gas used
+    pub gas_used: u64,
+    /// Gas remaining
+    pub gas_remaining: u64,
+    /// Refund counter at the end of execution, before any cap is applied
+    pub gas_refund: u64,
+    /// Event logs
+    pub logs: Vec<EventLog>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::MAX_INITCODE_SIZE;
+
+    #[test]
+    fn test_simple_arithmetic() {
+        // Code: PUSH1 0x02 PUSH1 0x03 ADD STOP
+        let code = Bytes::from(vec![0x60, 0x02, 0x60, 0x03, 0x01, 0x00]);
+        let context = ExecutionContext::new(
+            Address::zero(),
+            Address::zero(),
+            Uint256::zero(),
+            Bytes::empty(),
+            code,
+            1000,
+        );
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert_eq!(result.return_data.len(), 0);
+    }
+
+    #[test]
+    fn test_stack_operations() {
+        // Code: PUSH1 0x01 PUSH1 0x02 DUP1 ADD STOP
+        let code = Bytes::from(vec![0x60, 0x01, 0x60, 0x02, 0x80, 0x01, 0x00]);
+        let context = ExecutionContext::new(
             Address::zero(),
             Address::zero(),
             Uint256::zero(),
@@ -1147,4 +1754,882 @@ mod tests {
         
         assert!(result.success);
     }
+
+    fn contract_address(byte: u8) -> Address {
+        let mut bytes = [0u8; 20];
+        bytes[19] = byte;
+        Address::new(bytes)
+    }
+
+    #[test]
+    fn test_call_copies_return_data() {
+        // Callee: PUSH1 0x2a PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+        let callee_code = vec![0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+        // Caller: CALL(gas=0xff*0xff, 0xaa, 0, 0, 0, 0, 32) PUSH1 0x20 PUSH1 0x00 RETURN
+        let code = Bytes::from(vec![
+            0x60, 0x20, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0xaa,
+            0x60, 0xff, 0x60, 0xff, 0x02, 0xf1, 0x60, 0x20, 0x60, 0x00, 0xf3,
+        ]);
+        let mut context = ExecutionContext::new(
+            contract_address(0xbb),
+            Address::zero(),
+            Uint256::zero(),
+            Bytes::empty(),
+            code,
+            100000,
+        );
+        context.storage.set_code(contract_address(0xaa), callee_code);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert_eq!(result.return_data.as_slice()[31], 0x2a);
+        assert_eq!(executor.context.stack.peek().unwrap(), &Uint256::one());
+    }
+
+    #[test]
+    fn test_call_transfers_value() {
+        // Code: CALL(gas=0, 0xaa, value=7, 0, 0, 0, 0) STOP
+        let code = Bytes::from(vec![
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x07, 0x60, 0xaa,
+            0x60, 0x00, 0xf1, 0x00,
+        ]);
+        let mut context = ExecutionContext::new(
+            contract_address(0xbb),
+            Address::zero(),
+            Uint256::zero(),
+            Bytes::empty(),
+            code,
+            100000,
+        );
+        context.storage.set_balance(contract_address(0xbb), Uint256::from_u32(100));
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert_eq!(executor.context.storage.get_balance(&contract_address(0xbb)), Uint256::from_u32(93));
+        assert_eq!(executor.context.storage.get_balance(&contract_address(0xaa)), Uint256::from_u32(7));
+    }
+
+    #[test]
+    fn test_staticcall_rejects_sstore() {
+        // Callee: PUSH1 0x01 PUSH1 0x00 SSTORE STOP
+        let callee_code = vec![0x60, 0x01, 0x60, 0x00, 0x55, 0x00];
+        // Caller: STATICCALL(gas=0xff*0xff, 0xaa, 0, 0, 0, 0) STOP
+        let code = Bytes::from(vec![
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0xaa,
+            0x60, 0xff, 0x60, 0xff, 0x02, 0xfa, 0x00,
+        ]);
+        let mut context = ExecutionContext::new(
+            contract_address(0xbb),
+            Address::zero(),
+            Uint256::zero(),
+            Bytes::empty(),
+            code,
+            100000,
+        );
+        context.storage.set_code(contract_address(0xaa), callee_code);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert!(executor.context.stack.peek().unwrap().is_zero());
+        assert!(executor.context.storage.get_storage(&contract_address(0xaa), &Uint256::zero()).is_zero());
+    }
+
+    #[test]
+    fn test_delegatecall_writes_caller_storage() {
+        // Callee: PUSH1 0x01 PUSH1 0x00 SSTORE STOP
+        let callee_code = vec![0x60, 0x01, 0x60, 0x00, 0x55, 0x00];
+        // Caller: DELEGATECALL(gas=0xff*0xff, 0xaa, 0, 0, 0, 0) STOP
+        let code = Bytes::from(vec![
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0xaa,
+            0x60, 0xff, 0x60, 0xff, 0x02, 0xf4, 0x00,
+        ]);
+        let mut context = ExecutionContext::new(
+            contract_address(0xbb),
+            Address::zero(),
+            Uint256::zero(),
+            Bytes::empty(),
+            code,
+            100000,
+        );
+        context.storage.set_code(contract_address(0xaa), callee_code);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert_eq!(executor.context.stack.peek().unwrap(), &Uint256::one());
+        assert_eq!(executor.context.storage.get_storage(&contract_address(0xbb), &Uint256::zero()), Uint256::one());
+        assert!(executor.context.storage.get_storage(&contract_address(0xaa), &Uint256::zero()).is_zero());
+    }
+
+    #[test]
+    fn test_reverted_call_rolls_back_state() {
+        // Callee: SSTORE(1, 0x2a) LOG0(0, 0) REVERT(0, 0)
+        let callee_code = vec![
+            0x60, 0x2a, 0x60, 0x01, 0x55, 0x60, 0x00, 0x60, 0x00, 0xa0, 0x60, 0x00, 0x60, 0x00, 0xfd,
+        ];
+        // Caller: CALL(gas=0xff*0xff, 0xaa, value=7, 0, 0, 0, 0)
+        let code = Bytes::from(vec![
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x07, 0x60, 0xaa,
+            0x60, 0xff, 0x60, 0xff, 0x02, 0xf1,
+        ]);
+        let mut context = ExecutionContext::new(contract_address(0xbb), Address::zero(), Uint256::zero(), Bytes::empty(), code, 100000);
+        context.storage.set_code(contract_address(0xaa), callee_code);
+        context.storage.set_balance(contract_address(0xbb), Uint256::from_u32(100));
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert!(result.logs.is_empty());
+        assert!(executor.context.stack.peek().unwrap().is_zero());
+        assert!(executor.context.storage.get_storage(&contract_address(0xaa), &Uint256::one()).is_zero());
+        assert_eq!(executor.context.storage.get_balance(&contract_address(0xbb)), Uint256::from_u32(100));
+        assert!(executor.context.storage.get_balance(&contract_address(0xaa)).is_zero());
+    }
+
+    #[test]
+    fn test_top_level_revert_rolls_back_state() {
+        // Code: SSTORE(1, 0x2a) REVERT(0, 0)
+        let code = Bytes::from(vec![0x60, 0x2a, 0x60, 0x01, 0x55, 0x60, 0x00, 0x60, 0x00, 0xfd]);
+        let context = ExecutionContext::new(contract_address(0xbb), Address::zero(), Uint256::zero(), Bytes::empty(), code, 100000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(!result.success);
+        assert!(executor.context.storage.get_storage(&contract_address(0xbb), &Uint256::one()).is_zero());
+        assert_eq!(executor.context.storage.journal_len(), 0);
+    }
+
+    #[test]
+    fn test_call_depth_limit() {
+        // Code: CALL(gas=0xff, 0xaa, 0, 0, 0, 0, 0) STOP
+        let code = Bytes::from(vec![
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0xaa,
+            0x60, 0xff, 0xf1, 0x00,
+        ]);
+        let mut context = ExecutionContext::new(
+            contract_address(0xbb),
+            Address::zero(),
+            Uint256::zero(),
+            Bytes::empty(),
+            code,
+            100000,
+        );
+        context.depth = MAX_CALL_DEPTH;
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert!(executor.context.stack.peek().unwrap().is_zero());
+    }
+
+    /// Init code that copies the 10-byte runtime `PUSH1 0x2a PUSH1 0 MSTORE PUSH1 0x20 PUSH1 0 RETURN`
+    /// out of itself and returns it
+    fn init_code() -> Vec<u8> {
+        vec![
+            0x60, 0x0a, 0x60, 0x0c, 0x60, 0x00, 0x39, 0x60, 0x0a, 0x60, 0x00, 0xf3,
+            0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+        ]
+    }
+
+    #[test]
+    fn test_create_address_derivation() {
+        let sender = Address::from_hex("0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0").unwrap();
+        assert_eq!(create_address(&sender, 0).to_hex(), "0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d");
+        assert_eq!(create_address(&sender, 1).to_hex(), "0x343c43a37d37dff08ae8c4a11544c718abb4fcf8");
+
+        // EIP-1014 examples
+        assert_eq!(
+            create2_address(&Address::zero(), &Uint256::zero(), &[0x00]).to_hex(),
+            "0x4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38"
+        );
+        let sender = Address::from_hex("0xdeadbeef00000000000000000000000000000000").unwrap();
+        assert_eq!(
+            create2_address(&sender, &Uint256::zero(), &[0x00]).to_hex(),
+            "0xb928f69bb1d91cd65274e3c79d8986362984fda3"
+        );
+    }
+
+    #[test]
+    fn test_create_deploys_runtime_code() {
+        // Code: CODECOPY(0, 0x0f, 22) CREATE(0, 0, 22) STOP ++ init code
+        let mut code = vec![
+            0x60, 0x16, 0x60, 0x0f, 0x60, 0x00, 0x39, 0x60, 0x16, 0x60, 0x00, 0x60, 0x00, 0xf0, 0x00,
+        ];
+        code.extend(init_code());
+        let creator = contract_address(0xbb);
+        let context = ExecutionContext::new(
+            creator,
+            Address::zero(),
+            Uint256::zero(),
+            Bytes::empty(),
+            Bytes::from(code),
+            100000,
+        );
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        let expected = create_address(&creator, 0);
+        assert!(result.success);
+        assert_eq!(executor.context.stack.peek().unwrap(), &Uint256::from_bytes_be(expected.as_bytes()));
+        assert_eq!(executor.context.storage.get_code(&expected), init_code()[12..].to_vec());
+        assert_eq!(executor.context.storage.get_nonce(&creator), Uint256::one());
+        assert_eq!(executor.context.storage.get_nonce(&expected), Uint256::one());
+    }
+
+    #[test]
+    fn test_create_before_spurious_dragon_starts_at_nonce_zero() {
+        // Code: CODECOPY(0, 0x0f, 22) CREATE(0, 0, 22) STOP ++ init code
+        let mut code = vec![
+            0x60, 0x16, 0x60, 0x0f, 0x60, 0x00, 0x39, 0x60, 0x16, 0x60, 0x00, 0x60, 0x00, 0xf0, 0x00,
+        ];
+        code.extend(init_code());
+        let creator = contract_address(0xbb);
+        let mut context = ExecutionContext::new(
+            creator,
+            Address::zero(),
+            Uint256::zero(),
+            Bytes::empty(),
+            Bytes::from(code),
+            100000,
+        );
+        context.set_fork(Hardfork::Homestead);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+
+        let expected = create_address(&creator, 0);
+        assert!(result.success);
+        assert_eq!(executor.context.storage.get_code(&expected), init_code()[12..].to_vec());
+        assert_eq!(executor.context.storage.get_nonce(&creator), Uint256::one());
+        assert_eq!(executor.context.storage.get_nonce(&expected), Uint256::zero());
+    }
+
+    /// Run CREATE over `size` zero bytes of memory, which deploy an empty contract
+    fn create_zeros(size: u32, fork: Hardfork) -> ExecutionResult {
+        // Code: PUSH3 size PUSH1 0 PUSH1 0 CREATE STOP
+        let [_, high, mid, low] = size.to_be_bytes();
+        let code = Bytes::from(vec![0x62, high, mid, low, 0x60, 0x00, 0x60, 0x00, 0xf0, 0x00]);
+        let mut context =
+            ExecutionContext::new(contract_address(0xbb), Address::zero(), Uint256::zero(), Bytes::empty(), code, 1_000_000);
+        context.set_fork(fork);
+        Executor::new(context).execute().unwrap()
+    }
+
+    #[test]
+    fn test_create_initcode_limit_from_shanghai() {
+        let limit = MAX_INITCODE_SIZE as u32;
+        assert!(create_zeros(limit + 1, Hardfork::Paris).success);
+        assert!(create_zeros(limit, Hardfork::Shanghai).success);
+        assert_eq!(
+            create_zeros(limit + 1, Hardfork::Shanghai).outcome,
+            ExecutionOutcome::Halt(HaltReason::InitcodeSizeLimit)
+        );
+
+        // Shanghai also charges two gas per init code word
+        let words = (limit / 32) as u64;
+        assert_eq!(
+            create_zeros(limit, Hardfork::Shanghai).gas_used,
+            create_zeros(limit, Hardfork::Paris).gas_used + 2 * words
+        );
+    }
+
+    #[test]
+    fn test_create2_uses_salt() {
+        // Code: CODECOPY(0, 0x11, 22) CREATE2(0, 0, 22, salt=7) STOP ++ init code
+        let mut code = vec![
+            0x60, 0x16, 0x60, 0x11, 0x60, 0x00, 0x39, 0x60, 0x07, 0x60, 0x16, 0x60, 0x00, 0x60, 0x00, 0xf5, 0x00,
+        ];
+        code.extend(init_code());
+        let creator = contract_address(0xbb);
+        let context = ExecutionContext::new(
+            creator,
+            Address::zero(),
+            Uint256::zero(),
+            Bytes::empty(),
+            Bytes::from(code),
+            100000,
+        );
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        let expected = create2_address(&creator, &Uint256::from_u32(7), &init_code());
+        assert!(result.success);
+        assert_eq!(executor.context.stack.peek().unwrap(), &Uint256::from_bytes_be(expected.as_bytes()));
+        assert!(executor.context.storage.get_account(&expected).unwrap().is_contract());
+    }
+
+    #[test]
+    fn test_create_rejects_ef_prefix() {
+        // Init code: PUSH1 0xef PUSH1 0 MSTORE8 PUSH1 1 PUSH1 0 RETURN
+        let init = vec![0x60, 0xef, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xf3];
+        // Code: CODECOPY(0, 0x0f, 10) CREATE(0, 0, 10) STOP ++ init code
+        let mut code = vec![
+            0x60, 0x0a, 0x60, 0x0f, 0x60, 0x00, 0x39, 0x60, 0x0a, 0x60, 0x00, 0x60, 0x00, 0xf0, 0x00,
+        ];
+        code.extend(init);
+        let creator = contract_address(0xbb);
+        let context = ExecutionContext::new(
+            creator,
+            Address::zero(),
+            Uint256::zero(),
+            Bytes::empty(),
+            Bytes::from(code),
+            100000,
+        );
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert!(executor.context.stack.peek().unwrap().is_zero());
+        assert!(executor.context.storage.get_code(&create_address(&creator, 0)).is_empty());
+    }
+
+    fn selfdestruct_context(fork: Hardfork) -> ExecutionContext {
+        // Code: PUSH1 0xcc SELFDESTRUCT
+        let code = Bytes::from(vec![0x60, 0xcc, 0xff]);
+        let mut context = ExecutionContext::new(
+            contract_address(0xbb),
+            Address::zero(),
+            Uint256::zero(),
+            Bytes::empty(),
+            code.clone(),
+            100000,
+        );
+        context.set_fork(fork);
+        context.storage.set_code(contract_address(0xbb), code.as_slice().to_vec());
+        context.storage.set_balance(contract_address(0xbb), Uint256::from_u32(50));
+        context
+    }
+
+    #[test]
+    fn test_selfdestruct_pre_cancun_deletes_account() {
+        let mut executor = Executor::new(selfdestruct_context(Hardfork::Shanghai));
+        let result = executor.execute().unwrap();
+        executor.apply_selfdestructs();
+        
+        assert!(result.success);
+        // PUSH1 + SELFDESTRUCT + cold beneficiary + new account
+        assert_eq!(result.gas_used, 3 + 5000 + 2600 + 25000);
+        assert_eq!(executor.context.storage.get_balance(&contract_address(0xcc)), Uint256::from_u32(50));
+        assert!(executor.context.storage.get_code(&contract_address(0xbb)).is_empty());
+        assert!(!executor.context.storage.account_exists(&contract_address(0xbb)));
+
+        // Funding the destroyed address again creates a fresh account in the state root
+        let storage = &mut executor.context.storage;
+        storage.add_balance(contract_address(0xbb), Uint256::from_u32(7));
+        let mut expected = Storage::new();
+        expected.set_balance(contract_address(0xcc), Uint256::from_u32(50));
+        expected.set_balance(contract_address(0xbb), Uint256::from_u32(7));
+        assert_eq!(storage.state_root(), expected.state_root());
+    }
+
+    #[test]
+    fn test_selfdestruct_post_cancun_keeps_existing_contract() {
+        let mut executor = Executor::new(selfdestruct_context(Hardfork::Cancun));
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert_eq!(executor.context.storage.get_balance(&contract_address(0xcc)), Uint256::from_u32(50));
+        assert!(executor.context.storage.get_balance(&contract_address(0xbb)).is_zero());
+        assert!(!executor.context.storage.get_code(&contract_address(0xbb)).is_empty());
+    }
+
+    #[test]
+    fn test_selfdestruct_post_cancun_deletes_new_contract() {
+        // Init code: PUSH1 0xcc SELFDESTRUCT
+        // Code: CODECOPY(0, 0x0f, 3) CREATE(0, 0, 3) STOP ++ init code
+        let code = Bytes::from(vec![
+            0x60, 0x03, 0x60, 0x0f, 0x60, 0x00, 0x39, 0x60, 0x03, 0x60, 0x00, 0x60, 0x00, 0xf0, 0x00,
+            0x60, 0xcc, 0xff,
+        ]);
+        let creator = contract_address(0xbb);
+        let context = ExecutionContext::new(creator, Address::zero(), Uint256::zero(), Bytes::empty(), code, 100000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        executor.apply_selfdestructs();
+        
+        assert!(result.success);
+        let created = create_address(&creator, 0);
+        assert!(!executor.context.storage.account_exists(&created));
+    }
+
+    #[test]
+    fn test_create_collides_with_storage() {
+        // Code: CODECOPY(0, 0x0f, 1) CREATE(0, 0, 1) STOP ++ init code STOP
+        let code = Bytes::from(vec![
+            0x60, 0x01, 0x60, 0x0f, 0x60, 0x00, 0x39, 0x60, 0x01, 0x60, 0x00, 0x60, 0x00, 0xf0, 0x00, 0x00,
+        ]);
+        let creator = contract_address(0xbb);
+        let mut context = ExecutionContext::new(creator, Address::zero(), Uint256::zero(), Bytes::empty(), code, 100000);
+        context.storage.set_storage(create_address(&creator, 0), Uint256::one(), Uint256::one());
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+
+        assert!(result.success);
+        assert!(executor.context.stack.peek().unwrap().is_zero());
+        assert_eq!(executor.context.storage.get_nonce(&create_address(&creator, 0)), Uint256::zero());
+    }
+
+    #[test]
+    fn test_code_deposit_out_of_gas_before_homestead() {
+        // Init code: RETURN(0, 32), 6400 gas to deposit
+        // Code: MSTORE(0, init code) CREATE(0, 27, 5), ending the frame with the CREATE
+        let code = Bytes::from(vec![
+            0x64, 0x60, 0x20, 0x60, 0x00, 0xf3, 0x60, 0x00, 0x52, 0x60, 0x05, 0x60, 0x1b, 0x60, 0x00, 0xf0,
+        ]);
+        let creator = contract_address(0xbb);
+        for fork in [Hardfork::Frontier, Hardfork::Homestead] {
+            // Enough for CREATE itself and the init code, not for the deposit
+            let mut context = ExecutionContext::new(creator, Address::zero(), Uint256::zero(), Bytes::empty(), code.clone(), 33_000);
+            context.set_fork(fork);
+            let mut executor = Executor::new(context);
+            assert!(executor.execute().unwrap().success);
+
+            // Frontier deploys empty code; Homestead fails the creation
+            let created = !executor.context.stack.peek().unwrap().is_zero();
+            assert_eq!(created, fork == Hardfork::Frontier);
+            assert_eq!(executor.context.storage.account_exists(&create_address(&creator, 0)), created);
+            assert!(executor.context.storage.get_code(&create_address(&creator, 0)).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_failed_create_is_not_recorded_as_created() {
+        // Init code: REVERT(0, 0)
+        // Code: CODECOPY(0, 0x0f, 5) CREATE(0, 0, 5) STOP ++ init code
+        let code = Bytes::from(vec![
+            0x60, 0x05, 0x60, 0x0f, 0x60, 0x00, 0x39, 0x60, 0x05, 0x60, 0x00, 0x60, 0x00, 0xf0, 0x00,
+            0x60, 0x00, 0x60, 0x00, 0xfd,
+        ]);
+        let creator = contract_address(0xbb);
+        let context = ExecutionContext::new(creator, Address::zero(), Uint256::zero(), Bytes::empty(), code, 100000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+
+        assert!(result.success);
+        assert!(executor.context.stack.peek().unwrap().is_zero());
+        assert!(!executor.context.substate.created_contracts.contains(&create_address(&creator, 0)));
+    }
+
+    fn precompile_call_code(gas: u8) -> Bytes {
+        // Code: STATICCALL(gas, 0x02, 0, 0, 0, 32) PUSH1 0x20 PUSH1 0x00 RETURN
+        Bytes::from(vec![
+            0x60, 0x20, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x02, 0x60, gas, 0xfa,
+            0x60, 0x20, 0x60, 0x00, 0xf3,
+        ])
+    }
+
+    #[test]
+    fn test_call_runs_precompile() {
+        let code = precompile_call_code(0xff);
+        let context = ExecutionContext::new(contract_address(0xbb), Address::zero(), Uint256::zero(), Bytes::empty(), code, 100000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert_eq!(result.return_data.as_slice(), sha2::Sha256::digest([]).as_slice());
+    }
+
+    #[test]
+    fn test_precompile_out_of_gas_fails_call() {
+        // SHA256 of empty input needs 60 gas
+        let code = precompile_call_code(0x10);
+        let context = ExecutionContext::new(contract_address(0xbb), Address::zero(), Uint256::zero(), Bytes::empty(), code, 100000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert_eq!(result.return_data.as_slice(), &[0u8; 32]);
+        assert_eq!(executor.context.stack.peek().unwrap(), &Uint256::zero());
+    }
+
+    #[test]
+    fn test_sar_sign_extends() {
+        // Code: PUSH1 0x0f NOT PUSH1 0x02 SAR STOP  (-16 >> 2)
+        let code = Bytes::from(vec![0x60, 0x0f, 0x19, 0x60, 0x02, 0x1d, 0x00]);
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 1000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        let minus_four = Uint256::from_u32(3) ^ Uint256::from_bytes_be(&[0xFF; 32]);
+        assert_eq!(executor.context.stack.peek().unwrap(), &minus_four);
+    }
+
+    #[test]
+    fn test_calldatacopy_zero_fills() {
+        // Code: CALLDATACOPY(0, 1, 4)
+        let code = Bytes::from(vec![0x60, 0x04, 0x60, 0x01, 0x60, 0x00, 0x37]);
+        let context = ExecutionContext::new(
+            Address::zero(),
+            Address::zero(),
+            Uint256::zero(),
+            Bytes::from(vec![0x01, 0x02, 0x03]),
+            code,
+            1000,
+        );
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert_eq!(executor.context.memory.read_bytes(0, 4).unwrap(), vec![0x02, 0x03, 0x00, 0x00]);
+        // Three pushes, base cost, one copied word and one word of memory expansion
+        assert_eq!(result.gas_used, 9 + 3 + 3 + 3);
+    }
+
+    #[test]
+    fn test_extcode_opcodes() {
+        // Code: PUSH1 0xaa EXTCODESIZE PUSH1 0xaa EXTCODEHASH PUSH1 0xcc EXTCODEHASH STOP
+        let code = Bytes::from(vec![0x60, 0xaa, 0x3b, 0x60, 0xaa, 0x3f, 0x60, 0xcc, 0x3f, 0x00]);
+        let mut context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 10000);
+        context.storage.set_code(contract_address(0xaa), vec![0x60, 0x2a]);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        // 0xaa is cold then warm, 0xcc is cold (EIP-2929)
+        assert_eq!(result.gas_used, 9 + 2600 + 100 + 2600 + 2);
+        let stack = &mut executor.context.stack;
+        assert!(stack.pop().unwrap().is_zero());
+        assert_eq!(stack.pop().unwrap(), Uint256::from_bytes_be(&Keccak256::digest([0x60, 0x2a])));
+        assert_eq!(stack.pop().unwrap(), Uint256::from_u32(2));
+    }
+
+    #[test]
+    fn test_sload_cold_then_warm() {
+        // Code: PUSH1 0 SLOAD PUSH1 0 SLOAD STOP
+        let code = Bytes::from(vec![0x60, 0x00, 0x54, 0x60, 0x00, 0x54, 0x00]);
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 10000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.gas_used, 3 + 2100 + 3 + 100 + 2);
+        // Warm sets end with the transaction
+        assert!(!executor.context.storage.is_warm_slot(&Address::zero(), &Uint256::zero()));
+    }
+
+    #[test]
+    fn test_fork_selects_schedule() {
+        // Code: PUSH1 0 SLOAD PUSH1 0 SLOAD STOP
+        let code = Bytes::from(vec![0x60, 0x00, 0x54, 0x60, 0x00, 0x54, 0x00]);
+        for (fork, sload) in [(Hardfork::Homestead, 50), (Hardfork::Byzantium, 200), (Hardfork::Istanbul, 800)] {
+            let mut context =
+                ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code.clone(), 10000);
+            context.set_fork(fork);
+            let result = Executor::new(context).execute().unwrap();
+            // No warm or cold accesses before Berlin
+            assert_eq!(result.gas_used, 3 + sload + 3 + sload + 2, "{}", fork);
+        }
+
+        // Code: PUSH2 0x0100 PUSH1 2 EXP STOP; the exponent is two bytes long
+        let code = Bytes::from(vec![0x61, 0x01, 0x00, 0x60, 0x02, 0x0a, 0x00]);
+        for (fork, exp_byte) in [(Hardfork::Homestead, 10), (Hardfork::SpuriousDragon, 50)] {
+            let mut context =
+                ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code.clone(), 1000);
+            context.set_fork(fork);
+            let result = Executor::new(context).execute().unwrap();
+            assert_eq!(result.gas_used, 3 + 3 + 10 + 2 * exp_byte + 2, "{}", fork);
+        }
+    }
+
+    #[test]
+    fn test_opcodes_follow_fork() {
+        // Code: PUSH0 STOP
+        let code = Bytes::from(vec![0x5f, 0x00]);
+        let mut context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code.clone(), 1000);
+        context.set_fork(Hardfork::London);
+        let result = Executor::new(context).execute().unwrap();
+        assert_eq!(result.outcome, ExecutionOutcome::Halt(HaltReason::InvalidOpcode(0x5f)));
+
+        let mut context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 1000);
+        context.set_fork(Hardfork::Shanghai);
+        assert!(Executor::new(context).execute().unwrap().success);
+    }
+
+    #[test]
+    fn test_selfdestruct_refund_before_london() {
+        let mut executor = Executor::new(selfdestruct_context(Hardfork::Istanbul));
+        let result = executor.execute().unwrap();
+        executor.apply_selfdestructs();
+
+        assert!(result.success);
+        // PUSH1 + SELFDESTRUCT + new account, with no cold surcharge
+        assert_eq!(result.gas_used, 3 + 5000 + 25000);
+        assert_eq!(result.gas_refund, 24000);
+        assert!(!executor.context.storage.account_exists(&contract_address(0xbb)));
+    }
+
+    #[test]
+    fn test_sstore_stipend_sentry() {
+        // Code: PUSH1 1 PUSH1 0 SSTORE
+        let code = Bytes::from(vec![0x60, 0x01, 0x60, 0x00, 0x55]);
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code.clone(), 2306);
+        let result = Executor::new(context).execute().unwrap();
+        assert_eq!(result.outcome, ExecutionOutcome::Halt(HaltReason::OutOfGas));
+
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 30000);
+        let result = Executor::new(context).execute().unwrap();
+        assert!(result.success);
+        assert_eq!(result.gas_used, 6 + 2100 + 20000);
+    }
+
+    #[test]
+    fn test_returndata_buffer_after_call() {
+        // Callee: PUSH1 0x2a PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+        let callee_code = vec![0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+        // Caller: CALL(gas=0xff*0xff, 0xaa, 0, 0, 0, 0, 0) RETURNDATASIZE
+        //         RETURNDATACOPY(0, 0, 32) PUSH1 0x20 PUSH1 0x00 RETURN
+        let code = Bytes::from(vec![
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0xaa,
+            0x60, 0xff, 0x60, 0xff, 0x02, 0xf1, 0x3d, 0x60, 0x20, 0x60, 0x00, 0x60, 0x00, 0x3e,
+            0x60, 0x20, 0x60, 0x00, 0xf3,
+        ]);
+        let mut context = ExecutionContext::new(contract_address(0xbb), Address::zero(), Uint256::zero(), Bytes::empty(), code, 100000);
+        context.storage.set_code(contract_address(0xaa), callee_code);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert_eq!(result.return_data.as_slice()[31], 0x2a);
+        assert_eq!(executor.context.stack.peek().unwrap(), &Uint256::from_u32(32));
+    }
+
+    #[test]
+    fn test_returndatacopy_out_of_bounds() {
+        // Code: RETURNDATACOPY(0, 0, 1) STOP with an empty buffer
+        let code = Bytes::from(vec![0x60, 0x01, 0x60, 0x00, 0x60, 0x00, 0x3e, 0x00]);
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 1000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert_eq!(result.outcome, ExecutionOutcome::Halt(HaltReason::ReturnDataOutOfBounds));
+        assert_eq!(result.gas_used, 1000);
+    }
+
+    #[test]
+    fn test_push0_and_gas() {
+        // Code: PUSH0 GAS
+        let code = Bytes::from(vec![0x5f, 0x5a]);
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 1000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert_eq!(executor.context.stack.pop().unwrap(), Uint256::from_u32(996));
+        assert!(executor.context.stack.pop().unwrap().is_zero());
+    }
+
+    #[test]
+    fn test_transient_storage_is_cleared_after_transaction() {
+        // Code: TSTORE(1, 0x2a) TLOAD(1)
+        let code = Bytes::from(vec![0x60, 0x2a, 0x60, 0x01, 0x5d, 0x60, 0x01, 0x5c]);
+        let context = ExecutionContext::new(contract_address(0xbb), Address::zero(), Uint256::zero(), Bytes::empty(), code, 1000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert_eq!(executor.context.stack.peek().unwrap(), &Uint256::from_u32(0x2a));
+        assert!(executor.context.storage.get_transient(&contract_address(0xbb), &Uint256::one()).is_zero());
+        assert!(executor.context.storage.get_storage(&contract_address(0xbb), &Uint256::one()).is_zero());
+    }
+
+    #[test]
+    fn test_mcopy() {
+        // Code: MSTORE(0, 0x2a) MCOPY(32, 0, 32)
+        let code = Bytes::from(vec![0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0x60, 0x20, 0x5e]);
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 1000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert_eq!(executor.context.memory.size(), 64);
+        assert_eq!(executor.context.memory.read_word(32).unwrap(), Uint256::from_u32(0x2a));
+    }
+
+    #[test]
+    fn test_fee_and_blob_opcodes() {
+        // Code: BASEFEE BLOBBASEFEE PUSH0 BLOBHASH PUSH1 0x01 BLOBHASH
+        let code = Bytes::from(vec![0x48, 0x4a, 0x5f, 0x49, 0x60, 0x01, 0x49]);
+        let mut context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 1000);
+        context.block_context.blob_base_fee = Uint256::from_u32(7);
+        context.transaction_context.blob_hashes = vec![Hash::new([0x01; 32])];
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        let stack = &mut executor.context.stack;
+        assert!(stack.pop().unwrap().is_zero());
+        assert_eq!(stack.pop().unwrap(), Uint256::from_bytes_be(&[0x01; 32]));
+        assert_eq!(stack.pop().unwrap(), Uint256::from_u32(7));
+        assert_eq!(stack.pop().unwrap(), Uint256::from_u64(20_000_000_000));
+    }
+
+    #[test]
+    fn test_push32_dup2_swap2_decoding() {
+        // Code: PUSH32 0x01..0x20 PUSH1 0x07 PUSH1 0x09 DUP2 SWAP2
+        let mut code = vec![0x7f];
+        code.extend(1..=32u8);
+        code.extend([0x60, 0x07, 0x60, 0x09, 0x81, 0x91]);
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), Bytes::from(code), 1000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        assert_eq!(executor.context.stack.size(), 4);
+        assert_eq!(executor.context.stack.pop().unwrap(), Uint256::from_u32(0x09));
+        assert_eq!(executor.context.stack.pop().unwrap(), Uint256::from_u32(0x07));
+        assert_eq!(executor.context.stack.pop().unwrap(), Uint256::from_u32(0x07));
+        assert_eq!(executor.context.stack.pop().unwrap().to_bytes_be().to_vec(), (1..=32u8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_signed_comparison_and_division() {
+        // Code: PUSH1 1 PUSH1 0 NOT SLT  PUSH1 2 PUSH1 8 PUSH1 0 SUB SDIV
+        let code = Bytes::from(vec![0x60, 0x01, 0x60, 0x00, 0x19, 0x12, 0x60, 0x02, 0x60, 0x08, 0x60, 0x00, 0x03, 0x05]);
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 1000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(result.success);
+        // -8 / 2 == -4 and -1 < 1
+        assert_eq!(executor.context.stack.pop().unwrap(), Uint256::from_u32(4).wrapping_neg());
+        assert_eq!(executor.context.stack.pop().unwrap(), Uint256::one());
+    }
+
+    #[test]
+    fn test_designated_invalid_opcode() {
+        let code = Bytes::from(vec![0xfe]);
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 1000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert!(!result.success);
+        assert_eq!(result.outcome, ExecutionOutcome::Halt(HaltReason::InvalidOpcode(0xfe)));
+        assert_eq!(result.gas_used, 1000);
+        assert_eq!(result.gas_remaining, 0);
+    }
+
+    #[test]
+    fn test_exceptional_halt_reasons() {
+        // (code, gas limit, expected halt)
+        let cases: [(Vec<u8>, u64, HaltReason); 4] = [
+            (vec![0x01], 1000, HaltReason::StackUnderflow),
+            (vec![0x60, 0x01, 0x60, 0x02, 0x01], 5, HaltReason::OutOfGas),
+            (vec![0x60, 0x10, 0x56], 1000, HaltReason::InvalidJump),
+            (vec![0x0c], 1000, HaltReason::InvalidOpcode(0x0c)),
+        ];
+        for (code, gas_limit, expected) in cases {
+            let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), Bytes::from(code), gas_limit);
+            let mut executor = Executor::new(context);
+            let result = executor.execute().unwrap();
+            
+            assert_eq!(result.outcome, ExecutionOutcome::Halt(expected));
+            assert_eq!(result.gas_used, gas_limit);
+        }
+    }
+
+    #[test]
+    fn test_memory_operands_out_of_range() {
+        let mut huge_mload = vec![0x7f];
+        huge_mload.extend([0xff; 32]);
+        huge_mload.push(0x51);
+        // Code: RETURN(0, 2^32), above the memory limit
+        let oversized_return = vec![0x64, 0x01, 0x00, 0x00, 0x00, 0x00, 0x60, 0x00, 0xf3];
+        // Code: LOG0(2^64, 1)
+        let huge_log = vec![0x60, 0x01, 0x68, 0x01, 0, 0, 0, 0, 0, 0, 0, 0, 0xa0];
+        let cases = [
+            (huge_mload, HaltReason::OutOfGas),
+            (oversized_return, HaltReason::MemoryLimit),
+            (huge_log, HaltReason::OutOfGas),
+        ];
+        for (code, expected) in cases {
+            let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), Bytes::from(code), 100_000);
+            let mut executor = Executor::new(context);
+            let result = executor.execute().unwrap();
+            assert_eq!(result.outcome, ExecutionOutcome::Halt(expected));
+            assert_eq!(executor.context.memory.size(), 0);
+        }
+
+        // A zero size touches no memory, whatever the offset
+        let mut code = vec![0x60, 0x00, 0x7f];
+        code.extend([0xff; 32]);
+        code.push(0xf3);
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), Bytes::from(code), 1000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        assert!(result.success);
+        assert_eq!(result.gas_used, 6);
+    }
+
+    #[test]
+    fn test_memory_limit_is_configurable() {
+        // Code: MSTORE(64, 1), growing memory to 96 bytes
+        let code = Bytes::from(vec![0x60, 0x01, 0x60, 0x40, 0x52]);
+        let mut context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code.clone(), 100_000);
+        context.set_memory_limit(64);
+        let child = context.child(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), Bytes::empty(), 1000);
+        assert_eq!(child.memory.limit(), 64);
+
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        assert_eq!(result.outcome, ExecutionOutcome::Halt(HaltReason::MemoryLimit));
+
+        let mut context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 100_000);
+        context.set_memory_limit(96);
+        let mut executor = Executor::new(context);
+        assert!(executor.execute().unwrap().success);
+        assert_eq!(executor.context.memory.size(), 96);
+    }
+
+    #[test]
+    fn test_memory_gas_for_log_and_sha3() {
+        // Code: LOG0(0, 32) STOP
+        let code = Bytes::from(vec![0x60, 0x20, 0x60, 0x00, 0xa0, 0x00]);
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 1000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        // Pushes, LOG0, one word of expansion, 32 data bytes and STOP
+        assert_eq!(result.gas_used, 3 + 3 + 375 + 3 + 32 * 8 + 2);
+
+        // Code: SHA3(0, 64) STOP
+        let code = Bytes::from(vec![0x60, 0x40, 0x60, 0x00, 0x20, 0x00]);
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 1000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        assert_eq!(result.gas_used, 3 + 3 + 30 + 2 * 6 + 2 * 3 + 2);
+        assert_eq!(executor.context.memory.size(), 64);
+    }
+
+    #[test]
+    fn test_jump_requires_jumpdest_instruction() {
+        // Code: PUSH1 0x04 JUMP STOP JUMPDEST PUSH1 0x01
+        let code = Bytes::from(vec![0x60, 0x04, 0x56, 0x00, 0x5b, 0x60, 0x01]);
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 1000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        assert!(result.success);
+        assert_eq!(executor.context.stack.peek().unwrap(), &Uint256::one());
+        
+        // Code: PUSH1 0x04 JUMP PUSH1 0x5b, where 0x5b is push data
+        let code = Bytes::from(vec![0x60, 0x04, 0x56, 0x60, 0x5b]);
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 1000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        assert_eq!(result.outcome, ExecutionOutcome::Halt(HaltReason::InvalidJump));
+    }
+
+    #[test]
+    fn test_revert_outcome_keeps_data_and_gas() {
+        // Code: MSTORE8(0, 0x2a) REVERT(0, 1)
+        let code = Bytes::from(vec![0x60, 0x2a, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xfd]);
+        let context = ExecutionContext::new(Address::zero(), Address::zero(), Uint256::zero(), Bytes::empty(), code, 1000);
+        let mut executor = Executor::new(context);
+        let result = executor.execute().unwrap();
+        
+        assert_eq!(result.outcome, ExecutionOutcome::Revert(Bytes::from(vec![0x2a])));
+        assert!(result.gas_remaining > 0);
+    }
 }
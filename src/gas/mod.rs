@@ -1,4 +1,4 @@
-use crate::types::Uint256;
+use crate::{hardfork::Hardfork, types::Uint256};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,6 +10,7 @@ pub enum GasError {
 }
 
 /// Gas costs for EVM operations
+#[derive(Debug, Clone)]
 pub struct GasCosts {
     // Arithmetic operations
     pub add: u64,
@@ -22,6 +23,7 @@ pub struct GasCosts {
     pub addmod: u64,
     pub mulmod: u64,
     pub exp: u64,
+    pub exp_byte: u64,
     pub signextend: u64,
 
     // Comparison operations
@@ -61,6 +63,7 @@ pub struct GasCosts {
     pub returndatasize: u64,
     pub returndatacopy: u64,
     pub extcodehash: u64,
+    pub copy_word: u64,
 
     // Block information
     pub blockhash: u64,
@@ -71,6 +74,9 @@ pub struct GasCosts {
     pub gaslimit: u64,
     pub chainid: u64,
     pub selfbalance: u64,
+    pub basefee: u64,
+    pub blobhash: u64,
+    pub blobbasefee: u64,
 
     // Storage operations
     pub sload: u64,
@@ -78,15 +84,21 @@ pub struct GasCosts {
     pub sstore_set: u64,
     pub sstore_reset: u64,
     pub sstore_clear: u64,
+    pub sstore_sentry: u64,
+    pub tload: u64,
+    pub tstore: u64,
 
     // Memory operations
     pub mload: u64,
     pub mstore: u64,
     pub mstore8: u64,
     pub msize: u64,
+    pub mcopy: u64,
+    pub memory_word: u64,
 
     // Stack operations
     pub push: u64,
+    pub push0: u64,
     pub dup: u64,
     pub swap: u64,
     pub pop: u64,
@@ -96,6 +108,7 @@ pub struct GasCosts {
     pub jumpi: u64,
     pub pc: u64,
     pub jumpdest: u64,
+    pub gas: u64,
 
     // Logging operations
     pub log0: u64,
@@ -108,10 +121,14 @@ pub struct GasCosts {
 
     // System operations
     pub create: u64,
+    pub create_data: u64,
     pub call: u64,
     pub callcode: u64,
     pub delegatecall: u64,
     pub staticcall: u64,
+    pub call_value: u64,
+    pub call_stipend: u64,
+    pub new_account: u64,
     pub return_: u64,
     pub revert: u64,
     pub selfdestruct: u64,
@@ -125,8 +142,22 @@ pub struct GasCosts {
     pub high: u64,
     pub warm_storage_read: u64,
     pub cold_storage_read: u64,
+    pub cold_account_access: u64,
     pub access_list_storage_key: u64,
     pub access_list_address: u64,
+
+    // Transaction costs
+    pub tx_base: u64,
+    pub tx_create: u64,
+    pub tx_data_zero: u64,
+    pub tx_data_non_zero: u64,
+    pub initcode_word: u64,
+    pub tx_access_list_address: u64,
+    pub tx_access_list_storage_key: u64,
+    pub tx_data_floor_token: u64,
+    pub blob_gas_per_blob: u64,
+    pub sstore_clears_schedule: u64,
+    pub max_refund_quotient: u64,
 }
 
 impl Default for GasCosts {
@@ -150,6 +181,7 @@ impl Default for GasCosts {
             addmod: 8,
             mulmod: 8,
             exp: 10,
+            exp_byte: 50,
             signextend: 5,
 
             // Comparison operations
@@ -189,6 +221,7 @@ impl Default for GasCosts {
             returndatasize: 2,
             returndatacopy: 3,
             extcodehash: 100,
+            copy_word: 3,
 
             // Block information
             blockhash: 20,
@@ -199,6 +232,9 @@ impl Default for GasCosts {
             gaslimit: 2,
             chainid: 2,
             selfbalance: 5,
+            basefee: 2,
+            blobhash: 3,
+            blobbasefee: 2,
 
             // Storage operations
             sload: 100,
@@ -206,15 +242,21 @@ impl Default for GasCosts {
             sstore_set: 20000,
             sstore_reset: 5000,
             sstore_clear: 15000,
+            sstore_sentry: 2300,
+            tload: 100,
+            tstore: 100,
 
             // Memory operations
             mload: 3,
             mstore: 3,
             mstore8: 3,
             msize: 2,
+            mcopy: 3,
+            memory_word: 3,
 
             // Stack operations
             push: 3,
+            push0: 2,
             dup: 3,
             swap: 3,
             pop: 2,
@@ -224,6 +266,7 @@ impl Default for GasCosts {
             jumpi: 10,
             pc: 2,
             jumpdest: 1,
+            gas: 2,
 
             // Logging operations
             log0: 375,
@@ -236,21 +279,100 @@ impl Default for GasCosts {
 
             // System operations
             create: 32000,
+            create_data: 200,
             call: 100,
             callcode: 100,
             delegatecall: 100,
             staticcall: 100,
+            call_value: 9000,
+            call_stipend: 2300,
+            new_account: 25000,
             return_: 0,
             revert: 0,
             selfdestruct: 5000,
-            selfdestruct_refund: 24000,
+            selfdestruct_refund: 0,
 
             // Access list costs
             warm_storage_read: 100,
             cold_storage_read: 2100,
+            cold_account_access: 2600,
             access_list_storage_key: 1900,
             access_list_address: 2400,
+
+            // Transaction costs
+            tx_base: 21000,
+            tx_create: 32000,
+            tx_data_zero: 4,
+            tx_data_non_zero: 16,
+            initcode_word: 2,
+            tx_access_list_address: 2400,
+            tx_access_list_storage_key: 1900,
+            tx_data_floor_token: 0,
+            blob_gas_per_blob: 131072,
+            sstore_clears_schedule: 4800,
+            max_refund_quotient: 5,
+        }
+    }
+}
+
+impl GasCosts {
+    /// The gas schedule in force at `fork`
+    ///
+    /// The default schedule is Cancun's; earlier forks undo the repricings
+    /// that came after them, and Prague adds its calldata floor.
+    pub fn for_fork(fork: Hardfork) -> Self {
+        use Hardfork::*;
+        let mut costs = GasCosts::default();
+        if fork >= Prague {
+            // Calldata floor price per token (EIP-7623)
+            costs.tx_data_floor_token = 10;
+        }
+        if fork < Shanghai {
+            // Init code metering (EIP-3860)
+            costs.initcode_word = 0;
+        }
+        if fork < London {
+            // Refund reductions (EIP-3529)
+            costs.sstore_clears_schedule = 15000;
+            costs.selfdestruct_refund = 24000;
+            costs.max_refund_quotient = 2;
+        }
+        if fork < Berlin {
+            // Flat state access prices, before warm and cold accesses (EIP-2929)
+            let (sload, balance, account) = match fork {
+                _ if fork >= Istanbul => (800, 700, 700),
+                _ if fork >= TangerineWhistle => (200, 400, 700),
+                _ => (50, 20, 20),
+            };
+            costs.sload = sload;
+            costs.sstore = sload;
+            costs.balance = balance;
+            costs.extcodesize = account;
+            costs.extcodecopy = account;
+            costs.extcodehash = if fork >= Istanbul { 700 } else { 400 };
+            let call = if fork >= TangerineWhistle { 700 } else { 40 };
+            costs.call = call;
+            costs.callcode = call;
+            costs.delegatecall = call;
+            costs.staticcall = call;
         }
+        if fork < Istanbul {
+            // Calldata repricing (EIP-2028)
+            costs.tx_data_non_zero = 68;
+        }
+        if fork < SpuriousDragon {
+            // EXP repricing (EIP-160)
+            costs.exp_byte = 10;
+        }
+        if fork < TangerineWhistle {
+            // IO-heavy repricing (EIP-150)
+            costs.selfdestruct = 0;
+        }
+        if fork < Homestead {
+            // Contract creation transactions cost the same as calls (EIP-2)
+            costs.tx_create = 0;
+        }
+        costs
     }
 }
 
@@ -262,6 +384,8 @@ pub struct GasMeter {
     limit: u64,
     /// Gas costs configuration
     costs: GasCosts,
+    /// Hardfork whose metering rules apply
+    fork: Hardfork,
 }
 
 impl GasMeter {
@@ -271,6 +395,7 @@ impl GasMeter {
             available: gas_limit,
             limit: gas_limit,
             costs: GasCosts::default(),
+            fork: Hardfork::default(),
         }
     }
 
@@ -280,6 +405,27 @@ impl GasMeter {
             available: gas_limit,
             limit: gas_limit,
             costs,
+            fork: Hardfork::default(),
+        }
+    }
+
+    /// Create a new gas meter with the schedule and rules of `fork`
+    pub fn for_fork(gas_limit: u64, fork: Hardfork) -> Self {
+        GasMeter {
+            available: gas_limit,
+            limit: gas_limit,
+            costs: GasCosts::for_fork(fork),
+            fork,
+        }
+    }
+
+    /// Create a meter for a nested frame with the same costs and rules
+    pub fn child(&self, gas_limit: u64) -> Self {
+        GasMeter {
+            available: gas_limit,
+            limit: gas_limit,
+            costs: self.costs.clone(),
+            fork: self.fork,
         }
     }
 
@@ -310,11 +456,14 @@ impl GasMeter {
         Ok(())
     }
 
-    /// Refund gas (up to half of the gas used)
-    pub fn refund(&mut self, amount: u64) {
-        let max_refund = self.used() / 2;
-        let refund = amount.min(max_refund);
-        self.available += refund;
+    /// Consume all remaining gas, as an exceptional halt does
+    pub fn consume_all(&mut self) {
+        self.available = 0;
+    }
+
+    /// Return gas that was forwarded to a sub-call but not used
+    pub fn return_gas(&mut self, amount: u64) {
+        self.available = (self.available + amount).min(self.limit);
     }
 
     /// Check if there's enough gas for an operation
@@ -327,27 +476,31 @@ impl GasMeter {
         &self.costs
     }
 
+    /// Get the hardfork whose rules apply
+    pub fn fork(&self) -> Hardfork {
+        self.fork
+    }
+
     /// Calculate gas cost for memory expansion
+    ///
+    /// Saturates at `u64::MAX` for sizes no gas limit could pay for.
     pub fn memory_expansion_cost(&self, current_size: usize, new_size: usize) -> u64 {
         if new_size <= current_size {
             return 0;
         }
-        
-        let current_words = (current_size + 31) / 32;
-        let new_words = (new_size + 31) / 32;
-        
-        if new_words <= current_words {
-            return 0;
-        }
-        
-        let additional_words = new_words - current_words;
-        let cost = additional_words * 3 + (new_words * new_words) / 512 - (current_words * current_words) / 512;
-        cost as u64
+        self.memory_cost(new_size).saturating_sub(self.memory_cost(current_size))
+    }
+
+    /// Total gas for a memory of `size` bytes: linear in words plus a quadratic term
+    fn memory_cost(&self, size: usize) -> u64 {
+        let words = size.div_ceil(32) as u128;
+        let cost = words * u128::from(self.costs.memory_word) + words * words / 512;
+        u64::try_from(cost).unwrap_or(u64::MAX)
     }
 
     /// Calculate gas cost for Keccak256 operation
     pub fn keccak256_cost(&self, data_size: usize) -> u64 {
-        let words = (data_size + 31) / 32;
+        let words = data_size.div_ceil(32);
         self.costs.keccak256 + (words as u64 * self.costs.keccak256_word)
     }
 
@@ -360,28 +513,72 @@ impl GasMeter {
         }
     }
 
-    /// Calculate gas cost for SSTORE operation
-    pub fn sstore_cost(&self, current_value: &Uint256, new_value: &Uint256, _original_value: &Uint256) -> u64 {
-        if current_value == new_value {
-            // No change
-            if current_value.is_zero() {
-                self.costs.sstore_clear
+    /// Gas for an SSTORE to a warm slot, net-metered against the slot's value at
+    /// the start of the transaction (EIP-2200, EIP-2929)
+    ///
+    /// A cold slot additionally costs `cold_storage_read`. Before Istanbul only
+    /// the current and new values matter.
+    pub fn sstore_cost(&self, current_value: &Uint256, new_value: &Uint256, original_value: &Uint256) -> u64 {
+        if self.fork < Hardfork::Istanbul {
+            return if current_value.is_zero() && !new_value.is_zero() {
+                self.costs.sstore_set
             } else {
                 self.costs.sstore_reset
-            }
+            };
+        }
+
+        if current_value == new_value || original_value != current_value {
+            // No-op, or a slot already dirtied by this transaction
+            self.costs.sstore
+        } else if original_value.is_zero() {
+            self.costs.sstore_set
+        } else {
+            self.sstore_reset_cost()
+        }
+    }
+
+    /// Gas for changing a clean, non-zero slot
+    fn sstore_reset_cost(&self) -> u64 {
+        if self.fork >= Hardfork::Berlin {
+            // The cold read is charged separately (EIP-2929)
+            self.costs.sstore_reset - self.costs.cold_storage_read
         } else {
-            // Value is changing
+            self.costs.sstore_reset
+        }
+    }
+
+    /// Change to the refund counter for an SSTORE (EIP-2200, EIP-3529)
+    ///
+    /// Negative when an earlier clearing refund is taken back.
+    pub fn sstore_refund(&self, current_value: &Uint256, new_value: &Uint256, original_value: &Uint256) -> i64 {
+        let clears = self.costs.sstore_clears_schedule as i64;
+        if current_value == new_value {
+            return 0;
+        }
+        if self.fork < Hardfork::Istanbul {
+            return if new_value.is_zero() { clears } else { 0 };
+        }
+        if original_value == current_value {
+            return if new_value.is_zero() { clears } else { 0 };
+        }
+
+        let mut refund = 0;
+        if !original_value.is_zero() {
             if current_value.is_zero() {
-                // Setting a zero value to non-zero
-                self.costs.sstore_set
+                refund -= clears;
             } else if new_value.is_zero() {
-                // Setting a non-zero value to zero
-                self.costs.sstore_clear
-            } else {
-                // Changing from one non-zero value to another
-                self.costs.sstore_reset
+                refund += clears;
             }
         }
+        if original_value == new_value {
+            // Restoring the original value refunds all but the no-op cost
+            refund += if original_value.is_zero() {
+                self.costs.sstore_set - self.costs.sstore
+            } else {
+                self.sstore_reset_cost() - self.costs.sstore
+            } as i64;
+        }
+        refund
     }
 
     /// Calculate gas cost for LOG operation
@@ -428,16 +625,64 @@ mod tests {
     }
 
     #[test]
-    fn test_gas_refund() {
+    fn test_sstore_net_metering() {
+        let meter = GasMeter::new(1000);
+        let (zero, one, two) = (Uint256::zero(), Uint256::one(), Uint256::from_u32(2));
+
+        // Clean slots: set, reset, clear
+        assert_eq!(meter.sstore_cost(&zero, &one, &zero), 20000);
+        assert_eq!(meter.sstore_cost(&one, &two, &one), 2900);
+        assert_eq!(meter.sstore_cost(&one, &zero, &one), 2900);
+        assert_eq!(meter.sstore_refund(&one, &zero, &one), 4800);
+        // No-ops and dirty slots cost a warm read
+        assert_eq!(meter.sstore_cost(&one, &one, &one), 100);
+        assert_eq!(meter.sstore_cost(&two, &zero, &one), 100);
+
+        // Dirty slots: undoing a clear, and restoring the original value
+        assert_eq!(meter.sstore_refund(&zero, &two, &one), -4800);
+        assert_eq!(meter.sstore_refund(&zero, &one, &one), -4800 + 2800);
+        assert_eq!(meter.sstore_refund(&one, &zero, &zero), 19900);
+        assert_eq!(meter.sstore_refund(&two, &zero, &one), 4800);
+    }
+
+    #[test]
+    fn test_fork_schedules() {
+        let (zero, one, two) = (Uint256::zero(), Uint256::one(), Uint256::from_u32(2));
+
+        // Istanbul net metering charges the flat SLOAD price for dirty slots
+        let istanbul = GasMeter::for_fork(1000, Hardfork::Istanbul);
+        assert_eq!(istanbul.sstore_cost(&two, &zero, &one), 800);
+        assert_eq!(istanbul.sstore_cost(&one, &two, &one), 5000);
+        assert_eq!(istanbul.sstore_refund(&one, &zero, &one), 15000);
+        assert_eq!(istanbul.sstore_refund(&two, &one, &one), 4200);
+
+        // Before Istanbul only the current and new values matter
+        let byzantium = GasMeter::for_fork(1000, Hardfork::Byzantium);
+        assert_eq!(byzantium.sstore_cost(&zero, &one, &one), 20000);
+        assert_eq!(byzantium.sstore_cost(&one, &one, &one), 5000);
+        assert_eq!(byzantium.sstore_refund(&two, &zero, &one), 15000);
+
+        let frontier = GasCosts::for_fork(Hardfork::Frontier);
+        assert_eq!((frontier.sload, frontier.call, frontier.exp_byte), (50, 40, 10));
+        assert_eq!(frontier.max_refund_quotient, 2);
+        assert_eq!(GasCosts::for_fork(Hardfork::Cancun).max_refund_quotient, 5);
+        assert_eq!(GasCosts::for_fork(Hardfork::Cancun).tx_data_floor_token, 0);
+        assert_eq!(GasCosts::for_fork(Hardfork::Prague).tx_data_floor_token, 10);
+        assert_eq!(GasMeter::for_fork(1000, Hardfork::Berlin).child(10).fork(), Hardfork::Berlin);
+    }
+
+    #[test]
+    fn test_return_gas() {
         let mut meter = GasMeter::new(1000);
         
-        // Use some gas
-        meter.consume(800).unwrap();
-        assert_eq!(meter.used(), 800);
+        // Forward gas to a sub-call and get part of it back
+        meter.consume(600).unwrap();
+        meter.return_gas(250);
+        assert_eq!(meter.available(), 650);
         
-        // Refund gas (max refund is half of used gas)
-        meter.refund(500);
-        assert_eq!(meter.available(), 600); // 200 + 400 (half of 800)
+        // Returned gas never exceeds the limit
+        meter.return_gas(5000);
+        assert_eq!(meter.available(), 1000);
     }
 
     #[test]
@@ -451,6 +696,11 @@ mod tests {
         // Cost for expanding from 32 to 64 bytes
         let cost = meter.memory_expansion_cost(32, 64);
         assert_eq!(cost, 3);
+        assert_eq!(meter.memory_expansion_cost(0, 64), 6);
+        
+        // The quadratic term dominates large memories, and absurd sizes saturate
+        assert_eq!(meter.memory_expansion_cost(0, 32 * 1024), 3 * 1024 + 1024 * 1024 / 512);
+        assert_eq!(meter.memory_expansion_cost(0, usize::MAX), u64::MAX);
     }
 
     #[test]
@@ -0,0 +1,152 @@
+use crate::block::BlockHashMode;
+use crate::validation::{MAX_CODE_SIZE, MAX_INITCODE_SIZE};
+use std::fmt;
+use std::str::FromStr;
+
+/// Ethereum protocol upgrades, in activation order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Hardfork {
+    Frontier,
+    Homestead,
+    TangerineWhistle,
+    SpuriousDragon,
+    Byzantium,
+    Constantinople,
+    Petersburg,
+    Istanbul,
+    Berlin,
+    London,
+    Paris,
+    Shanghai,
+    Cancun,
+    Prague,
+}
+
+impl Hardfork {
+    /// Every hardfork in activation order
+    pub const ALL: [Hardfork; 14] = [
+        Hardfork::Frontier,
+        Hardfork::Homestead,
+        Hardfork::TangerineWhistle,
+        Hardfork::SpuriousDragon,
+        Hardfork::Byzantium,
+        Hardfork::Constantinople,
+        Hardfork::Petersburg,
+        Hardfork::Istanbul,
+        Hardfork::Berlin,
+        Hardfork::London,
+        Hardfork::Paris,
+        Hardfork::Shanghai,
+        Hardfork::Cancun,
+        Hardfork::Prague,
+    ];
+
+    /// Human-readable name of the hardfork
+    pub fn name(self) -> &'static str {
+        match self {
+            Hardfork::Frontier => "Frontier",
+            Hardfork::Homestead => "Homestead",
+            Hardfork::TangerineWhistle => "Tangerine Whistle",
+            Hardfork::SpuriousDragon => "Spurious Dragon",
+            Hardfork::Byzantium => "Byzantium",
+            Hardfork::Constantinople => "Constantinople",
+            Hardfork::Petersburg => "Petersburg",
+            Hardfork::Istanbul => "Istanbul",
+            Hardfork::Berlin => "Berlin",
+            Hardfork::London => "London",
+            Hardfork::Paris => "Paris",
+            Hardfork::Shanghai => "Shanghai",
+            Hardfork::Cancun => "Cancun",
+            Hardfork::Prague => "Prague",
+        }
+    }
+
+    /// Largest runtime code a contract may deploy, if limited (EIP-170)
+    pub fn max_code_size(self) -> Option<usize> {
+        (self >= Hardfork::SpuriousDragon).then_some(MAX_CODE_SIZE)
+    }
+
+    /// Largest init code a creation may run, if limited (EIP-3860)
+    pub fn max_initcode_size(self) -> Option<usize> {
+        (self >= Hardfork::Shanghai).then_some(MAX_INITCODE_SIZE)
+    }
+
+    /// Nonce a newly created contract starts with: 1 from Spurious Dragon (EIP-161), 0 before
+    pub fn initial_contract_nonce(self) -> u64 {
+        u64::from(self >= Hardfork::SpuriousDragon)
+    }
+
+    /// Where block hashes are kept: also the EIP-2935 history contract from Prague
+    pub fn block_hash_mode(self) -> BlockHashMode {
+        if self >= Hardfork::Prague {
+            BlockHashMode::HistoryContract
+        } else {
+            BlockHashMode::Recent
+        }
+    }
+}
+
+impl Default for Hardfork {
+    /// The rules the executor implements when no fork is chosen
+    fn default() -> Self {
+        Hardfork::Cancun
+    }
+}
+
+impl FromStr for Hardfork {
+    type Err = String;
+
+    /// Parse a name such as `cancun`, `Tangerine Whistle` or `spurious-dragon`
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let normalize = |name: &str| -> String {
+            name.chars().filter(char::is_ascii_alphanumeric).map(|c| c.to_ascii_lowercase()).collect()
+        };
+        let wanted = normalize(text);
+        Hardfork::ALL
+            .into_iter()
+            .find(|fork| normalize(fork.name()) == wanted)
+            .ok_or_else(|| format!("unknown hardfork {:?}", text))
+    }
+}
+
+impl fmt::Display for Hardfork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hardfork_ordering() {
+        assert!(Hardfork::Frontier < Hardfork::Homestead);
+        assert!(Hardfork::Shanghai < Hardfork::Cancun);
+        assert!(Hardfork::Cancun < Hardfork::Prague);
+        assert!(Hardfork::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_hardfork_display() {
+        assert_eq!(Hardfork::TangerineWhistle.to_string(), "Tangerine Whistle");
+        assert_eq!(Hardfork::Cancun.to_string(), "Cancun");
+    }
+
+    #[test]
+    fn test_hardfork_from_str() {
+        assert_eq!("cancun".parse::<Hardfork>(), Ok(Hardfork::Cancun));
+        assert_eq!("Tangerine Whistle".parse::<Hardfork>(), Ok(Hardfork::TangerineWhistle));
+        assert_eq!("spurious-dragon".parse::<Hardfork>(), Ok(Hardfork::SpuriousDragon));
+        assert!("osaka".parse::<Hardfork>().is_err());
+        assert_eq!("prague".parse::<Hardfork>(), Ok(Hardfork::Prague));
+        assert_eq!(Hardfork::Homestead.max_code_size(), None);
+        assert_eq!(Hardfork::SpuriousDragon.max_code_size(), Some(MAX_CODE_SIZE));
+        assert_eq!(Hardfork::Paris.max_initcode_size(), None);
+        assert_eq!(Hardfork::Shanghai.max_initcode_size(), Some(2 * MAX_CODE_SIZE));
+        assert_eq!(Hardfork::Homestead.initial_contract_nonce(), 0);
+        assert_eq!(Hardfork::SpuriousDragon.initial_contract_nonce(), 1);
+        assert_eq!(Hardfork::Cancun.block_hash_mode(), BlockHashMode::Recent);
+        assert_eq!(Hardfork::Prague.block_hash_mode(), BlockHashMode::HistoryContract);
+    }
+}
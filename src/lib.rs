@@ -1,9 +1,13 @@
 pub mod types;
+pub mod rlp;
+pub mod trie;
 pub mod stack;
 pub mod memory;
 pub mod storage;
 pub mod opcodes;
+pub mod analysis;
 pub mod executor;
+pub mod transaction;
 pub mod gas;
 pub mod debug;
 pub mod events;
@@ -12,5 +16,7 @@ pub mod cli;
 pub mod validation;
 pub mod tracing;
 pub mod advanced;
+pub mod precompiles;
+pub mod hardfork;
 
 pub use types::*;
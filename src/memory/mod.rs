@@ -1,27 +1,40 @@
-use crate::types::Uint256;
+use crate::{types::Uint256, validation::MAX_MEMORY_SIZE};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum MemoryError {
     #[error("Memory access out of bounds: offset {offset}, size {size}")]
     OutOfBounds { offset: usize, size: usize },
-    #[error("Memory expansion failed: new size {size}")]
-    ExpansionFailed { size: usize },
+    #[error("Memory expansion failed: new size {size} exceeds the {limit} byte limit")]
+    ExpansionFailed { size: usize, limit: usize },
 }
 
 /// EVM Memory - expandable byte array
 pub struct Memory {
     data: Vec<u8>,
+    /// Largest size the memory may grow to
+    limit: usize,
 }
 
 impl Memory {
     /// Create a new empty memory
     pub fn new() -> Self {
+        Memory::with_limit(MAX_MEMORY_SIZE)
+    }
+
+    /// Create a new empty memory that can grow to at most `limit` bytes
+    pub fn with_limit(limit: usize) -> Self {
         Memory {
             data: Vec::new(),
+            limit,
         }
     }
 
+    /// Get the largest size the memory may grow to
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
     /// Get the current memory size in bytes
     pub fn size(&self) -> usize {
         self.data.len()
@@ -29,6 +42,9 @@ impl Memory {
 
     /// Ensure memory is at least the specified size
     pub fn ensure_size(&mut self, size: usize) -> Result<(), MemoryError> {
+        if size > self.limit {
+            return Err(MemoryError::ExpansionFailed { size, limit: self.limit });
+        }
         if self.data.len() < size {
             self.data.resize(size, 0);
         }
@@ -55,12 +71,10 @@ impl Memory {
         // For reading, we don't need to expand memory, just return zeros for uninitialized areas
         
         let mut bytes = [0u8; 32];
-        for i in 0..32 {
-            bytes[i] = if offset + i < self.data.len() {
-                self.data[offset + i]
-            } else {
-                0
-            };
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            if offset + i < self.data.len() {
+                *byte = self.data[offset + i];
+            }
         }
         
         Ok(Uint256::from_bytes_be(&bytes))
@@ -71,9 +85,7 @@ impl Memory {
         self.ensure_size(offset + 32)?;
         
         let bytes = value.to_bytes_be();
-        for i in 0..32 {
-            self.data[offset + i] = bytes[i];
-        }
+        self.data[offset..offset + 32].copy_from_slice(&bytes);
         
         Ok(())
     }
@@ -135,26 +147,6 @@ impl Memory {
     pub fn data(&self) -> &[u8] {
         &self.data
     }
-
-    /// Calculate the gas cost for memory expansion
-    pub fn expansion_cost(&self, new_size: usize) -> u64 {
-        let current_size = self.size();
-        if new_size <= current_size {
-            return 0;
-        }
-        
-        // Gas cost calculation based on EVM specification
-        let current_words = (current_size + 31) / 32;
-        let new_words = (new_size + 31) / 32;
-        
-        if new_words <= current_words {
-            return 0;
-        }
-        
-        let additional_words = new_words - current_words;
-        let cost = additional_words * 3 + (new_words * new_words) / 512 - (current_words * current_words) / 512;
-        cost as u64
-    }
 }
 
 impl Default for Memory {
@@ -181,7 +173,7 @@ mod tests {
         let mut memory = Memory::new();
         let value = Uint256::from_u32(0x12345678);
         
-        assert!(memory.write_word(0, value.clone()).is_ok());
+        assert!(memory.write_word(0, value).is_ok());
         assert_eq!(memory.read_word(0).unwrap(), value);
     }
 
@@ -211,15 +203,12 @@ mod tests {
     }
 
     #[test]
-    fn test_memory_expansion_cost() {
-        let memory = Memory::new();
-        
-        // Cost for expanding to 32 bytes (1 word)
-        let cost = memory.expansion_cost(32);
-        assert_eq!(cost, 3); // 1 word * 3 + 1*1/512 - 0*0/512 = 3
+    fn test_memory_limit() {
+        let mut memory = Memory::with_limit(64);
         
-        // Cost for expanding to 64 bytes (2 words)
-        let cost = memory.expansion_cost(64);
-        assert_eq!(cost, 6); // 2 words * 3 + 2*2/512 - 0*0/512 = 6
+        assert!(memory.write_word(32, Uint256::one()).is_ok());
+        assert!(matches!(memory.write_byte(64, 1), Err(MemoryError::ExpansionFailed { size: 65, limit: 64 })));
+        assert_eq!(memory.size(), 64);
+        assert_eq!(Memory::new().limit(), MAX_MEMORY_SIZE);
     }
 }
@@ -1,3 +1,4 @@
+use crate::hardfork::Hardfork;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -79,6 +80,9 @@ pub enum Opcode {
     Gaslimit = 0x45,
     Chainid = 0x46,
     Selfbalance = 0x47,
+    Basefee = 0x48,
+    Blobhash = 0x49,
+    Blobbasefee = 0x4a,
 
     // Storage and memory operations
     Pop = 0x50,
@@ -88,8 +92,13 @@ pub enum Opcode {
     Sload = 0x54,
     Sstore = 0x55,
     Msize = 0x59,
+    Gas = 0x5a,
+    Tload = 0x5c,
+    Tstore = 0x5d,
+    Mcopy = 0x5e,
 
     // Push operations
+    Push0 = 0x5f,
     Push1 = 0x60,
     Push2 = 0x61,
     Push3 = 0x62,
@@ -175,6 +184,7 @@ pub enum Opcode {
     Create2 = 0xf5,
     Staticcall = 0xfa,
     Revert = 0xfd,
+    Invalid = 0xfe,
     Selfdestruct = 0xff,
 
     // Control flow
@@ -184,91 +194,197 @@ pub enum Opcode {
     Jumpdest = 0x5b,
 }
 
+/// Static metadata describing one opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpcodeInfo {
+    pub opcode: Opcode,
+    pub mnemonic: &'static str,
+    /// Number of immediate bytes following the opcode
+    pub immediate_size: usize,
+    /// Stack items consumed
+    pub inputs: usize,
+    /// Stack items produced
+    pub outputs: usize,
+    /// Constant part of the gas cost
+    pub base_gas: u64,
+    /// Hardfork that introduced the opcode
+    pub introduced: Hardfork,
+}
+
+const fn entry(
+    opcode: Opcode,
+    mnemonic: &'static str,
+    immediate_size: usize,
+    inputs: usize,
+    outputs: usize,
+    base_gas: u64,
+    introduced: Hardfork,
+) -> Option<OpcodeInfo> {
+    Some(OpcodeInfo { opcode, mnemonic, immediate_size, inputs, outputs, base_gas, introduced })
+}
+
+/// Metadata for every byte value; `None` marks an undefined opcode
+pub static OPCODE_TABLE: [Option<OpcodeInfo>; 256] = {
+    use Hardfork::*;
+    let mut table = [None; 256];
+    table[0x00] = entry(Opcode::Stop, "STOP", 0, 0, 0, 0, Frontier);
+    table[0x01] = entry(Opcode::Add, "ADD", 0, 2, 1, 3, Frontier);
+    table[0x02] = entry(Opcode::Mul, "MUL", 0, 2, 1, 5, Frontier);
+    table[0x03] = entry(Opcode::Sub, "SUB", 0, 2, 1, 3, Frontier);
+    table[0x04] = entry(Opcode::Div, "DIV", 0, 2, 1, 5, Frontier);
+    table[0x05] = entry(Opcode::Sdiv, "SDIV", 0, 2, 1, 5, Frontier);
+    table[0x06] = entry(Opcode::Mod, "MOD", 0, 2, 1, 5, Frontier);
+    table[0x07] = entry(Opcode::Smod, "SMOD", 0, 2, 1, 5, Frontier);
+    table[0x08] = entry(Opcode::Addmod, "ADDMOD", 0, 3, 1, 8, Frontier);
+    table[0x09] = entry(Opcode::Mulmod, "MULMOD", 0, 3, 1, 8, Frontier);
+    table[0x0a] = entry(Opcode::Exp, "EXP", 0, 2, 1, 10, Frontier);
+    table[0x0b] = entry(Opcode::Signextend, "SIGNEXTEND", 0, 2, 1, 5, Frontier);
+    table[0x10] = entry(Opcode::Lt, "LT", 0, 2, 1, 3, Frontier);
+    table[0x11] = entry(Opcode::Gt, "GT", 0, 2, 1, 3, Frontier);
+    table[0x12] = entry(Opcode::Slt, "SLT", 0, 2, 1, 3, Frontier);
+    table[0x13] = entry(Opcode::Sgt, "SGT", 0, 2, 1, 3, Frontier);
+    table[0x14] = entry(Opcode::Eq, "EQ", 0, 2, 1, 3, Frontier);
+    table[0x15] = entry(Opcode::Iszero, "ISZERO", 0, 1, 1, 3, Frontier);
+    table[0x16] = entry(Opcode::And, "AND", 0, 2, 1, 3, Frontier);
+    table[0x17] = entry(Opcode::Or, "OR", 0, 2, 1, 3, Frontier);
+    table[0x18] = entry(Opcode::Xor, "XOR", 0, 2, 1, 3, Frontier);
+    table[0x19] = entry(Opcode::Not, "NOT", 0, 1, 1, 3, Frontier);
+    table[0x1a] = entry(Opcode::Byte, "BYTE", 0, 2, 1, 3, Frontier);
+    table[0x1b] = entry(Opcode::Shl, "SHL", 0, 2, 1, 3, Constantinople);
+    table[0x1c] = entry(Opcode::Shr, "SHR", 0, 2, 1, 3, Constantinople);
+    table[0x1d] = entry(Opcode::Sar, "SAR", 0, 2, 1, 3, Constantinople);
+    table[0x20] = entry(Opcode::Sha3, "SHA3", 0, 2, 1, 30, Frontier);
+    table[0x30] = entry(Opcode::Address, "ADDRESS", 0, 0, 1, 2, Frontier);
+    table[0x31] = entry(Opcode::Balance, "BALANCE", 0, 1, 1, 100, Frontier);
+    table[0x32] = entry(Opcode::Origin, "ORIGIN", 0, 0, 1, 2, Frontier);
+    table[0x33] = entry(Opcode::Caller, "CALLER", 0, 0, 1, 2, Frontier);
+    table[0x34] = entry(Opcode::Callvalue, "CALLVALUE", 0, 0, 1, 2, Frontier);
+    table[0x35] = entry(Opcode::Calldataload, "CALLDATALOAD", 0, 1, 1, 3, Frontier);
+    table[0x36] = entry(Opcode::Calldatasize, "CALLDATASIZE", 0, 0, 1, 2, Frontier);
+    table[0x37] = entry(Opcode::Calldatacopy, "CALLDATACOPY", 0, 3, 0, 3, Frontier);
+    table[0x38] = entry(Opcode::Codesize, "CODESIZE", 0, 0, 1, 2, Frontier);
+    table[0x39] = entry(Opcode::Codecopy, "CODECOPY", 0, 3, 0, 3, Frontier);
+    table[0x3a] = entry(Opcode::Gasprice, "GASPRICE", 0, 0, 1, 2, Frontier);
+    table[0x3b] = entry(Opcode::Extcodesize, "EXTCODESIZE", 0, 1, 1, 100, Frontier);
+    table[0x3c] = entry(Opcode::Extcodecopy, "EXTCODECOPY", 0, 4, 0, 100, Frontier);
+    table[0x3d] = entry(Opcode::Returndatasize, "RETURNDATASIZE", 0, 0, 1, 2, Byzantium);
+    table[0x3e] = entry(Opcode::Returndatacopy, "RETURNDATACOPY", 0, 3, 0, 3, Byzantium);
+    table[0x3f] = entry(Opcode::Extcodehash, "EXTCODEHASH", 0, 1, 1, 100, Constantinople);
+    table[0x40] = entry(Opcode::Blockhash, "BLOCKHASH", 0, 1, 1, 20, Frontier);
+    table[0x41] = entry(Opcode::Coinbase, "COINBASE", 0, 0, 1, 2, Frontier);
+    table[0x42] = entry(Opcode::Timestamp, "TIMESTAMP", 0, 0, 1, 2, Frontier);
+    table[0x43] = entry(Opcode::Number, "NUMBER", 0, 0, 1, 2, Frontier);
+    table[0x44] = entry(Opcode::Difficulty, "DIFFICULTY", 0, 0, 1, 2, Frontier);
+    table[0x45] = entry(Opcode::Gaslimit, "GASLIMIT", 0, 0, 1, 2, Frontier);
+    table[0x46] = entry(Opcode::Chainid, "CHAINID", 0, 0, 1, 2, Istanbul);
+    table[0x47] = entry(Opcode::Selfbalance, "SELFBALANCE", 0, 0, 1, 5, Istanbul);
+    table[0x48] = entry(Opcode::Basefee, "BASEFEE", 0, 0, 1, 2, London);
+    table[0x49] = entry(Opcode::Blobhash, "BLOBHASH", 0, 1, 1, 3, Cancun);
+    table[0x4a] = entry(Opcode::Blobbasefee, "BLOBBASEFEE", 0, 0, 1, 2, Cancun);
+    table[0x50] = entry(Opcode::Pop, "POP", 0, 1, 0, 2, Frontier);
+    table[0x51] = entry(Opcode::Mload, "MLOAD", 0, 1, 1, 3, Frontier);
+    table[0x52] = entry(Opcode::Mstore, "MSTORE", 0, 2, 0, 3, Frontier);
+    table[0x53] = entry(Opcode::Mstore8, "MSTORE8", 0, 2, 0, 3, Frontier);
+    table[0x54] = entry(Opcode::Sload, "SLOAD", 0, 1, 1, 100, Frontier);
+    table[0x55] = entry(Opcode::Sstore, "SSTORE", 0, 2, 0, 100, Frontier);
+    table[0x56] = entry(Opcode::Jump, "JUMP", 0, 1, 0, 8, Frontier);
+    table[0x57] = entry(Opcode::Jumpi, "JUMPI", 0, 2, 0, 10, Frontier);
+    table[0x58] = entry(Opcode::Pc, "PC", 0, 0, 1, 2, Frontier);
+    table[0x59] = entry(Opcode::Msize, "MSIZE", 0, 0, 1, 2, Frontier);
+    table[0x5a] = entry(Opcode::Gas, "GAS", 0, 0, 1, 2, Frontier);
+    table[0x5b] = entry(Opcode::Jumpdest, "JUMPDEST", 0, 0, 0, 1, Frontier);
+    table[0x5c] = entry(Opcode::Tload, "TLOAD", 0, 1, 1, 100, Cancun);
+    table[0x5d] = entry(Opcode::Tstore, "TSTORE", 0, 2, 0, 100, Cancun);
+    table[0x5e] = entry(Opcode::Mcopy, "MCOPY", 0, 3, 0, 3, Cancun);
+    table[0x5f] = entry(Opcode::Push0, "PUSH0", 0, 0, 1, 2, Shanghai);
+    table[0x60] = entry(Opcode::Push1, "PUSH1", 1, 0, 1, 3, Frontier);
+    table[0x61] = entry(Opcode::Push2, "PUSH2", 2, 0, 1, 3, Frontier);
+    table[0x62] = entry(Opcode::Push3, "PUSH3", 3, 0, 1, 3, Frontier);
+    table[0x63] = entry(Opcode::Push4, "PUSH4", 4, 0, 1, 3, Frontier);
+    table[0x64] = entry(Opcode::Push5, "PUSH5", 5, 0, 1, 3, Frontier);
+    table[0x65] = entry(Opcode::Push6, "PUSH6", 6, 0, 1, 3, Frontier);
+    table[0x66] = entry(Opcode::Push7, "PUSH7", 7, 0, 1, 3, Frontier);
+    table[0x67] = entry(Opcode::Push8, "PUSH8", 8, 0, 1, 3, Frontier);
+    table[0x68] = entry(Opcode::Push9, "PUSH9", 9, 0, 1, 3, Frontier);
+    table[0x69] = entry(Opcode::Push10, "PUSH10", 10, 0, 1, 3, Frontier);
+    table[0x6a] = entry(Opcode::Push11, "PUSH11", 11, 0, 1, 3, Frontier);
+    table[0x6b] = entry(Opcode::Push12, "PUSH12", 12, 0, 1, 3, Frontier);
+    table[0x6c] = entry(Opcode::Push13, "PUSH13", 13, 0, 1, 3, Frontier);
+    table[0x6d] = entry(Opcode::Push14, "PUSH14", 14, 0, 1, 3, Frontier);
+    table[0x6e] = entry(Opcode::Push15, "PUSH15", 15, 0, 1, 3, Frontier);
+    table[0x6f] = entry(Opcode::Push16, "PUSH16", 16, 0, 1, 3, Frontier);
+    table[0x70] = entry(Opcode::Push17, "PUSH17", 17, 0, 1, 3, Frontier);
+    table[0x71] = entry(Opcode::Push18, "PUSH18", 18, 0, 1, 3, Frontier);
+    table[0x72] = entry(Opcode::Push19, "PUSH19", 19, 0, 1, 3, Frontier);
+    table[0x73] = entry(Opcode::Push20, "PUSH20", 20, 0, 1, 3, Frontier);
+    table[0x74] = entry(Opcode::Push21, "PUSH21", 21, 0, 1, 3, Frontier);
+    table[0x75] = entry(Opcode::Push22, "PUSH22", 22, 0, 1, 3, Frontier);
+    table[0x76] = entry(Opcode::Push23, "PUSH23", 23, 0, 1, 3, Frontier);
+    table[0x77] = entry(Opcode::Push24, "PUSH24", 24, 0, 1, 3, Frontier);
+    table[0x78] = entry(Opcode::Push25, "PUSH25", 25, 0, 1, 3, Frontier);
+    table[0x79] = entry(Opcode::Push26, "PUSH26", 26, 0, 1, 3, Frontier);
+    table[0x7a] = entry(Opcode::Push27, "PUSH27", 27, 0, 1, 3, Frontier);
+    table[0x7b] = entry(Opcode::Push28, "PUSH28", 28, 0, 1, 3, Frontier);
+    table[0x7c] = entry(Opcode::Push29, "PUSH29", 29, 0, 1, 3, Frontier);
+    table[0x7d] = entry(Opcode::Push30, "PUSH30", 30, 0, 1, 3, Frontier);
+    table[0x7e] = entry(Opcode::Push31, "PUSH31", 31, 0, 1, 3, Frontier);
+    table[0x7f] = entry(Opcode::Push32, "PUSH32", 32, 0, 1, 3, Frontier);
+    table[0x80] = entry(Opcode::Dup1, "DUP1", 0, 1, 2, 3, Frontier);
+    table[0x81] = entry(Opcode::Dup2, "DUP2", 0, 2, 3, 3, Frontier);
+    table[0x82] = entry(Opcode::Dup3, "DUP3", 0, 3, 4, 3, Frontier);
+    table[0x83] = entry(Opcode::Dup4, "DUP4", 0, 4, 5, 3, Frontier);
+    table[0x84] = entry(Opcode::Dup5, "DUP5", 0, 5, 6, 3, Frontier);
+    table[0x85] = entry(Opcode::Dup6, "DUP6", 0, 6, 7, 3, Frontier);
+    table[0x86] = entry(Opcode::Dup7, "DUP7", 0, 7, 8, 3, Frontier);
+    table[0x87] = entry(Opcode::Dup8, "DUP8", 0, 8, 9, 3, Frontier);
+    table[0x88] = entry(Opcode::Dup9, "DUP9", 0, 9, 10, 3, Frontier);
+    table[0x89] = entry(Opcode::Dup10, "DUP10", 0, 10, 11, 3, Frontier);
+    table[0x8a] = entry(Opcode::Dup11, "DUP11", 0, 11, 12, 3, Frontier);
+    table[0x8b] = entry(Opcode::Dup12, "DUP12", 0, 12, 13, 3, Frontier);
+    table[0x8c] = entry(Opcode::Dup13, "DUP13", 0, 13, 14, 3, Frontier);
+    table[0x8d] = entry(Opcode::Dup14, "DUP14", 0, 14, 15, 3, Frontier);
+    table[0x8e] = entry(Opcode::Dup15, "DUP15", 0, 15, 16, 3, Frontier);
+    table[0x8f] = entry(Opcode::Dup16, "DUP16", 0, 16, 17, 3, Frontier);
+    table[0x90] = entry(Opcode::Swap1, "SWAP1", 0, 2, 2, 3, Frontier);
+    table[0x91] = entry(Opcode::Swap2, "SWAP2", 0, 3, 3, 3, Frontier);
+    table[0x92] = entry(Opcode::Swap3, "SWAP3", 0, 4, 4, 3, Frontier);
+    table[0x93] = entry(Opcode::Swap4, "SWAP4", 0, 5, 5, 3, Frontier);
+    table[0x94] = entry(Opcode::Swap5, "SWAP5", 0, 6, 6, 3, Frontier);
+    table[0x95] = entry(Opcode::Swap6, "SWAP6", 0, 7, 7, 3, Frontier);
+    table[0x96] = entry(Opcode::Swap7, "SWAP7", 0, 8, 8, 3, Frontier);
+    table[0x97] = entry(Opcode::Swap8, "SWAP8", 0, 9, 9, 3, Frontier);
+    table[0x98] = entry(Opcode::Swap9, "SWAP9", 0, 10, 10, 3, Frontier);
+    table[0x99] = entry(Opcode::Swap10, "SWAP10", 0, 11, 11, 3, Frontier);
+    table[0x9a] = entry(Opcode::Swap11, "SWAP11", 0, 12, 12, 3, Frontier);
+    table[0x9b] = entry(Opcode::Swap12, "SWAP12", 0, 13, 13, 3, Frontier);
+    table[0x9c] = entry(Opcode::Swap13, "SWAP13", 0, 14, 14, 3, Frontier);
+    table[0x9d] = entry(Opcode::Swap14, "SWAP14", 0, 15, 15, 3, Frontier);
+    table[0x9e] = entry(Opcode::Swap15, "SWAP15", 0, 16, 16, 3, Frontier);
+    table[0x9f] = entry(Opcode::Swap16, "SWAP16", 0, 17, 17, 3, Frontier);
+    table[0xa0] = entry(Opcode::Log0, "LOG0", 0, 2, 0, 375, Frontier);
+    table[0xa1] = entry(Opcode::Log1, "LOG1", 0, 3, 0, 750, Frontier);
+    table[0xa2] = entry(Opcode::Log2, "LOG2", 0, 4, 0, 1125, Frontier);
+    table[0xa3] = entry(Opcode::Log3, "LOG3", 0, 5, 0, 1500, Frontier);
+    table[0xa4] = entry(Opcode::Log4, "LOG4", 0, 6, 0, 1875, Frontier);
+    table[0xf0] = entry(Opcode::Create, "CREATE", 0, 3, 1, 32000, Frontier);
+    table[0xf1] = entry(Opcode::Call, "CALL", 0, 7, 1, 100, Frontier);
+    table[0xf2] = entry(Opcode::Callcode, "CALLCODE", 0, 7, 1, 100, Frontier);
+    table[0xf3] = entry(Opcode::Return, "RETURN", 0, 2, 0, 0, Frontier);
+    table[0xf4] = entry(Opcode::Delegatecall, "DELEGATECALL", 0, 6, 1, 100, Homestead);
+    table[0xf5] = entry(Opcode::Create2, "CREATE2", 0, 4, 1, 32000, Constantinople);
+    table[0xfa] = entry(Opcode::Staticcall, "STATICCALL", 0, 6, 1, 100, Byzantium);
+    table[0xfd] = entry(Opcode::Revert, "REVERT", 0, 2, 0, 0, Byzantium);
+    table[0xfe] = entry(Opcode::Invalid, "INVALID", 0, 0, 0, 0, Frontier);
+    table[0xff] = entry(Opcode::Selfdestruct, "SELFDESTRUCT", 0, 1, 0, 5000, Frontier);
+    table
+};
+
 impl Opcode {
     /// Convert a byte to an opcode
     pub fn from_byte(byte: u8) -> Result<Self, OpcodeError> {
-        match byte {
-            0x00 => Ok(Opcode::Stop),
-            0x01 => Ok(Opcode::Add),
-            0x02 => Ok(Opcode::Mul),
-            0x03 => Ok(Opcode::Sub),
-            0x04 => Ok(Opcode::Div),
-            0x05 => Ok(Opcode::Sdiv),
-            0x06 => Ok(Opcode::Mod),
-            0x07 => Ok(Opcode::Smod),
-            0x08 => Ok(Opcode::Addmod),
-            0x09 => Ok(Opcode::Mulmod),
-            0x0a => Ok(Opcode::Exp),
-            0x0b => Ok(Opcode::Signextend),
-            0x10 => Ok(Opcode::Lt),
-            0x11 => Ok(Opcode::Gt),
-            0x12 => Ok(Opcode::Slt),
-            0x13 => Ok(Opcode::Sgt),
-            0x14 => Ok(Opcode::Eq),
-            0x15 => Ok(Opcode::Iszero),
-            0x16 => Ok(Opcode::And),
-            0x17 => Ok(Opcode::Or),
-            0x18 => Ok(Opcode::Xor),
-            0x19 => Ok(Opcode::Not),
-            0x1a => Ok(Opcode::Byte),
-            0x1b => Ok(Opcode::Shl),
-            0x1c => Ok(Opcode::Shr),
-            0x1d => Ok(Opcode::Sar),
-            0x20 => Ok(Opcode::Sha3),
-            0x30 => Ok(Opcode::Address),
-            0x31 => Ok(Opcode::Balance),
-            0x32 => Ok(Opcode::Origin),
-            0x33 => Ok(Opcode::Caller),
-            0x34 => Ok(Opcode::Callvalue),
-            0x35 => Ok(Opcode::Calldataload),
-            0x36 => Ok(Opcode::Calldatasize),
-            0x37 => Ok(Opcode::Calldatacopy),
-            0x38 => Ok(Opcode::Codesize),
-            0x39 => Ok(Opcode::Codecopy),
-            0x3a => Ok(Opcode::Gasprice),
-            0x3b => Ok(Opcode::Extcodesize),
-            0x3c => Ok(Opcode::Extcodecopy),
-            0x3d => Ok(Opcode::Returndatasize),
-            0x3e => Ok(Opcode::Returndatacopy),
-            0x3f => Ok(Opcode::Extcodehash),
-            0x40 => Ok(Opcode::Blockhash),
-            0x41 => Ok(Opcode::Coinbase),
-            0x42 => Ok(Opcode::Timestamp),
-            0x43 => Ok(Opcode::Number),
-            0x44 => Ok(Opcode::Difficulty),
-            0x45 => Ok(Opcode::Gaslimit),
-            0x46 => Ok(Opcode::Chainid),
-            0x47 => Ok(Opcode::Selfbalance),
-            0x50 => Ok(Opcode::Pop),
-            0x51 => Ok(Opcode::Mload),
-            0x52 => Ok(Opcode::Mstore),
-            0x53 => Ok(Opcode::Mstore8),
-            0x54 => Ok(Opcode::Sload),
-            0x55 => Ok(Opcode::Sstore),
-            0x56 => Ok(Opcode::Jump),
-            0x57 => Ok(Opcode::Jumpi),
-            0x58 => Ok(Opcode::Pc),
-            0x59 => Ok(Opcode::Msize),
-            0x5b => Ok(Opcode::Jumpdest),
-            0x60..=0x7f => Ok(Opcode::Push1), // Will be handled specially
-            0x80..=0x8f => Ok(Opcode::Dup1), // Will be handled specially
-            0x90..=0x9f => Ok(Opcode::Swap1), // Will be handled specially
-            0xa0 => Ok(Opcode::Log0),
-            0xa1 => Ok(Opcode::Log1),
-            0xa2 => Ok(Opcode::Log2),
-            0xa3 => Ok(Opcode::Log3),
-            0xa4 => Ok(Opcode::Log4),
-            0xf0 => Ok(Opcode::Create),
-            0xf1 => Ok(Opcode::Call),
-            0xf2 => Ok(Opcode::Callcode),
-            0xf3 => Ok(Opcode::Return),
-            0xf4 => Ok(Opcode::Delegatecall),
-            0xf5 => Ok(Opcode::Create2),
-            0xfa => Ok(Opcode::Staticcall),
-            0xfd => Ok(Opcode::Revert),
-            0xff => Ok(Opcode::Selfdestruct),
-            _ => Err(OpcodeError::InvalidOpcode { opcode: byte }),
-        }
+        OPCODE_TABLE[byte as usize]
+            .map(|info| info.opcode)
+            .ok_or(OpcodeError::InvalidOpcode { opcode: byte })
     }
 
     /// Convert opcode to byte
@@ -276,64 +392,48 @@ impl Opcode {
         self as u8
     }
 
-    /// Get the number of bytes this opcode pushes to the stack
+    /// Static metadata for this opcode
+    pub fn info(self) -> &'static OpcodeInfo {
+        OPCODE_TABLE[self as usize]
+            .as_ref()
+            .expect("every opcode has a table entry")
+    }
+
+    /// Assembly mnemonic, e.g. `PUSH1`
+    pub fn mnemonic(self) -> &'static str {
+        self.info().mnemonic
+    }
+
+    /// Get the number of immediate bytes that follow a push opcode (0 for anything else)
     pub fn push_size(self) -> usize {
-        let byte = self.to_byte();
-        if byte >= 0x60 && byte <= 0x7f {
-            (byte - 0x60 + 1) as usize
-        } else {
-            0
-        }
+        self.info().immediate_size
     }
 
     /// Get the number of items this opcode pops from the stack
     pub fn pop_count(self) -> usize {
-        match self {
-            // Arithmetic operations
-            Opcode::Add | Opcode::Mul | Opcode::Sub | Opcode::Div | Opcode::Sdiv |
-            Opcode::Mod | Opcode::Smod | Opcode::Addmod | Opcode::Mulmod | Opcode::Exp |
-            Opcode::Signextend => 2,
-            Opcode::Iszero | Opcode::Not => 1,
-            Opcode::Lt | Opcode::Gt | Opcode::Slt | Opcode::Sgt | Opcode::Eq |
-            Opcode::And | Opcode::Or | Opcode::Xor | Opcode::Byte | Opcode::Shl |
-            Opcode::Shr | Opcode::Sar => 2,
-            Opcode::Sha3 => 2,
-            Opcode::Calldataload | Opcode::Sload | Opcode::Mload => 1,
-            Opcode::Calldatacopy | Opcode::Codecopy | Opcode::Extcodecopy |
-            Opcode::Returndatacopy => 3,
-            Opcode::Mstore | Opcode::Mstore8 | Opcode::Sstore => 2,
-            Opcode::Pop => 1,
-            Opcode::Jump => 1,
-            Opcode::Jumpi => 2,
-            Opcode::Log0 => 2,
-            Opcode::Log1 => 3,
-            Opcode::Log2 => 4,
-            Opcode::Log3 => 5,
-            Opcode::Log4 => 6,
-            Opcode::Create | Opcode::Create2 => 3,
-            Opcode::Call | Opcode::Callcode | Opcode::Delegatecall | Opcode::Staticcall => 7,
-            Opcode::Return | Opcode::Revert => 2,
-            Opcode::Selfdestruct => 1,
-            _ => 0,
-        }
+        self.info().inputs
     }
 
-    /// Check if this is a push opcode
+    /// Get the number of items this opcode pushes onto the stack
+    pub fn push_count(self) -> usize {
+        self.info().outputs
+    }
+
+    /// Check if this is a push opcode with immediate data (PUSH1-PUSH32)
     pub fn is_push(self) -> bool {
-        let byte = self.to_byte();
-        byte >= 0x60 && byte <= 0x7f
+        self.info().immediate_size > 0
     }
 
     /// Check if this is a dup opcode
     pub fn is_dup(self) -> bool {
         let byte = self.to_byte();
-        byte >= 0x80 && byte <= 0x8f
+        (0x80..=0x8f).contains(&byte)
     }
 
     /// Check if this is a swap opcode
     pub fn is_swap(self) -> bool {
         let byte = self.to_byte();
-        byte >= 0x90 && byte <= 0x9f
+        (0x90..=0x9f).contains(&byte)
     }
 
     /// Get the dup depth (0-based)
@@ -355,15 +455,38 @@ impl Opcode {
     }
 
     /// Get the push size for push opcodes
+    #[deprecated(note = "use `Opcode::push_size`")]
     pub fn get_push_size(self) -> usize {
-        if self.is_push() {
-            (self.to_byte() - 0x60 + 1) as usize
-        } else {
-            0
-        }
+        self.push_size()
+    }
+}
+
+/// A decoded instruction: its offset, raw byte and metadata (`None` if undefined)
+pub type Instruction = (usize, u8, Option<&'static OpcodeInfo>);
+
+/// Iterator over the instructions in bytecode, skipping push immediates
+pub struct Instructions<'a> {
+    code: &'a [u8],
+    pc: usize,
+}
+
+impl Iterator for Instructions<'_> {
+    type Item = Instruction;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pc = self.pc;
+        let byte = *self.code.get(pc)?;
+        let info = OPCODE_TABLE[byte as usize].as_ref();
+        self.pc += 1 + info.map_or(0, |info| info.immediate_size);
+        Some((pc, byte, info))
     }
 }
 
+/// Walk the instructions in `code`
+pub fn instructions(code: &[u8]) -> Instructions<'_> {
+    Instructions { code, pc: 0 }
+}
+
 impl std::fmt::Display for Opcode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self)
@@ -379,9 +502,13 @@ mod tests {
         assert_eq!(Opcode::from_byte(0x00).unwrap(), Opcode::Stop);
         assert_eq!(Opcode::from_byte(0x01).unwrap(), Opcode::Add);
         assert_eq!(Opcode::from_byte(0x60).unwrap(), Opcode::Push1);
-        assert_eq!(Opcode::from_byte(0x7f).unwrap(), Opcode::Push1); // Will be handled specially
+        assert_eq!(Opcode::from_byte(0x7f).unwrap(), Opcode::Push32);
+        assert_eq!(Opcode::from_byte(0x81).unwrap(), Opcode::Dup2);
+        assert_eq!(Opcode::from_byte(0x9f).unwrap(), Opcode::Swap16);
         assert!(Opcode::from_byte(0xff).is_ok());
-        assert!(Opcode::from_byte(0xfe).is_err());
+        assert_eq!(Opcode::from_byte(0x5f).unwrap(), Opcode::Push0);
+        assert_eq!(Opcode::from_byte(0xfe).unwrap(), Opcode::Invalid);
+        assert!(Opcode::from_byte(0x0c).is_err());
     }
 
     #[test]
@@ -390,8 +517,11 @@ mod tests {
         assert_eq!(Opcode::Iszero.pop_count(), 1);
         assert_eq!(Opcode::Pop.pop_count(), 1);
         assert_eq!(Opcode::Push1.pop_count(), 0);
-        assert_eq!(Opcode::Dup1.pop_count(), 0);
-        assert_eq!(Opcode::Swap1.pop_count(), 0);
+        assert_eq!(Opcode::Dup1.pop_count(), 1);
+        assert_eq!(Opcode::Dup16.push_count(), 17);
+        assert_eq!(Opcode::Swap1.pop_count(), 2);
+        assert_eq!(Opcode::Call.pop_count(), 7);
+        assert_eq!(Opcode::Staticcall.pop_count(), 6);
     }
 
     #[test]
@@ -400,8 +530,9 @@ mod tests {
         assert!(Opcode::Push32.is_push());
         assert!(!Opcode::Add.is_push());
         
-        assert_eq!(Opcode::Push1.get_push_size(), 1);
-        assert_eq!(Opcode::Push32.get_push_size(), 32);
+        assert_eq!(Opcode::Push1.push_size(), 1);
+        assert_eq!(Opcode::Push32.push_size(), 32);
+        assert_eq!(Opcode::Add.push_size(), 0);
     }
 
     #[test]
@@ -420,4 +551,30 @@ mod tests {
         assert_eq!(Opcode::Swap1.swap_depth(), 0);
         assert_eq!(Opcode::Swap16.swap_depth(), 15);
     }
+
+    #[test]
+    fn test_opcode_table() {
+        for (byte, entry) in OPCODE_TABLE.iter().enumerate() {
+            if let Some(info) = entry {
+                assert_eq!(info.opcode.to_byte() as usize, byte);
+            }
+        }
+        assert_eq!(OPCODE_TABLE.iter().flatten().count(), 149);
+
+        let info = Opcode::Push0.info();
+        assert_eq!(info.mnemonic, "PUSH0");
+        assert_eq!(info.introduced, Hardfork::Shanghai);
+        assert_eq!(Opcode::Delegatecall.info().introduced, Hardfork::Homestead);
+        assert_eq!(Opcode::Sload.info().base_gas, 100);
+    }
+
+    #[test]
+    fn test_instructions_skip_immediates() {
+        // PUSH2 0x5b 0x5b JUMPDEST 0x0c
+        let code = [0x61, 0x5b, 0x5b, 0x5b, 0x0c];
+        let decoded: Vec<_> = instructions(&code)
+            .map(|(pc, _, info)| (pc, info.map(|info| info.opcode)))
+            .collect();
+        assert_eq!(decoded, vec![(0, Some(Opcode::Push2)), (3, Some(Opcode::Jumpdest)), (4, None)]);
+    }
 }
@@ -0,0 +1,350 @@
+//! BLS12-381 curve operations for the 0x0b-0x11 precompiles (EIP-2537)
+
+use super::PrecompileError;
+use ark_bls12_381::{g1, g2, Bls12_381, Fq, Fq2, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::hashing::{curve_maps::wb::WBMap, map_to_curve_hasher::MapToCurve};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{BigInt, One, PrimeField, Zero};
+
+/// Size of an encoded base field element: 48 bytes left-padded to 64
+const FP_SIZE: usize = 64;
+/// Size of an encoded G1 point
+pub const G1_SIZE: usize = 2 * FP_SIZE;
+/// Size of an encoded G2 point
+pub const G2_SIZE: usize = 4 * FP_SIZE;
+/// Size of a scalar in the multi-scalar multiplication input
+const SCALAR_SIZE: usize = 32;
+/// Size of one (point, scalar) pair in the G1 MSM input
+pub const G1_MSM_PAIR_SIZE: usize = G1_SIZE + SCALAR_SIZE;
+/// Size of one (point, scalar) pair in the G2 MSM input
+pub const G2_MSM_PAIR_SIZE: usize = G2_SIZE + SCALAR_SIZE;
+/// Size of one (G1, G2) pair in the pairing input
+pub const PAIR_SIZE: usize = G1_SIZE + G2_SIZE;
+
+/// Price of one G1 scalar multiplication before the MSM discount
+pub const G1_MUL_GAS: u64 = 12000;
+/// Price of one G2 scalar multiplication before the MSM discount
+pub const G2_MUL_GAS: u64 = 22500;
+
+/// Per-mille discount for a G1 MSM of `k` pairs, indexed by `k - 1`
+const G1_MSM_DISCOUNT: [u16; 128] = [
+    1000, 949, 848, 797, 764, 750, 738, 728, 719, 712, 705, 698, 692, 687, 682, 677, 673, 669, 665, 661, 658, 654,
+    651, 648, 645, 642, 640, 637, 635, 632, 630, 627, 625, 623, 621, 619, 617, 615, 613, 611, 609, 608, 606, 604,
+    603, 601, 599, 598, 596, 595, 593, 592, 591, 589, 588, 586, 585, 584, 582, 581, 580, 579, 577, 576, 575, 574,
+    573, 572, 570, 569, 568, 567, 566, 565, 564, 563, 562, 561, 560, 559, 558, 557, 556, 555, 554, 553, 552, 551,
+    550, 549, 548, 547, 547, 546, 545, 544, 543, 542, 541, 540, 540, 539, 538, 537, 536, 536, 535, 534, 533, 532,
+    532, 531, 530, 529, 528, 528, 527, 526, 525, 525, 524, 523, 522, 522, 521, 520, 520, 519,
+];
+
+/// Per-mille discount for a G2 MSM of `k` pairs, indexed by `k - 1`
+const G2_MSM_DISCOUNT: [u16; 128] = [
+    1000, 1000, 923, 884, 855, 832, 812, 796, 782, 770, 759, 749, 740, 732, 724, 717, 711, 704, 699, 693, 688, 683,
+    679, 674, 670, 666, 663, 659, 655, 652, 649, 646, 643, 640, 637, 634, 632, 629, 627, 624, 622, 620, 618, 615,
+    613, 611, 609, 607, 606, 604, 602, 600, 598, 597, 595, 593, 592, 590, 589, 587, 586, 584, 583, 582, 580, 579,
+    578, 576, 575, 574, 573, 571, 570, 569, 568, 567, 566, 565, 563, 562, 561, 560, 559, 558, 557, 556, 555, 554,
+    553, 552, 552, 551, 550, 549, 548, 547, 546, 545, 545, 544, 543, 542, 541, 541, 540, 539, 538, 537, 537, 536,
+    535, 535, 534, 533, 532, 532, 531, 530, 530, 529, 528, 528, 527, 526, 526, 525, 524, 524,
+];
+
+/// Gas for a G1 (`g2 == false`) or G2 multi-scalar multiplication over `input`
+pub fn msm_gas(input: &[u8], g2: bool) -> u64 {
+    let (pair_size, mul_gas, discounts) = if g2 {
+        (G2_MSM_PAIR_SIZE, G2_MUL_GAS, &G2_MSM_DISCOUNT)
+    } else {
+        (G1_MSM_PAIR_SIZE, G1_MUL_GAS, &G1_MSM_DISCOUNT)
+    };
+    let k = input.len() / pair_size;
+    if k == 0 {
+        return 0;
+    }
+    let discount = discounts[k.min(discounts.len()) - 1] as u64;
+    k as u64 * mul_gas * discount / 1000
+}
+
+fn invalid(message: &str) -> PrecompileError {
+    PrecompileError::InvalidInput(message.to_string())
+}
+
+fn check_length(input: &[u8], expected: usize) -> Result<(), PrecompileError> {
+    if input.len() != expected {
+        return Err(PrecompileError::InvalidInput(format!(
+            "input length {} is not {}",
+            input.len(),
+            expected
+        )));
+    }
+    Ok(())
+}
+
+fn check_multiple(input: &[u8], chunk: usize) -> Result<(), PrecompileError> {
+    if input.is_empty() || !input.len().is_multiple_of(chunk) {
+        return Err(PrecompileError::InvalidInput(format!(
+            "input length {} is not a non-zero multiple of {}",
+            input.len(),
+            chunk
+        )));
+    }
+    Ok(())
+}
+
+/// Read a base field element: 16 zero bytes, then a big-endian value below the modulus
+fn read_fp(bytes: &[u8]) -> Result<Fq, PrecompileError> {
+    if bytes[..16].iter().any(|&b| b != 0) {
+        return Err(invalid("field element has non-zero padding"));
+    }
+    let mut limbs = [0u64; 6];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes[16..].rchunks_exact(8)) {
+        *limb = u64::from_be_bytes(chunk.try_into().expect("chunks are eight bytes"));
+    }
+    Fq::from_bigint(BigInt(limbs)).ok_or_else(|| invalid("field element is not below the modulus"))
+}
+
+/// Read an extension field element encoded as (c0, c1)
+fn read_fp2(bytes: &[u8]) -> Result<Fq2, PrecompileError> {
+    Ok(Fq2::new(read_fp(&bytes[..FP_SIZE])?, read_fp(&bytes[FP_SIZE..])?))
+}
+
+fn write_fp(output: &mut Vec<u8>, element: &Fq) {
+    output.extend_from_slice(&[0u8; 16]);
+    for limb in element.into_bigint().0.iter().rev() {
+        output.extend_from_slice(&limb.to_be_bytes());
+    }
+}
+
+/// Read a G1 point, all zeros encoding infinity; the subgroup check is optional
+fn read_g1(bytes: &[u8], subgroup_check: bool) -> Result<G1Affine, PrecompileError> {
+    let (x, y) = (read_fp(&bytes[..FP_SIZE])?, read_fp(&bytes[FP_SIZE..])?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1Affine::identity());
+    }
+    let point = G1Affine::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err(invalid("G1 point not on curve"));
+    }
+    if subgroup_check && !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(invalid("G1 point not in subgroup"));
+    }
+    Ok(point)
+}
+
+/// Read a G2 point, all zeros encoding infinity; the subgroup check is optional
+fn read_g2(bytes: &[u8], subgroup_check: bool) -> Result<G2Affine, PrecompileError> {
+    let (x, y) = (read_fp2(&bytes[..2 * FP_SIZE])?, read_fp2(&bytes[2 * FP_SIZE..])?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2Affine::identity());
+    }
+    let point = G2Affine::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err(invalid("G2 point not on curve"));
+    }
+    if subgroup_check && !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(invalid("G2 point not in subgroup"));
+    }
+    Ok(point)
+}
+
+fn write_g1(point: &G1Affine) -> Vec<u8> {
+    let mut output = Vec::with_capacity(G1_SIZE);
+    match point.xy() {
+        Some((x, y)) => {
+            write_fp(&mut output, x);
+            write_fp(&mut output, y);
+        }
+        None => output.resize(G1_SIZE, 0),
+    }
+    output
+}
+
+fn write_g2(point: &G2Affine) -> Vec<u8> {
+    let mut output = Vec::with_capacity(G2_SIZE);
+    match point.xy() {
+        Some((x, y)) => {
+            for c in [&x.c0, &x.c1, &y.c0, &y.c1] {
+                write_fp(&mut output, c);
+            }
+        }
+        None => output.resize(G2_SIZE, 0),
+    }
+    output
+}
+
+/// Read a 32-byte big-endian scalar, which is not reduced
+fn read_scalar(bytes: &[u8]) -> [u64; 4] {
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.rchunks_exact(8)) {
+        *limb = u64::from_be_bytes(chunk.try_into().expect("chunks are eight bytes"));
+    }
+    limbs
+}
+
+/// BLS12_G1ADD (0x0b): add two G1 points
+pub fn g1_add(input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+    check_length(input, 2 * G1_SIZE)?;
+    let a = read_g1(&input[..G1_SIZE], false)?;
+    let b = read_g1(&input[G1_SIZE..], false)?;
+    Ok(write_g1(&(a + b).into_affine()))
+}
+
+/// BLS12_G1MSM (0x0c): sum of G1 points times scalars
+pub fn g1_msm(input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+    check_multiple(input, G1_MSM_PAIR_SIZE)?;
+    let mut sum = G1Projective::zero();
+    for pair in input.chunks_exact(G1_MSM_PAIR_SIZE) {
+        let point = read_g1(&pair[..G1_SIZE], true)?;
+        sum += point.mul_bigint(read_scalar(&pair[G1_SIZE..]));
+    }
+    Ok(write_g1(&sum.into_affine()))
+}
+
+/// BLS12_G2ADD (0x0d): add two G2 points
+pub fn g2_add(input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+    check_length(input, 2 * G2_SIZE)?;
+    let a = read_g2(&input[..G2_SIZE], false)?;
+    let b = read_g2(&input[G2_SIZE..], false)?;
+    Ok(write_g2(&(a + b).into_affine()))
+}
+
+/// BLS12_G2MSM (0x0e): sum of G2 points times scalars
+pub fn g2_msm(input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+    check_multiple(input, G2_MSM_PAIR_SIZE)?;
+    let mut sum = G2Projective::zero();
+    for pair in input.chunks_exact(G2_MSM_PAIR_SIZE) {
+        let point = read_g2(&pair[..G2_SIZE], true)?;
+        sum += point.mul_bigint(read_scalar(&pair[G2_SIZE..]));
+    }
+    Ok(write_g2(&sum.into_affine()))
+}
+
+/// BLS12_PAIRING_CHECK (0x0f): check that the product of pairings is one
+pub fn pairing(input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+    check_multiple(input, PAIR_SIZE)?;
+    let mut g1_points = Vec::new();
+    let mut g2_points = Vec::new();
+    for pair in input.chunks_exact(PAIR_SIZE) {
+        g1_points.push(read_g1(&pair[..G1_SIZE], true)?);
+        g2_points.push(read_g2(&pair[G1_SIZE..], true)?);
+    }
+
+    let mut output = vec![0u8; 32];
+    if Bls12_381::multi_pairing(g1_points, g2_points).0.is_one() {
+        output[31] = 1;
+    }
+    Ok(output)
+}
+
+/// BLS12_MAP_FP_TO_G1 (0x10): map a base field element to a G1 point
+pub fn map_fp_to_g1(input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+    check_length(input, FP_SIZE)?;
+    let element = read_fp(input)?;
+    let map = WBMap::<g1::Config>::new().map_err(|e| PrecompileError::InvalidInput(e.to_string()))?;
+    let point = map.map_to_curve(element).map_err(|e| PrecompileError::InvalidInput(e.to_string()))?;
+    Ok(write_g1(&point.clear_cofactor()))
+}
+
+/// BLS12_MAP_FP2_TO_G2 (0x11): map an extension field element to a G2 point
+pub fn map_fp2_to_g2(input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+    check_length(input, 2 * FP_SIZE)?;
+    let element = read_fp2(input)?;
+    let map = WBMap::<g2::Config>::new().map_err(|e| PrecompileError::InvalidInput(e.to_string()))?;
+    let point = map.map_to_curve(element).map_err(|e| PrecompileError::InvalidInput(e.to_string()))?;
+    Ok(write_g2(&point.clear_cofactor()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn g1_generator() -> Vec<u8> {
+        write_g1(&G1Affine::generator())
+    }
+
+    fn g2_generator() -> Vec<u8> {
+        write_g2(&G2Affine::generator())
+    }
+
+    fn scalar(value: u64) -> Vec<u8> {
+        let mut word = vec![0u8; 32];
+        word[24..].copy_from_slice(&value.to_be_bytes());
+        word
+    }
+
+    #[test]
+    fn test_generator_encoding() {
+        assert_eq!(
+            hex::encode(g1_generator()),
+            "0000000000000000000000000000000017f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb\
+             0000000000000000000000000000000008b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1"
+        );
+        assert_eq!(read_g2(&g2_generator(), true).unwrap(), G2Affine::generator());
+    }
+
+    #[test]
+    fn test_add_matches_msm() {
+        let doubled = g1_add(&[g1_generator(), g1_generator()].concat()).unwrap();
+        assert_eq!(g1_msm(&[g1_generator(), scalar(2)].concat()).unwrap(), doubled);
+
+        let doubled = g2_add(&[g2_generator(), g2_generator()].concat()).unwrap();
+        assert_eq!(g2_msm(&[g2_generator(), scalar(2)].concat()).unwrap(), doubled);
+
+        // Adding infinity is the identity
+        assert_eq!(g1_add(&[g1_generator(), vec![0u8; G1_SIZE]].concat()).unwrap(), g1_generator());
+    }
+
+    #[test]
+    fn test_pairing_bilinearity() {
+        let double_g1 = g1_msm(&[g1_generator(), scalar(2)].concat()).unwrap();
+        let double_g2 = g2_msm(&[g2_generator(), scalar(2)].concat()).unwrap();
+        let neg_g1 = write_g1(&-G1Affine::generator());
+
+        // e(2 G1, G2) * e(-G1, 2 G2) == 1
+        let input = [double_g1, g2_generator(), neg_g1, double_g2].concat();
+        assert_eq!(pairing(&input).unwrap()[31], 1);
+
+        // e(G1, G2) alone is not one
+        assert_eq!(pairing(&[g1_generator(), g2_generator()].concat()).unwrap()[31], 0);
+    }
+
+    #[test]
+    fn test_map_to_curve_lands_in_subgroup() {
+        let g1 = map_fp_to_g1(&scalar(7)[..].repeat(2)).unwrap();
+        assert!(read_g1(&g1, true).is_ok());
+        let g2 = map_fp2_to_g2(&scalar(7)[..].repeat(4)).unwrap();
+        assert!(read_g2(&g2, true).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        // Wrong lengths, including empty MSM and pairing inputs
+        assert!(g1_add(&g1_generator()).is_err());
+        assert!(g1_msm(&[]).is_err());
+        assert!(pairing(&[]).is_err());
+
+        // Non-zero padding in a field element
+        let mut padded = g1_generator();
+        padded[0] = 1;
+        assert!(g1_add(&[padded, g1_generator()].concat()).is_err());
+
+        // Points off the curve
+        let mut off_curve = g1_generator();
+        off_curve[G1_SIZE - 1] ^= 1;
+        assert!(g1_add(&[off_curve, g1_generator()].concat()).is_err());
+
+        // Field elements at or above the modulus
+        assert!(map_fp_to_g1(&[0xff; FP_SIZE]).is_err());
+
+        // A curve point outside the subgroup can be added but not multiplied
+        let outside = WBMap::<g1::Config>::new().unwrap().map_to_curve(Fq::from(7u64)).unwrap();
+        assert!(!outside.is_in_correct_subgroup_assuming_on_curve());
+        let outside = write_g1(&outside);
+        assert!(g1_add(&[outside.clone(), g1_generator()].concat()).is_ok());
+        assert!(g1_msm(&[outside, scalar(1)].concat()).is_err());
+    }
+
+    #[test]
+    fn test_msm_gas() {
+        assert_eq!(msm_gas(&[0u8; G1_MSM_PAIR_SIZE], false), 12000);
+        assert_eq!(msm_gas(&[0u8; 2 * G1_MSM_PAIR_SIZE], false), 2 * 12000 * 949 / 1000);
+        assert_eq!(msm_gas(&[0u8; 200 * G1_MSM_PAIR_SIZE], false), 200 * 12000 * 519 / 1000);
+        assert_eq!(msm_gas(&[0u8; G2_MSM_PAIR_SIZE], true), 22500);
+    }
+}
@@ -0,0 +1,253 @@
+//! alt_bn128 (BN254) curve operations for the 0x06-0x08 precompiles (EIP-196, EIP-197)
+
+use super::PrecompileError;
+use ark_bn254::{Bn254, Fq, Fq2, G1Affine, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{BigInt, One, PrimeField, Zero};
+
+/// Size of an encoded base field element
+const FP_SIZE: usize = 32;
+/// Size of an encoded G1 point
+const G1_SIZE: usize = 2 * FP_SIZE;
+/// Size of one (G1, G2) pair in the pairing input
+pub const PAIR_SIZE: usize = 192;
+
+fn invalid(message: &str) -> PrecompileError {
+    PrecompileError::InvalidInput(message.to_string())
+}
+
+/// Read 32 big-endian bytes into little-endian limbs
+fn read_limbs(bytes: &[u8]) -> [u64; 4] {
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.rchunks_exact(8)) {
+        *limb = u64::from_be_bytes(chunk.try_into().expect("chunks are eight bytes"));
+    }
+    limbs
+}
+
+/// Read a 32-byte big-endian element, rejecting values not below the modulus
+fn read_fq(bytes: &[u8]) -> Result<Fq, PrecompileError> {
+    Fq::from_bigint(BigInt(read_limbs(bytes))).ok_or_else(|| invalid("field element not below modulus"))
+}
+
+fn write_fq(output: &mut Vec<u8>, element: &Fq) {
+    for limb in element.into_bigint().0.iter().rev() {
+        output.extend_from_slice(&limb.to_be_bytes());
+    }
+}
+
+/// Read a G1 point, all zeros encoding infinity
+fn read_g1(bytes: &[u8]) -> Result<G1Affine, PrecompileError> {
+    let (x, y) = (read_fq(&bytes[..FP_SIZE])?, read_fq(&bytes[FP_SIZE..G1_SIZE])?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1Affine::identity());
+    }
+    let point = G1Affine::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err(invalid("G1 point not on curve"));
+    }
+    Ok(point)
+}
+
+/// Read a G2 point encoded as (x_im, x_re, y_im, y_re), all zeros encoding infinity
+fn read_g2(bytes: &[u8]) -> Result<G2Affine, PrecompileError> {
+    let x = Fq2::new(read_fq(&bytes[32..64])?, read_fq(&bytes[..32])?);
+    let y = Fq2::new(read_fq(&bytes[96..128])?, read_fq(&bytes[64..96])?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2Affine::identity());
+    }
+    let point = G2Affine::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err(invalid("G2 point not on curve"));
+    }
+    if !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(invalid("G2 point not in subgroup"));
+    }
+    Ok(point)
+}
+
+fn write_g1(point: &G1Affine) -> Vec<u8> {
+    let mut output = Vec::with_capacity(G1_SIZE);
+    match point.xy() {
+        Some((x, y)) => {
+            write_fq(&mut output, x);
+            write_fq(&mut output, y);
+        }
+        None => output.resize(G1_SIZE, 0),
+    }
+    output
+}
+
+fn pad_input<const N: usize>(input: &[u8]) -> [u8; N] {
+    let mut buffer = [0u8; N];
+    let len = input.len().min(N);
+    buffer[..len].copy_from_slice(&input[..len]);
+    buffer
+}
+
+/// ECADD (0x06): add two G1 points
+pub fn add(input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+    let input: [u8; 128] = pad_input(input);
+    let a = read_g1(&input[..64])?;
+    let b = read_g1(&input[64..])?;
+    Ok(write_g1(&(a + b).into_affine()))
+}
+
+/// ECMUL (0x07): multiply a G1 point by a scalar
+pub fn mul(input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+    let input: [u8; 96] = pad_input(input);
+    let point = read_g1(&input[..64])?;
+    Ok(write_g1(&point.mul_bigint(read_limbs(&input[64..])).into_affine()))
+}
+
+/// ECPAIRING (0x08): check that the product of pairings is one
+pub fn pairing(input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+    if !input.len().is_multiple_of(PAIR_SIZE) {
+        return Err(PrecompileError::InvalidInput(format!(
+            "pairing input length {} is not a multiple of {}",
+            input.len(),
+            PAIR_SIZE
+        )));
+    }
+
+    let mut g1_points = Vec::new();
+    let mut g2_points = Vec::new();
+    for pair in input.chunks_exact(PAIR_SIZE) {
+        g1_points.push(read_g1(&pair[..G1_SIZE])?);
+        g2_points.push(read_g2(&pair[G1_SIZE..])?);
+    }
+
+    let mut output = vec![0u8; 32];
+    if Bn254::multi_pairing(g1_points, g2_points).0.is_one() {
+        output[31] = 1;
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const G2: &str = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+                      1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+                      090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+                      12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+
+    // Known-answer vectors from the ethereum/tests bn256 precompile suites
+    const ADD_INPUT: &str = "18b18acfb4c2c30276db5411368e7185b311dd124691610c5d3b74034e093dc9\
+                             063c909c4720840cb5134cb9f59fa749755796819658d32efc0d288198f37266\
+                             07c2b7f58a84bd6145f00c9c2bc0bb1a187f20ff2c92963a88019e7c6a014eed\
+                             06614e20c147e940f2d70da3f74c9a17df361706a4485c742bd6788478fa17d7";
+    const ADD_OUTPUT: &str = "2243525c5efd4b9c3d3c45ac0ca3fe4dd85e830a4ce6b65fa1eeaee202839703\
+                              301d1d33be6da8e509df21cc35964723180eed7532537db9ae5e7d48f195c915";
+    const MUL_INPUT: &str = "2bd3e6d0f3b142924f5ca7b49ce5b9d54c4703d7ae5648e61d02268b1a0a9fb7\
+                             21611ce0a6af85915e2f1d70300909ce2e49dfad4a4619c8390cae66cefdb204\
+                             00000000000000000000000000000000000000000000000011138ce750fa15c2";
+    const MUL_OUTPUT: &str = "070a8d6a982153cae4be29d434e8faef8a47b274a053f5a4ee2a6c9c13c31e5c\
+                              031b8ce914eba3a9ffb989f9cdd5b0f01943074bf4f0f315690ec3cec6981afc";
+    const PAIRING_INPUT: &str = "1c76476f4def4bb94541d57ebba1193381ffa7aa76ada664dd31c16024c43f59\
+                                 3034dd2920f673e204fee2811c678745fc819b55d3e9d294e45c9b03a76aef41\
+                                 209dd15ebff5d46c4bd888e51a93cf99a7329636c63514396b4a452003a35bf7\
+                                 04bf11ca01483bfa8b34b43561848d28905960114c8ac04049af4b6315a41678\
+                                 2bb8324af6cfc93537a2ad1a445cfd0ca2a71acd7ac41fadbf933c2a51be344d\
+                                 120a2a4cf30c1bf9845f20c6fe39e07ea2cce61f0c9bb048165fe5e4de877550\
+                                 111e129f1cf1097710d41c4ac70fcdfa5ba2023c6ff1cbeac322de49d1b6df7c\
+                                 2032c61a830e3c17286de9462bf242fca2883585b93870a73853face6a6bf411";
+
+    /// A point on the twist that is not in the order-r subgroup
+    const G2_NOT_IN_SUBGROUP: &str = "0000000000000000000000000000000000000000000000000000000000000001\
+                                      0000000000000000000000000000000000000000000000000000000000000002\
+                                      2b76c179599bb92a963dac85546a005a777f7c13f6a7b75d5918b6b5808f5fde\
+                                      101f7278419308b95099eca02dcee0c5381f4d26d1d62313f057167f064101ce";
+
+    /// G1 generator (1, 2)
+    const G1: &str = "0000000000000000000000000000000000000000000000000000000000000001\
+                      0000000000000000000000000000000000000000000000000000000000000002";
+
+    fn g2_bytes(point: &G2Affine) -> Vec<u8> {
+        let (x, y) = point.xy().unwrap();
+        let mut output = Vec::new();
+        for c in [&x.c1, &x.c0, &y.c1, &y.c0] {
+            write_fq(&mut output, c);
+        }
+        output
+    }
+
+    #[test]
+    fn test_g2_generator_is_valid() {
+        let g2 = read_g2(&hex::decode(G2).unwrap()).unwrap();
+        assert_eq!(g2, G2Affine::generator());
+        assert_eq!(hex::encode(g2_bytes(&g2)), G2);
+        assert_eq!(hex::encode(write_g1(&G1Affine::generator())), G1);
+    }
+
+    #[test]
+    fn test_pairing_bilinearity() {
+        let g2_input = hex::decode(G2).unwrap();
+        let generator = G1Affine::generator();
+        let double_g2 = (G2Affine::generator() + G2Affine::generator()).into_affine();
+        let double_g1 = (generator + generator).into_affine();
+
+        // e(2 G1, G2) * e(-G1, 2 G2) == 1
+        let mut input = write_g1(&double_g1);
+        input.extend_from_slice(&g2_input);
+        input.extend_from_slice(&write_g1(&-generator));
+        input.extend_from_slice(&g2_bytes(&double_g2));
+        assert_eq!(pairing(&input).unwrap()[31], 1);
+
+        // e(G1, G2) alone is not one
+        let mut input = write_g1(&generator);
+        input.extend_from_slice(&g2_input);
+        assert_eq!(pairing(&input).unwrap()[31], 0);
+    }
+
+    #[test]
+    fn test_add_vectors() {
+        assert_eq!(hex::encode(add(&hex::decode(ADD_INPUT).unwrap()).unwrap()), ADD_OUTPUT);
+
+        // (1, 1) is not on the curve
+        let mut input = [0u8; 128];
+        input[31] = 1;
+        input[63] = 1;
+        assert!(add(&input).is_err());
+
+        // Short input is zero-padded, so a lone generator adds infinity
+        assert_eq!(hex::encode(add(&hex::decode(G1).unwrap()).unwrap()), G1);
+    }
+
+    #[test]
+    fn test_mul_vectors() {
+        assert_eq!(hex::encode(mul(&hex::decode(MUL_INPUT).unwrap()).unwrap()), MUL_OUTPUT);
+
+        // (1, 3) is not on the curve
+        let mut input = [0u8; 96];
+        input[31] = 1;
+        input[63] = 3;
+        input[95] = 2;
+        assert!(mul(&input).is_err());
+    }
+
+    #[test]
+    fn test_pairing_vectors() {
+        let valid = hex::decode(format!("{}{}", PAIRING_INPUT, G2)).unwrap();
+        assert_eq!(pairing(&valid).unwrap()[31], 1);
+
+        // e(G1, G2) != 1
+        let one_point = hex::decode(format!("{}{}", G1, G2)).unwrap();
+        assert_eq!(pairing(&one_point).unwrap()[31], 0);
+
+        // Empty input is the empty product
+        assert_eq!(pairing(&[]).unwrap()[31], 1);
+
+        // G1 point off the curve
+        let mut invalid = one_point.clone();
+        invalid[63] = 3;
+        assert!(pairing(&invalid).is_err());
+
+        let not_in_subgroup = hex::decode(format!("{}{}", G1, G2_NOT_IN_SUBGROUP)).unwrap();
+        assert!(pairing(&not_in_subgroup).is_err());
+
+        assert!(pairing(&valid[..PAIR_SIZE - 1]).is_err());
+        assert!(pairing(&valid[..PAIR_SIZE + 1]).is_err());
+    }
+}
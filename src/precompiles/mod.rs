@@ -0,0 +1,511 @@
+mod blake2f;
+mod bls12_381;
+mod bn254;
+mod modexp;
+mod point_evaluation;
+
+use crate::{hardfork::Hardfork, types::Address};
+use ripemd::Ripemd160;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, Secp256k1};
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum PrecompileError {
+    #[error("Out of gas: required {required}, available {available}")]
+    OutOfGas { required: u64, available: u64 },
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+}
+
+/// Result of a successful precompile invocation
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrecompileOutput {
+    pub gas_used: u64,
+    pub output: Vec<u8>,
+}
+
+/// Precompiled contracts living at the low addresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Precompile {
+    EcRecover,
+    Sha256,
+    Ripemd160,
+    Identity,
+    ModExp,
+    Bn254Add,
+    Bn254Mul,
+    Bn254Pairing,
+    Blake2F,
+    PointEvaluation,
+    Bls12G1Add,
+    Bls12G1Msm,
+    Bls12G2Add,
+    Bls12G2Msm,
+    Bls12Pairing,
+    Bls12MapFpToG1,
+    Bls12MapFp2ToG2,
+}
+
+impl Precompile {
+    /// Every precompile in address order
+    pub const ALL: [Precompile; 17] = [
+        Precompile::EcRecover,
+        Precompile::Sha256,
+        Precompile::Ripemd160,
+        Precompile::Identity,
+        Precompile::ModExp,
+        Precompile::Bn254Add,
+        Precompile::Bn254Mul,
+        Precompile::Bn254Pairing,
+        Precompile::Blake2F,
+        Precompile::PointEvaluation,
+        Precompile::Bls12G1Add,
+        Precompile::Bls12G1Msm,
+        Precompile::Bls12G2Add,
+        Precompile::Bls12G2Msm,
+        Precompile::Bls12Pairing,
+        Precompile::Bls12MapFpToG1,
+        Precompile::Bls12MapFp2ToG2,
+    ];
+
+    /// Look up the precompile installed at `address`, if any
+    pub fn from_address(address: &Address) -> Option<Self> {
+        let bytes = address.as_bytes();
+        if bytes[..19].iter().any(|&b| b != 0) {
+            return None;
+        }
+        match bytes[19] {
+            0x01 => Some(Precompile::EcRecover),
+            0x02 => Some(Precompile::Sha256),
+            0x03 => Some(Precompile::Ripemd160),
+            0x04 => Some(Precompile::Identity),
+            0x05 => Some(Precompile::ModExp),
+            0x06 => Some(Precompile::Bn254Add),
+            0x07 => Some(Precompile::Bn254Mul),
+            0x08 => Some(Precompile::Bn254Pairing),
+            0x09 => Some(Precompile::Blake2F),
+            0x0a => Some(Precompile::PointEvaluation),
+            0x0b => Some(Precompile::Bls12G1Add),
+            0x0c => Some(Precompile::Bls12G1Msm),
+            0x0d => Some(Precompile::Bls12G2Add),
+            0x0e => Some(Precompile::Bls12G2Msm),
+            0x0f => Some(Precompile::Bls12Pairing),
+            0x10 => Some(Precompile::Bls12MapFpToG1),
+            0x11 => Some(Precompile::Bls12MapFp2ToG2),
+            _ => None,
+        }
+    }
+
+    /// Look up the precompile installed at `address` under the rules of `fork`
+    pub fn from_address_at(address: &Address, fork: Hardfork) -> Option<Self> {
+        Self::from_address(address).filter(|precompile| precompile.introduced() <= fork)
+    }
+
+    /// Hardfork that installed this precompile
+    pub fn introduced(&self) -> Hardfork {
+        match self {
+            Precompile::EcRecover | Precompile::Sha256 | Precompile::Ripemd160 | Precompile::Identity => {
+                Hardfork::Frontier
+            }
+            Precompile::ModExp | Precompile::Bn254Add | Precompile::Bn254Mul | Precompile::Bn254Pairing => {
+                Hardfork::Byzantium
+            }
+            Precompile::Blake2F => Hardfork::Istanbul,
+            Precompile::PointEvaluation => Hardfork::Cancun,
+            Precompile::Bls12G1Add
+            | Precompile::Bls12G1Msm
+            | Precompile::Bls12G2Add
+            | Precompile::Bls12G2Msm
+            | Precompile::Bls12Pairing
+            | Precompile::Bls12MapFpToG1
+            | Precompile::Bls12MapFp2ToG2 => Hardfork::Prague,
+        }
+    }
+
+    /// The address this precompile is installed at
+    pub fn address(&self) -> Address {
+        let mut bytes = [0u8; 20];
+        bytes[19] = match self {
+            Precompile::EcRecover => 0x01,
+            Precompile::Sha256 => 0x02,
+            Precompile::Ripemd160 => 0x03,
+            Precompile::Identity => 0x04,
+            Precompile::ModExp => 0x05,
+            Precompile::Bn254Add => 0x06,
+            Precompile::Bn254Mul => 0x07,
+            Precompile::Bn254Pairing => 0x08,
+            Precompile::Blake2F => 0x09,
+            Precompile::PointEvaluation => 0x0a,
+            Precompile::Bls12G1Add => 0x0b,
+            Precompile::Bls12G1Msm => 0x0c,
+            Precompile::Bls12G2Add => 0x0d,
+            Precompile::Bls12G2Msm => 0x0e,
+            Precompile::Bls12Pairing => 0x0f,
+            Precompile::Bls12MapFpToG1 => 0x10,
+            Precompile::Bls12MapFp2ToG2 => 0x11,
+        };
+        Address::new(bytes)
+    }
+
+    /// Gas charged for running this precompile on `input` under the rules of `fork`
+    ///
+    /// Istanbul cut the BN254 prices (EIP-1108) and Berlin repriced MODEXP (EIP-2565).
+    /// Point evaluation exists from Cancun (EIP-4844) and the BLS12-381 precompiles from
+    /// Prague (EIP-2537).
+    pub fn gas_cost(&self, input: &[u8], fork: Hardfork) -> u64 {
+        let words = input.len().div_ceil(32) as u64;
+        match self {
+            Precompile::EcRecover => 3000,
+            Precompile::Sha256 => 60 + 12 * words,
+            Precompile::Ripemd160 => 600 + 120 * words,
+            Precompile::Identity => 15 + 3 * words,
+            Precompile::ModExp => modexp::gas_cost(input, fork),
+            Precompile::Bn254Add if fork >= Hardfork::Istanbul => 150,
+            Precompile::Bn254Add => 500,
+            Precompile::Bn254Mul if fork >= Hardfork::Istanbul => 6000,
+            Precompile::Bn254Mul => 40000,
+            Precompile::Bn254Pairing => {
+                let pairs = (input.len() / bn254::PAIR_SIZE) as u64;
+                if fork >= Hardfork::Istanbul {
+                    45000 + 34000 * pairs
+                } else {
+                    100000 + 80000 * pairs
+                }
+            }
+            Precompile::Blake2F => blake2f::rounds(input).unwrap_or(0) as u64,
+            Precompile::PointEvaluation => point_evaluation::GAS_COST,
+            Precompile::Bls12G1Add => 375,
+            Precompile::Bls12G1Msm => bls12_381::msm_gas(input, false),
+            Precompile::Bls12G2Add => 600,
+            Precompile::Bls12G2Msm => bls12_381::msm_gas(input, true),
+            Precompile::Bls12Pairing => 37700 + 32600 * (input.len() / bls12_381::PAIR_SIZE) as u64,
+            Precompile::Bls12MapFpToG1 => 5500,
+            Precompile::Bls12MapFp2ToG2 => 23800,
+        }
+    }
+
+    /// Run the precompile with the given input and gas allowance, priced for `fork`
+    pub fn execute(&self, input: &[u8], gas_limit: u64, fork: Hardfork) -> Result<PrecompileOutput, PrecompileError> {
+        let gas_used = self.gas_cost(input, fork);
+        if gas_used > gas_limit {
+            return Err(PrecompileError::OutOfGas { required: gas_used, available: gas_limit });
+        }
+
+        let output = match self {
+            Precompile::EcRecover => ecrecover(input),
+            Precompile::Sha256 => Sha256::digest(input).to_vec(),
+            Precompile::Ripemd160 => {
+                let mut output = vec![0u8; 12];
+                output.extend_from_slice(&Ripemd160::digest(input));
+                output
+            }
+            Precompile::Identity => input.to_vec(),
+            Precompile::ModExp => modexp::run(input)?,
+            Precompile::Bn254Add => bn254::add(input)?,
+            Precompile::Bn254Mul => bn254::mul(input)?,
+            Precompile::Bn254Pairing => bn254::pairing(input)?,
+            Precompile::Blake2F => blake2f::run(input)?,
+            Precompile::PointEvaluation => point_evaluation::run(input)?,
+            Precompile::Bls12G1Add => bls12_381::g1_add(input)?,
+            Precompile::Bls12G1Msm => bls12_381::g1_msm(input)?,
+            Precompile::Bls12G2Add => bls12_381::g2_add(input)?,
+            Precompile::Bls12G2Msm => bls12_381::g2_msm(input)?,
+            Precompile::Bls12Pairing => bls12_381::pairing(input)?,
+            Precompile::Bls12MapFpToG1 => bls12_381::map_fp_to_g1(input)?,
+            Precompile::Bls12MapFp2ToG2 => bls12_381::map_fp2_to_g2(input)?,
+        };
+
+        Ok(PrecompileOutput { gas_used, output })
+    }
+}
+
+/// Copy `input` into a fixed-size buffer, zero-padding on the right
+fn right_pad<const N: usize>(input: &[u8]) -> [u8; N] {
+    let mut buffer = [0u8; N];
+    let len = input.len().min(N);
+    buffer[..len].copy_from_slice(&input[..len]);
+    buffer
+}
+
+/// Recover the signer address from `hash ++ v ++ r ++ s`
+///
+/// Malformed signatures produce empty output rather than an error.
+fn ecrecover(input: &[u8]) -> Vec<u8> {
+    let input: [u8; 128] = right_pad(input);
+    let (hash, v, signature) = (&input[..32], &input[32..64], &input[64..128]);
+
+    // v is a full word holding 27 or 28
+    if v[..31].iter().any(|&b| b != 0) || !(v[31] == 27 || v[31] == 28) {
+        return Vec::new();
+    }
+
+    let recovered = RecoveryId::from_i32((v[31] - 27) as i32)
+        .and_then(|id| RecoverableSignature::from_compact(signature, id))
+        .and_then(|sig| {
+            let message = Message::from_digest_slice(hash)?;
+            Secp256k1::verification_only().recover_ecdsa(&message, &sig)
+        });
+
+    match recovered {
+        Ok(public_key) => {
+            let hash = Keccak256::digest(&public_key.serialize_uncompressed()[1..]);
+            let mut output = vec![0u8; 12];
+            output.extend_from_slice(&hash[12..]);
+            output
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::SecretKey;
+
+    #[test]
+    fn test_from_address() {
+        for precompile in Precompile::ALL {
+            assert_eq!(Precompile::from_address(&precompile.address()), Some(precompile));
+        }
+        assert_eq!(Precompile::from_address(&Address::zero()), None);
+
+        let mut bytes = [0u8; 20];
+        bytes[0] = 0x01;
+        bytes[19] = 0x01;
+        assert_eq!(Precompile::from_address(&Address::new(bytes)), None);
+
+        let blake2f = Precompile::Blake2F.address();
+        assert_eq!(Precompile::from_address_at(&blake2f, Hardfork::Petersburg), None);
+        assert_eq!(Precompile::from_address_at(&blake2f, Hardfork::Istanbul), Some(Precompile::Blake2F));
+        assert_eq!(Precompile::from_address_at(&Precompile::ModExp.address(), Hardfork::Homestead), None);
+
+        let point_evaluation = Precompile::PointEvaluation.address();
+        assert_eq!(Precompile::from_address_at(&point_evaluation, Hardfork::Shanghai), None);
+        assert_eq!(
+            Precompile::from_address_at(&point_evaluation, Hardfork::Cancun),
+            Some(Precompile::PointEvaluation)
+        );
+
+        let bls_pairing = Precompile::Bls12Pairing.address();
+        assert_eq!(Precompile::from_address_at(&bls_pairing, Hardfork::Cancun), None);
+        assert_eq!(Precompile::from_address_at(&bls_pairing, Hardfork::Prague), Some(Precompile::Bls12Pairing));
+    }
+
+    #[test]
+    fn test_sha256_vectors() {
+        assert_eq!(
+            hex::encode(Sha256::digest(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex::encode(Sha256::digest(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex::encode(Sha256::digest(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn test_ripemd160_and_identity() {
+        let result = Precompile::Ripemd160.execute(b"", 600, Hardfork::Cancun).unwrap();
+        assert_eq!(
+            hex::encode(result.output),
+            "0000000000000000000000009c1185a5c5e9fc54612808977ee8f548b2258d31"
+        );
+
+        let result = Precompile::Identity.execute(&[1, 2, 3], 100, Hardfork::Cancun).unwrap();
+        assert_eq!(result.output, vec![1, 2, 3]);
+        assert_eq!(result.gas_used, 18);
+    }
+
+    #[test]
+    fn test_gas_costs() {
+        let input = [0u8; 33];
+        assert_eq!(Precompile::EcRecover.gas_cost(&input, Hardfork::Cancun), 3000);
+        assert_eq!(Precompile::Sha256.gas_cost(&input, Hardfork::Cancun), 84);
+        assert_eq!(Precompile::Ripemd160.gas_cost(&input, Hardfork::Cancun), 840);
+        assert_eq!(Precompile::Identity.gas_cost(&input, Hardfork::Cancun), 21);
+
+        assert_eq!(
+            Precompile::Sha256.execute(&input, 83, Hardfork::Cancun),
+            Err(PrecompileError::OutOfGas { required: 84, available: 83 })
+        );
+    }
+
+    #[test]
+    fn test_ecrecover() {
+        let secp = Secp256k1::new();
+        let secret = SecretKey::from_slice(&[0x42; 32]).unwrap();
+        let public_key = secret.public_key(&secp);
+        let expected = &Keccak256::digest(&public_key.serialize_uncompressed()[1..])[12..];
+
+        let hash = [0xab; 32];
+        let signature = secp.sign_ecdsa_recoverable(&Message::from_digest_slice(&hash).unwrap(), &secret);
+        let (recovery_id, compact) = signature.serialize_compact();
+
+        let mut input = hash.to_vec();
+        input.extend_from_slice(&[0u8; 31]);
+        input.push(27 + recovery_id.to_i32() as u8);
+        input.extend_from_slice(&compact);
+
+        let result = Precompile::EcRecover.execute(&input, 3000, Hardfork::Cancun).unwrap();
+        assert_eq!(&result.output[12..], expected);
+        assert_eq!(&result.output[..12], &[0u8; 12]);
+
+        // An out-of-range v yields empty output
+        input[63] = 29;
+        assert!(Precompile::EcRecover.execute(&input, 3000, Hardfork::Cancun).unwrap().output.is_empty());
+    }
+
+    fn word(value: u64) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&value.to_be_bytes());
+        word
+    }
+
+    /// EIP-198 example: 3^(p-1) % p for the secp256k1 prime
+    fn fermat_input() -> Vec<u8> {
+        let prime = hex::decode("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f").unwrap();
+        let mut exponent = prime.clone();
+        exponent[31] -= 1;
+        let mut input = Vec::new();
+        for len in [1, 32, 32] {
+            input.extend_from_slice(&word(len));
+        }
+        input.push(0x03);
+        input.extend_from_slice(&exponent);
+        input.extend_from_slice(&prime);
+        input
+    }
+
+    #[test]
+    fn test_modexp() {
+        // 2^10 % 1000 = 24, padded to the two-byte modulus
+        let mut input = Vec::new();
+        for len in [1, 1, 2] {
+            input.extend_from_slice(&word(len));
+        }
+        input.extend_from_slice(&[0x02, 0x0a, 0x03, 0xe8]);
+        let result = Precompile::ModExp.execute(&input, 200, Hardfork::Cancun).unwrap();
+        assert_eq!(result.output, vec![0x00, 0x18]);
+        assert_eq!(result.gas_used, 200);
+
+        let result = Precompile::ModExp.execute(&fermat_input(), 100000, Hardfork::Cancun).unwrap();
+        assert_eq!(result.output, word(1).to_vec());
+        assert_eq!(result.gas_used, 1360);
+    }
+
+    #[test]
+    fn test_modexp_rejects_oversized_operands() {
+        // A 2^32-byte exponent over a one-byte modulus is priced but never allocated
+        let mut input = Vec::new();
+        for len in [1, 1 << 32, 1] {
+            input.extend_from_slice(&word(len));
+        }
+        input.extend_from_slice(&[0x02, 0x03, 0x05]);
+        let gas = Precompile::ModExp.gas_cost(&input, Hardfork::Cancun);
+        assert!(matches!(
+            Precompile::ModExp.execute(&input, gas, Hardfork::Cancun),
+            Err(PrecompileError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_gas_costs_follow_fork() {
+        // Byzantium prices until Istanbul (EIP-1108)
+        let two_pairs = [0u8; 384];
+        for fork in [Hardfork::Byzantium, Hardfork::Petersburg] {
+            assert_eq!(Precompile::Bn254Add.gas_cost(&[], fork), 500);
+            assert_eq!(Precompile::Bn254Mul.gas_cost(&[], fork), 40000);
+            assert_eq!(Precompile::Bn254Pairing.gas_cost(&two_pairs, fork), 260000);
+        }
+        assert_eq!(Precompile::Bn254Add.gas_cost(&[], Hardfork::Istanbul), 150);
+        assert_eq!(Precompile::Bn254Mul.gas_cost(&[], Hardfork::Istanbul), 6000);
+        assert_eq!(Precompile::Bn254Pairing.gas_cost(&two_pairs, Hardfork::Istanbul), 113000);
+
+        // BLS12-381 prices (EIP-2537)
+        assert_eq!(Precompile::Bls12G1Add.gas_cost(&[], Hardfork::Prague), 375);
+        assert_eq!(Precompile::Bls12Pairing.gas_cost(&[0u8; 768], Hardfork::Prague), 37700 + 2 * 32600);
+        assert_eq!(Precompile::Bls12MapFp2ToG2.gas_cost(&[], Hardfork::Prague), 23800);
+
+        // EIP-198 pricing until Berlin (EIP-2565)
+        assert_eq!(Precompile::ModExp.gas_cost(&fermat_input(), Hardfork::Byzantium), 13056);
+        assert_eq!(Precompile::ModExp.gas_cost(&fermat_input(), Hardfork::Istanbul), 13056);
+        assert_eq!(Precompile::ModExp.gas_cost(&fermat_input(), Hardfork::Berlin), 1360);
+
+        // The byte-based complexity has three bands, and no minimum price
+        let base_only = |len: u64| [word(len), word(0), word(0)].concat();
+        assert_eq!(Precompile::ModExp.gas_cost(&base_only(32), Hardfork::Istanbul), 51);
+        assert_eq!(Precompile::ModExp.gas_cost(&base_only(100), Hardfork::Istanbul), 451);
+        assert_eq!(Precompile::ModExp.gas_cost(&base_only(2000), Hardfork::Istanbul), 50516);
+        assert_eq!(Precompile::ModExp.gas_cost(&base_only(100), Hardfork::Berlin), 200);
+        assert_eq!(
+            Precompile::ModExp.execute(&fermat_input(), 13055, Hardfork::Istanbul),
+            Err(PrecompileError::OutOfGas { required: 13056, available: 13055 })
+        );
+    }
+
+    #[test]
+    fn test_bn254_add_and_mul() {
+        let generator = [word(1), word(2)].concat();
+        let doubled = "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+                       15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4";
+
+        let sum = Precompile::Bn254Add.execute(&[generator.clone(), generator.clone()].concat(), 150, Hardfork::Cancun).unwrap();
+        assert_eq!(hex::encode(&sum.output), doubled);
+
+        let product = Precompile::Bn254Mul.execute(&[generator.clone(), word(2).to_vec()].concat(), 6000, Hardfork::Cancun).unwrap();
+        assert_eq!(product.output, sum.output);
+
+        // Points off the curve are rejected
+        let invalid = [word(1), word(3)].concat();
+        assert!(matches!(
+            Precompile::Bn254Add.execute(&invalid, 150, Hardfork::Cancun),
+            Err(PrecompileError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_bn254_pairing_empty_input() {
+        let result = Precompile::Bn254Pairing.execute(&[], 45000, Hardfork::Cancun).unwrap();
+        assert_eq!(result.output, word(1).to_vec());
+        assert_eq!(Precompile::Bn254Pairing.gas_cost(&[0u8; 384], Hardfork::Cancun), 113000);
+        assert!(Precompile::Bn254Pairing.execute(&[0u8; 100], 100000, Hardfork::Cancun).is_err());
+    }
+
+    #[test]
+    fn test_blake2f() {
+        // BLAKE2b-512 of "abc" as a single final block (EIP-152 test vector 5)
+        let mut input = 12u32.to_be_bytes().to_vec();
+        let mut h = [
+            0x6a09e667f3bcc908u64, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+            0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+        ];
+        h[0] ^= 0x01010040;
+        input.extend(h.iter().flat_map(|word| word.to_le_bytes()));
+        let mut message = [0u8; 128];
+        message[..3].copy_from_slice(b"abc");
+        input.extend_from_slice(&message);
+        input.extend_from_slice(&3u64.to_le_bytes());
+        input.extend_from_slice(&0u64.to_le_bytes());
+        input.push(1);
+
+        let result = Precompile::Blake2F.execute(&input, 12, Hardfork::Cancun).unwrap();
+        assert_eq!(
+            hex::encode(result.output),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+        assert_eq!(result.gas_used, 12);
+
+        // Wrong length and invalid final flag are rejected
+        assert!(Precompile::Blake2F.execute(&input[1..], 12, Hardfork::Cancun).is_err());
+        input[212] = 2;
+        assert!(Precompile::Blake2F.execute(&input, 12, Hardfork::Cancun).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Big-integer modular exponentiation for the 0x05 precompile (EIP-198, EIP-2565 pricing)
+
+use super::PrecompileError;
+use crate::hardfork::Hardfork;
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+/// Largest base, exponent or modulus length accepted, in bytes
+///
+/// Even with a one-byte base and modulus, a 64 MiB exponent prices above 25M gas
+/// under EIP-198 and above 170M under EIP-2565, past the block gas limits of the
+/// forks using each schedule, so no affordable call reaches this cap.
+const MAX_LENGTH: u64 = 1 << 26;
+
+/// Read `len` bytes at `offset`, zero-padding past the end of `input`
+fn read_padded(input: &[u8], offset: usize, len: usize) -> Vec<u8> {
+    let mut buffer = vec![0u8; len];
+    if offset < input.len() {
+        let available = (input.len() - offset).min(len);
+        buffer[..available].copy_from_slice(&input[offset..offset + available]);
+    }
+    buffer
+}
+
+/// Read a 32-byte length word, saturating at `u64::MAX`
+fn read_length(input: &[u8], offset: usize) -> u64 {
+    let word = read_padded(input, offset, 32);
+    if word[..24].iter().any(|&b| b != 0) {
+        return u64::MAX;
+    }
+    u64::from_be_bytes(word[24..].try_into().unwrap())
+}
+
+fn lengths(input: &[u8]) -> (u64, u64, u64) {
+    (read_length(input, 0), read_length(input, 32), read_length(input, 64))
+}
+
+/// Gas charged per EIP-198, repriced by EIP-2565 from Berlin
+pub fn gas_cost(input: &[u8], fork: Hardfork) -> u64 {
+    let (base_len, exp_len, mod_len) = lengths(input);
+
+    // Only the first 32 bytes of the exponent are inspected
+    let exp_offset = 96u64.saturating_add(base_len);
+    let exp_head = if exp_offset > usize::MAX as u64 {
+        BigUint::zero()
+    } else {
+        BigUint::from_bytes_be(&read_padded(input, exp_offset as usize, exp_len.min(32) as usize))
+    };
+    let head_bits = exp_head.bits().saturating_sub(1);
+    let iteration_count = if exp_len <= 32 {
+        head_bits
+    } else {
+        (exp_len - 32).saturating_mul(8).saturating_add(head_bits)
+    }
+    .max(1);
+
+    let max_len = base_len.max(mod_len);
+    if fork >= Hardfork::Berlin {
+        let words = max_len.div_ceil(8);
+        let multiplication_complexity = words.saturating_mul(words);
+        (multiplication_complexity.saturating_mul(iteration_count) / 3).max(200)
+    } else {
+        legacy_multiplication_complexity(max_len).saturating_mul(iteration_count) / 20
+    }
+}
+
+/// Multiplication complexity of EIP-198, which prices by bytes rather than words
+fn legacy_multiplication_complexity(len: u64) -> u64 {
+    let square = len.saturating_mul(len);
+    if len <= 64 {
+        square
+    } else if len <= 1024 {
+        (square / 4).saturating_add(96 * len) - 3072
+    } else {
+        (square / 16).saturating_add(480u64.saturating_mul(len)).saturating_sub(199680)
+    }
+}
+
+/// Compute `base ^ exp % modulus`, padded to the modulus length
+pub fn run(input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+    let (base_len, exp_len, mod_len) = lengths(input);
+    if mod_len == 0 {
+        return Ok(Vec::new());
+    }
+    if base_len.max(exp_len).max(mod_len) > MAX_LENGTH {
+        return Err(PrecompileError::InvalidInput(format!("MODEXP operand longer than {} bytes", MAX_LENGTH)));
+    }
+
+    let (base_len, exp_len, mod_len) = (base_len as usize, exp_len as usize, mod_len as usize);
+    let base = BigUint::from_bytes_be(&read_padded(input, 96, base_len));
+    let exponent = BigUint::from_bytes_be(&read_padded(input, 96 + base_len, exp_len));
+    let modulus = BigUint::from_bytes_be(&read_padded(input, 96 + base_len + exp_len, mod_len));
+
+    let mut output = vec![0u8; mod_len];
+    if modulus.is_zero() {
+        return Ok(output);
+    }
+    let result = base.modpow(&exponent, &modulus).to_bytes_be();
+    output[mod_len - result.len()..].copy_from_slice(&result);
+    Ok(output)
+}
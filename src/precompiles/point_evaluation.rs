@@ -0,0 +1,114 @@
+//! KZG point evaluation for the 0x0a precompile (EIP-4844)
+
+use super::PrecompileError;
+use c_kzg::{Bytes32, Bytes48};
+use sha2::{Digest, Sha256};
+
+/// Exact input length: versioned hash, z, y, commitment and proof
+pub const INPUT_LENGTH: usize = 192;
+
+/// Flat gas price of a point evaluation
+pub const GAS_COST: u64 = 50000;
+
+/// Version byte of a KZG versioned hash
+const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// Number of field elements in a blob
+const FIELD_ELEMENTS_PER_BLOB: u64 = 4096;
+
+/// Order of the BLS12-381 scalar field
+const BLS_MODULUS: [u8; 32] = [
+    0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09, 0xa1, 0xd8, 0x05,
+    0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01,
+];
+
+fn invalid(message: &str) -> PrecompileError {
+    PrecompileError::InvalidInput(message.to_string())
+}
+
+/// Versioned hash of a KZG commitment: sha256 with the first byte replaced by the version
+pub fn versioned_hash(commitment: &[u8]) -> [u8; 32] {
+    let mut hash: [u8; 32] = Sha256::digest(commitment).into();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    hash
+}
+
+/// Verify that the committed polynomial evaluates to `y` at `z`
+///
+/// Returns the blob size and the field modulus as two 32-byte words.
+pub fn run(input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+    if input.len() != INPUT_LENGTH {
+        return Err(PrecompileError::InvalidInput(format!(
+            "point evaluation input must be {} bytes",
+            INPUT_LENGTH
+        )));
+    }
+    let (hash, z, y) = (&input[..32], &input[32..64], &input[64..96]);
+    let (commitment, proof) = (&input[96..144], &input[144..192]);
+
+    if versioned_hash(commitment) != hash {
+        return Err(invalid("versioned hash does not match the commitment"));
+    }
+
+    let to_bytes32 = |bytes: &[u8]| Bytes32::new(bytes.try_into().unwrap());
+    let to_bytes48 = |bytes: &[u8]| Bytes48::new(bytes.try_into().unwrap());
+    let verified = c_kzg::ethereum_kzg_settings(0)
+        .verify_kzg_proof(&to_bytes48(commitment), &to_bytes32(z), &to_bytes32(y), &to_bytes48(proof))
+        .map_err(|e| PrecompileError::InvalidInput(format!("malformed point evaluation input: {}", e)))?;
+    if !verified {
+        return Err(invalid("KZG proof does not verify"));
+    }
+
+    let mut output = vec![0u8; 24];
+    output.extend_from_slice(&FIELD_ELEMENTS_PER_BLOB.to_be_bytes());
+    output.extend_from_slice(&BLS_MODULUS);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The point at infinity commits to the zero polynomial, which is 0 everywhere
+    fn zero_polynomial_input() -> Vec<u8> {
+        let mut infinity = [0u8; 48];
+        infinity[0] = 0xc0;
+
+        let mut input = versioned_hash(&infinity).to_vec();
+        input.extend_from_slice(&[0u8; 64]);
+        input.extend_from_slice(&infinity);
+        input.extend_from_slice(&infinity);
+        input
+    }
+
+    #[test]
+    fn test_valid_proof() {
+        let output = run(&zero_polynomial_input()).unwrap();
+        assert_eq!(
+            hex::encode(output),
+            "0000000000000000000000000000000000000000000000000000000000001000\
+             73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001"
+        );
+    }
+
+    #[test]
+    fn test_rejects_wrong_evaluation() {
+        let mut input = zero_polynomial_input();
+        input[95] = 1;
+        assert!(run(&input).is_err());
+    }
+
+    #[test]
+    fn test_rejects_wrong_versioned_hash() {
+        let mut input = zero_polynomial_input();
+        input[0] = 0x02;
+        assert!(run(&input).is_err());
+    }
+
+    #[test]
+    fn test_rejects_bad_length() {
+        let input = zero_polynomial_input();
+        assert!(run(&input[..191]).is_err());
+        assert!(run(&[input, vec![0]].concat()).is_err());
+    }
+}
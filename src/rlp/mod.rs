@@ -0,0 +1,421 @@
+use crate::types::{Address, Bytes, Hash, Uint256};
+use thiserror::Error;
+
+/// Errors decoding RLP data
+///
+/// Decoding is strict: any encoding other than the canonical one is rejected.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RlpError {
+    #[error("Input too short")]
+    InputTooShort,
+    #[error("{0} trailing bytes after item")]
+    TrailingBytes(usize),
+    #[error("Expected a list, found a string")]
+    UnexpectedString,
+    #[error("Expected a string, found a list")]
+    UnexpectedList,
+    #[error("List payload has {0} bytes left after the last field")]
+    ListLengthMismatch(usize),
+    #[error("Integer or length has leading zero bytes")]
+    LeadingZero,
+    #[error("Single byte below 0x80 must be encoded as itself")]
+    NonCanonicalSingleByte,
+    #[error("Length below 56 must use the short form")]
+    NonCanonicalSize,
+    #[error("Value does not fit in {bits} bits")]
+    Overflow { bits: u32 },
+    #[error("Expected {expected} bytes, got {got}")]
+    InvalidLength { expected: usize, got: usize },
+}
+
+/// Types that can be written as RLP
+pub trait Encodable {
+    /// Append the encoding of `self` to `out`
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Encode `self` into a new buffer
+    fn rlp_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode(&mut out);
+        out
+    }
+}
+
+/// Types that can be read from RLP
+pub trait Decodable: Sized {
+    /// Decode one item from the front of `buf`, advancing it past the item
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError>;
+}
+
+/// Encode a value into a new buffer
+pub fn encode<T: Encodable + ?Sized>(value: &T) -> Vec<u8> {
+    value.rlp_bytes()
+}
+
+/// Decode a value that must span all of `bytes`
+pub fn decode<T: Decodable>(bytes: &[u8]) -> Result<T, RlpError> {
+    let mut buf = bytes;
+    let value = T::decode(&mut buf)?;
+    if !buf.is_empty() {
+        return Err(RlpError::TrailingBytes(buf.len()));
+    }
+    Ok(value)
+}
+
+/// Encode heterogeneous fields as one list, as used for structs
+pub fn encode_list(fields: &[&dyn Encodable], out: &mut Vec<u8>) {
+    let mut payload = Vec::new();
+    for field in fields {
+        field.encode(&mut payload);
+    }
+    Header { list: true, payload_length: payload.len() }.encode(out);
+    out.extend_from_slice(&payload);
+}
+
+/// Prefix of an RLP item
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    /// Whether the item is a list
+    pub list: bool,
+    /// Length of the item's payload in bytes
+    pub payload_length: usize,
+}
+
+impl Header {
+    /// Write the prefix for a payload of this length
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        let offset = if self.list { 0xc0 } else { 0x80 };
+        if self.payload_length < 56 {
+            out.push(offset + self.payload_length as u8);
+        } else {
+            let len_bytes = trim_leading_zeros(&(self.payload_length as u64).to_be_bytes()).to_vec();
+            out.push(offset + 55 + len_bytes.len() as u8);
+            out.extend_from_slice(&len_bytes);
+        }
+    }
+
+    /// Read a prefix from the front of `buf`, leaving `buf` at the payload
+    ///
+    /// A single byte below 0x80 is its own payload, so `buf` is not advanced.
+    pub fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        let (&prefix, rest) = buf.split_first().ok_or(RlpError::InputTooShort)?;
+        let header = match prefix {
+            0x00..=0x7f => return Ok(Header { list: false, payload_length: 1 }),
+            0x80..=0xb7 => {
+                *buf = rest;
+                let header = Header { list: false, payload_length: (prefix - 0x80) as usize };
+                if header.payload_length == 1 && buf.first().is_some_and(|&byte| byte < 0x80) {
+                    return Err(RlpError::NonCanonicalSingleByte);
+                }
+                header
+            }
+            0xc0..=0xf7 => {
+                *buf = rest;
+                Header { list: true, payload_length: (prefix - 0xc0) as usize }
+            }
+            0xb8..=0xbf | 0xf8..=0xff => {
+                let list = prefix >= 0xf8;
+                let len_of_len = (prefix - if list { 0xf7 } else { 0xb7 }) as usize;
+                if rest.len() < len_of_len {
+                    return Err(RlpError::InputTooShort);
+                }
+                let (len_bytes, rest) = rest.split_at(len_of_len);
+                if len_bytes[0] == 0 {
+                    return Err(RlpError::LeadingZero);
+                }
+                if len_of_len > std::mem::size_of::<usize>() {
+                    return Err(RlpError::Overflow { bits: usize::BITS });
+                }
+                let payload_length = len_bytes.iter().fold(0usize, |len, &byte| (len << 8) | byte as usize);
+                if payload_length < 56 {
+                    return Err(RlpError::NonCanonicalSize);
+                }
+                *buf = rest;
+                Header { list, payload_length }
+            }
+        };
+        if buf.len() < header.payload_length {
+            return Err(RlpError::InputTooShort);
+        }
+        Ok(header)
+    }
+
+    /// Read a list prefix and split off its payload, advancing `buf` past the list
+    pub fn decode_list<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], RlpError> {
+        let header = Header::decode(buf)?;
+        if !header.list {
+            return Err(RlpError::UnexpectedString);
+        }
+        let (payload, rest) = buf.split_at(header.payload_length);
+        *buf = rest;
+        Ok(payload)
+    }
+
+    /// Read a string item and return its payload, advancing `buf` past it
+    pub fn decode_bytes<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], RlpError> {
+        let header = Header::decode(buf)?;
+        if header.list {
+            return Err(RlpError::UnexpectedList);
+        }
+        let (payload, rest) = buf.split_at(header.payload_length);
+        *buf = rest;
+        Ok(payload)
+    }
+}
+
+/// Check that a list payload was fully consumed by its fields
+pub fn finish_list(payload: &[u8]) -> Result<(), RlpError> {
+    if payload.is_empty() {
+        Ok(())
+    } else {
+        Err(RlpError::ListLengthMismatch(payload.len()))
+    }
+}
+
+/// Encode a byte string
+pub fn encode_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    if let [byte] = bytes {
+        if *byte < 0x80 {
+            out.push(*byte);
+            return;
+        }
+    }
+    Header { list: false, payload_length: bytes.len() }.encode(out);
+    out.extend_from_slice(bytes);
+}
+
+fn trim_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let first = bytes.iter().position(|&byte| byte != 0).unwrap_or(bytes.len());
+    &bytes[first..]
+}
+
+/// Read a big-endian integer payload of at most `N` bytes, rejecting leading zeros
+fn decode_integer<const N: usize>(buf: &mut &[u8]) -> Result<[u8; N], RlpError> {
+    let bytes = Header::decode_bytes(buf)?;
+    if bytes.first() == Some(&0) {
+        return Err(RlpError::LeadingZero);
+    }
+    if bytes.len() > N {
+        return Err(RlpError::Overflow { bits: N as u32 * 8 });
+    }
+    let mut padded = [0u8; N];
+    padded[N - bytes.len()..].copy_from_slice(bytes);
+    Ok(padded)
+}
+
+/// Read a string payload of exactly `N` bytes
+fn decode_fixed<const N: usize>(buf: &mut &[u8]) -> Result<[u8; N], RlpError> {
+    let bytes = Header::decode_bytes(buf)?;
+    bytes
+        .try_into()
+        .map_err(|_| RlpError::InvalidLength { expected: N, got: bytes.len() })
+}
+
+impl Encodable for u64 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        encode_bytes(trim_leading_zeros(&self.to_be_bytes()), out);
+    }
+}
+
+impl Decodable for u64 {
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        decode_integer::<8>(buf).map(u64::from_be_bytes)
+    }
+}
+
+impl Encodable for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u64).encode(out);
+    }
+}
+
+impl Decodable for bool {
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        match u64::decode(buf)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(RlpError::Overflow { bits: 1 }),
+        }
+    }
+}
+
+impl Encodable for Uint256 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        encode_bytes(trim_leading_zeros(&self.to_bytes_be()), out);
+    }
+}
+
+impl Decodable for Uint256 {
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        decode_integer::<32>(buf).map(|bytes| Uint256::from_bytes_be(&bytes))
+    }
+}
+
+impl Encodable for Address {
+    fn encode(&self, out: &mut Vec<u8>) {
+        encode_bytes(self.as_bytes(), out);
+    }
+}
+
+impl Decodable for Address {
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        decode_fixed(buf).map(Address::new)
+    }
+}
+
+/// A recipient field: the empty string means contract creation
+impl Encodable for Option<Address> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Some(address) => address.encode(out),
+            None => encode_bytes(&[], out),
+        }
+    }
+}
+
+impl Decodable for Option<Address> {
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        let mut peek = *buf;
+        if Header::decode_bytes(&mut peek)?.is_empty() {
+            *buf = peek;
+            return Ok(None);
+        }
+        Address::decode(buf).map(Some)
+    }
+}
+
+impl Encodable for Hash {
+    fn encode(&self, out: &mut Vec<u8>) {
+        encode_bytes(self.as_bytes(), out);
+    }
+}
+
+impl Decodable for Hash {
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        decode_fixed(buf).map(Hash::new)
+    }
+}
+
+impl Encodable for Bytes {
+    fn encode(&self, out: &mut Vec<u8>) {
+        encode_bytes(self.as_slice(), out);
+    }
+}
+
+impl Decodable for Bytes {
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        Header::decode_bytes(buf).map(|bytes| Bytes::new(bytes.to_vec()))
+    }
+}
+
+impl<T: Encodable> Encodable for [T] {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let mut payload = Vec::new();
+        for item in self {
+            item.encode(&mut payload);
+        }
+        Header { list: true, payload_length: payload.len() }.encode(out);
+        out.extend_from_slice(&payload);
+    }
+}
+
+impl<T: Encodable> Encodable for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_slice().encode(out);
+    }
+}
+
+impl<T: Decodable> Decodable for Vec<T> {
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        let mut payload = Header::decode_list(buf)?;
+        let mut items = Vec::new();
+        while !payload.is_empty() {
+            items.push(T::decode(&mut payload)?);
+        }
+        Ok(items)
+    }
+}
+
+impl<T: Encodable + ?Sized> Encodable for &T {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (**self).encode(out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_strings_and_integers() {
+        assert_eq!(encode(&Bytes::new(b"dog".to_vec())), vec![0x83, b'd', b'o', b'g']);
+        assert_eq!(encode(&Bytes::empty()), vec![0x80]);
+        assert_eq!(encode(&Bytes::new(vec![0x0f])), vec![0x0f]);
+        assert_eq!(encode(&Bytes::new(vec![0x80])), vec![0x81, 0x80]);
+        assert_eq!(encode(&0u64), vec![0x80]);
+        assert_eq!(encode(&1024u64), vec![0x82, 0x04, 0x00]);
+        assert_eq!(encode(&Uint256::from_u64(15)), vec![0x0f]);
+
+        let long = Bytes::new(vec![b'a'; 56]);
+        let encoded = encode(&long);
+        assert_eq!(&encoded[..2], &[0xb8, 56]);
+        assert_eq!(decode::<Bytes>(&encoded).unwrap(), long);
+    }
+
+    #[test]
+    fn test_encode_lists() {
+        let words = vec![Bytes::new(b"cat".to_vec()), Bytes::new(b"dog".to_vec())];
+        let encoded = encode(&words);
+        assert_eq!(encoded, vec![0xc8, 0x83, b'c', b'a', b't', 0x83, b'd', b'o', b'g']);
+        assert_eq!(decode::<Vec<Bytes>>(&encoded).unwrap(), words);
+
+        assert_eq!(encode(&Vec::<u64>::new()), vec![0xc0]);
+        let mut out = Vec::new();
+        encode_list(&[&1u64, &Address::zero()], &mut out);
+        assert_eq!(out.len(), 1 + 1 + 21);
+        assert_eq!(out[0], 0xc0 + 22);
+    }
+
+    #[test]
+    fn test_round_trip_fixed_types() {
+        let address = Address::from_hex("0x3535353535353535353535353535353535353535").unwrap();
+        assert_eq!(decode::<Address>(&encode(&address)).unwrap(), address);
+        assert_eq!(decode::<Option<Address>>(&encode(&None::<Address>)).unwrap(), None);
+        assert_eq!(decode::<Option<Address>>(&encode(&Some(address))).unwrap(), Some(address));
+
+        let hash = Hash::new([0xab; 32]);
+        assert_eq!(decode::<Hash>(&encode(&hash)).unwrap(), hash);
+        assert_eq!(decode::<Uint256>(&encode(&Uint256::MAX)).unwrap(), Uint256::MAX);
+        assert_eq!(decode::<u64>(&encode(&u64::MAX)).unwrap(), u64::MAX);
+        assert!(decode::<bool>(&encode(&true)).unwrap());
+    }
+
+    #[test]
+    fn test_rejects_non_canonical_encodings() {
+        // Integer with a leading zero byte
+        assert_eq!(decode::<u64>(&[0x82, 0x00, 0x01]), Err(RlpError::LeadingZero));
+        assert_eq!(decode::<u64>(&[0x00]), Err(RlpError::LeadingZero));
+        // Single byte wrapped in a string prefix
+        assert_eq!(decode::<Bytes>(&[0x81, 0x05]), Err(RlpError::NonCanonicalSingleByte));
+        // Long form for a short payload
+        assert_eq!(decode::<Bytes>(&[0xb8, 0x03, 1, 2, 3]), Err(RlpError::NonCanonicalSize));
+        // Length of length with a leading zero
+        let mut padded_length = vec![0xb9, 0x00, 0x38];
+        padded_length.extend([0u8; 56]);
+        assert_eq!(decode::<Bytes>(&padded_length), Err(RlpError::LeadingZero));
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        assert_eq!(decode::<Bytes>(&[]), Err(RlpError::InputTooShort));
+        assert_eq!(decode::<Bytes>(&[0x83, b'd', b'o']), Err(RlpError::InputTooShort));
+        assert_eq!(decode::<Bytes>(&[0x80, 0x80]), Err(RlpError::TrailingBytes(1)));
+        assert_eq!(decode::<Bytes>(&[0xc0]), Err(RlpError::UnexpectedList));
+        assert_eq!(decode::<Vec<u64>>(&[0x80]), Err(RlpError::UnexpectedString));
+        assert_eq!(decode::<u64>(&[0x89, 1, 0, 0, 0, 0, 0, 0, 0, 0]), Err(RlpError::Overflow { bits: 64 }));
+        assert_eq!(
+            decode::<Address>(&[0x82, 0x12, 0x34]),
+            Err(RlpError::InvalidLength { expected: 20, got: 2 })
+        );
+    }
+}
@@ -79,7 +79,7 @@ impl Stack {
         if depth >= self.items.len() {
             return Err(StackError::Underflow);
         }
-        let value = self.items[self.items.len() - 1 - depth].clone();
+        let value = self.items[self.items.len() - 1 - depth];
         self.push(value)?;
         Ok(())
     }
@@ -120,7 +120,7 @@ mod tests {
         let mut stack = Stack::new();
         let value = Uint256::from_u32(42);
         
-        assert!(stack.push(value.clone()).is_ok());
+        assert!(stack.push(value).is_ok());
         assert_eq!(stack.size(), 1);
         assert_eq!(stack.pop().unwrap(), value);
         assert!(stack.is_empty());
@@ -148,8 +148,8 @@ mod tests {
         let value1 = Uint256::from_u32(1);
         let value2 = Uint256::from_u32(2);
         
-        stack.push(value1.clone()).unwrap();
-        stack.push(value2.clone()).unwrap();
+        stack.push(value1).unwrap();
+        stack.push(value2).unwrap();
         
         // DUP1 should duplicate the top item
         stack.dup(0).unwrap();
@@ -166,9 +166,9 @@ mod tests {
         let value2 = Uint256::from_u32(2);
         let value3 = Uint256::from_u32(3);
         
-        stack.push(value1.clone()).unwrap();
-        stack.push(value2.clone()).unwrap();
-        stack.push(value3.clone()).unwrap();
+        stack.push(value1).unwrap();
+        stack.push(value2).unwrap();
+        stack.push(value3).unwrap();
         
         // SWAP1 should swap top with second item
         stack.swap(1).unwrap();
@@ -0,0 +1,521 @@
+use crate::{
+    types::{Address, Bytes, Hash, Uint256},
+    rlp::{self, Decodable, Encodable, Header, RlpError},
+};
+use super::{Account, EMPTY_CODE_HASH};
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Magic bytes and format version at the start of a state file
+const FILE_MAGIC: &[u8] = b"EVMSTATE\x01";
+
+#[derive(Error, Debug)]
+pub enum BackendError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Corrupt state file: {0}")]
+    Corrupt(#[from] RlpError),
+    #[error("Not a state file: {}", path.display())]
+    InvalidFormat { path: PathBuf },
+}
+
+/// Fields of an account as held by a backend; code and storage are stored separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountInfo {
+    pub balance: Uint256,
+    pub nonce: Uint256,
+    /// keccak256 of the account's code
+    pub code_hash: Hash,
+}
+
+impl AccountInfo {
+    /// Hash code the way `code_hash` commits to it
+    pub fn hash_code(code: &[u8]) -> Hash {
+        Hash::new(Keccak256::digest(code).into())
+    }
+
+    /// Check if the account has code
+    pub fn has_code(&self) -> bool {
+        self.code_hash != EMPTY_CODE_HASH
+    }
+
+    /// Check if the account is empty (EIP-161: no code, zero nonce and zero balance)
+    pub fn is_empty(&self) -> bool {
+        !self.has_code() && self.nonce.is_zero() && self.balance.is_zero()
+    }
+}
+
+impl Default for AccountInfo {
+    fn default() -> Self {
+        AccountInfo { balance: Uint256::zero(), nonce: Uint256::zero(), code_hash: EMPTY_CODE_HASH }
+    }
+}
+
+/// Account store behind `Storage`
+///
+/// `Storage` layers journaling, the warm access sets, transient storage and trie
+/// caching on top, so a backend only has to look up and write owned values. The
+/// full scans (`addresses` and `storage_slots`) are only used to build the tries
+/// the first time a root is asked for and to dump the whole state.
+pub trait StateBackend: Send {
+    /// Look up an account's fields
+    fn get_account(&self, address: &Address) -> Option<AccountInfo>;
+
+    /// Insert or replace an account's fields
+    fn put_account(&mut self, address: Address, info: AccountInfo);
+
+    /// Remove an account together with its storage
+    fn remove_account(&mut self, address: &Address);
+
+    /// Read a storage slot; missing slots are zero
+    fn get_storage(&self, address: &Address, key: &Uint256) -> Uint256;
+
+    /// Write a storage slot; writing zero removes it
+    fn put_storage(&mut self, address: Address, key: Uint256, value: Uint256);
+
+    /// Look up code by its hash
+    fn get_code(&self, code_hash: &Hash) -> Option<Vec<u8>>;
+
+    /// Store code under its hash
+    fn put_code(&mut self, code_hash: Hash, code: Vec<u8>);
+
+    /// Addresses of every account, in no particular order
+    fn addresses(&self) -> Vec<Address>;
+
+    /// Every non-zero slot of an account, in no particular order
+    fn storage_slots(&self, address: &Address) -> Vec<(Uint256, Uint256)>;
+
+    /// Remove every account
+    fn clear(&mut self);
+
+    /// Number of accounts
+    fn account_count(&self) -> usize {
+        self.addresses().len()
+    }
+
+    /// Persist pending changes; a no-op for in-memory backends
+    fn flush(&mut self) -> Result<(), BackendError> {
+        Ok(())
+    }
+
+    /// Assemble a full copy of an account, including its code and every storage slot
+    fn load_account(&self, address: &Address) -> Option<Account> {
+        let info = self.get_account(address)?;
+        let mut account = Account::new();
+        account.balance = info.balance;
+        account.nonce = info.nonce;
+        if info.has_code() {
+            account.code = self.get_code(&info.code_hash).unwrap_or_default();
+        }
+        for (key, value) in self.storage_slots(address) {
+            account.set_storage(key, value);
+        }
+        Some(account)
+    }
+
+    /// Replace an account wholesale with a full copy
+    fn store_account(&mut self, address: Address, account: &Account) {
+        self.remove_account(&address);
+        let code_hash = AccountInfo::hash_code(&account.code);
+        if !account.code.is_empty() {
+            self.put_code(code_hash, account.code.clone());
+        }
+        self.put_account(address, AccountInfo { balance: account.balance, nonce: account.nonce, code_hash });
+        for (key, value) in &account.storage {
+            self.put_storage(address, *key, *value);
+        }
+    }
+}
+
+/// In-memory backend; state is lost when it is dropped
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBackend {
+    accounts: HashMap<Address, AccountInfo>,
+    storage: HashMap<Address, HashMap<Uint256, Uint256>>,
+    code: HashMap<Hash, Vec<u8>>,
+}
+
+impl MemoryBackend {
+    /// Create an empty backend
+    pub fn new() -> Self {
+        MemoryBackend::default()
+    }
+}
+
+impl StateBackend for MemoryBackend {
+    fn get_account(&self, address: &Address) -> Option<AccountInfo> {
+        self.accounts.get(address).copied()
+    }
+
+    fn put_account(&mut self, address: Address, info: AccountInfo) {
+        self.accounts.insert(address, info);
+    }
+
+    fn remove_account(&mut self, address: &Address) {
+        self.accounts.remove(address);
+        self.storage.remove(address);
+    }
+
+    fn get_storage(&self, address: &Address, key: &Uint256) -> Uint256 {
+        self.storage
+            .get(address)
+            .and_then(|slots| slots.get(key))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn put_storage(&mut self, address: Address, key: Uint256, value: Uint256) {
+        if value.is_zero() {
+            if let Some(slots) = self.storage.get_mut(&address) {
+                slots.remove(&key);
+            }
+        } else {
+            self.storage.entry(address).or_default().insert(key, value);
+        }
+    }
+
+    fn get_code(&self, code_hash: &Hash) -> Option<Vec<u8>> {
+        self.code.get(code_hash).cloned()
+    }
+
+    fn put_code(&mut self, code_hash: Hash, code: Vec<u8>) {
+        self.code.insert(code_hash, code);
+    }
+
+    fn addresses(&self) -> Vec<Address> {
+        self.accounts.keys().copied().collect()
+    }
+
+    fn storage_slots(&self, address: &Address) -> Vec<(Uint256, Uint256)> {
+        self.storage
+            .get(address)
+            .map(|slots| slots.iter().map(|(key, value)| (*key, *value)).collect())
+            .unwrap_or_default()
+    }
+
+    fn clear(&mut self) {
+        self.accounts.clear();
+        self.storage.clear();
+        self.code.clear();
+    }
+
+    fn account_count(&self) -> usize {
+        self.accounts.len()
+    }
+}
+
+/// Persistent backend storing every account in a single snapshot file
+///
+/// This is a snapshot file, not an embedded database: the whole state is held in
+/// memory, loaded on `open` and rewritten in full on `flush`. The new snapshot is
+/// written to a temporary file, synced to disk and renamed over the old one, so a
+/// crash leaves either the previous or the new state, never a truncated one.
+/// Changes made since the last `flush` are lost on a crash.
+#[derive(Debug)]
+pub struct FileBackend {
+    path: PathBuf,
+    state: MemoryBackend,
+}
+
+impl FileBackend {
+    /// Open the state at `path`, starting empty if the file doesn't exist yet
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, BackendError> {
+        let path = path.as_ref().to_path_buf();
+        let mut state = MemoryBackend::new();
+        if path.exists() {
+            let contents = fs::read(&path)?;
+            let records = contents
+                .strip_prefix(FILE_MAGIC)
+                .ok_or_else(|| BackendError::InvalidFormat { path: path.clone() })?;
+            for AccountRecord { address, account } in rlp::decode::<Vec<AccountRecord>>(records)? {
+                state.store_account(address, &account);
+            }
+        }
+        Ok(FileBackend { path, state })
+    }
+
+    /// Location of the state file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl StateBackend for FileBackend {
+    fn get_account(&self, address: &Address) -> Option<AccountInfo> {
+        self.state.get_account(address)
+    }
+
+    fn put_account(&mut self, address: Address, info: AccountInfo) {
+        self.state.put_account(address, info);
+    }
+
+    fn remove_account(&mut self, address: &Address) {
+        self.state.remove_account(address);
+    }
+
+    fn get_storage(&self, address: &Address, key: &Uint256) -> Uint256 {
+        self.state.get_storage(address, key)
+    }
+
+    fn put_storage(&mut self, address: Address, key: Uint256, value: Uint256) {
+        self.state.put_storage(address, key, value);
+    }
+
+    fn get_code(&self, code_hash: &Hash) -> Option<Vec<u8>> {
+        self.state.get_code(code_hash)
+    }
+
+    fn put_code(&mut self, code_hash: Hash, code: Vec<u8>) {
+        self.state.put_code(code_hash, code);
+    }
+
+    fn addresses(&self) -> Vec<Address> {
+        self.state.addresses()
+    }
+
+    fn storage_slots(&self, address: &Address) -> Vec<(Uint256, Uint256)> {
+        self.state.storage_slots(address)
+    }
+
+    fn clear(&mut self) {
+        self.state.clear();
+    }
+
+    fn account_count(&self) -> usize {
+        self.state.account_count()
+    }
+
+    fn flush(&mut self) -> Result<(), BackendError> {
+        // Sorted so the same state always produces the same file
+        let mut records: Vec<AccountRecord> = self
+            .state
+            .addresses()
+            .into_iter()
+            .filter_map(|address| Some(AccountRecord { address, account: self.state.load_account(&address)? }))
+            .collect();
+        records.sort_by(|a, b| a.address.as_bytes().cmp(b.address.as_bytes()));
+
+        let mut contents = FILE_MAGIC.to_vec();
+        records.encode(&mut contents);
+
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(".tmp");
+        let mut file = File::create(&temp_path)?;
+        file.write_all(&contents)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&temp_path, &self.path)?;
+        sync_parent_dir(&self.path)?;
+        Ok(())
+    }
+}
+
+/// Sync the directory holding `path` so a rename into it survives a crash
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> Result<(), BackendError> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)?.sync_all()?;
+    Ok(())
+}
+
+/// Directories cannot be opened for syncing on this platform
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> Result<(), BackendError> {
+    Ok(())
+}
+
+/// On-disk form of an account: `[address, nonce, balance, code, [[key, value], ...]]`
+struct AccountRecord {
+    address: Address,
+    account: Account,
+}
+
+impl Encodable for AccountRecord {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let mut slots: Vec<(Uint256, Uint256)> = self
+            .account
+            .storage
+            .iter()
+            .filter(|(_, value)| !value.is_zero())
+            .map(|(key, value)| (*key, *value))
+            .collect();
+        slots.sort();
+        let slots: Vec<Vec<Uint256>> = slots.into_iter().map(|(key, value)| vec![key, value]).collect();
+
+        let code = Bytes::new(self.account.code.clone());
+        rlp::encode_list(&[&self.address, &self.account.nonce, &self.account.balance, &code, &slots], out);
+    }
+}
+
+impl Decodable for AccountRecord {
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        let mut payload = Header::decode_list(buf)?;
+        let address = Address::decode(&mut payload)?;
+        let mut account = Account::new();
+        account.nonce = Uint256::decode(&mut payload)?;
+        account.balance = Uint256::decode(&mut payload)?;
+        account.code = Bytes::decode(&mut payload)?.as_slice().to_vec();
+        for slot in Vec::<Vec<Uint256>>::decode(&mut payload)? {
+            match slot.as_slice() {
+                [key, value] => account.set_storage(*key, *value),
+                _ => return Err(RlpError::ListLengthMismatch(slot.len())),
+            }
+        }
+        rlp::finish_list(payload)?;
+        Ok(AccountRecord { address, account })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn temp_state_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("evm-rust-{}-{}.state", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_file_backend_persists_accounts() {
+        let path = temp_state_path("persist");
+        let address = Address::from_hex("0x1000000000000000000000000000000000000001").unwrap();
+        let removed = Address::from_hex("0x2000000000000000000000000000000000000002").unwrap();
+
+        let mut backend = FileBackend::open(&path).unwrap();
+        assert_eq!(backend.account_count(), 0);
+        let mut account = Account::new();
+        account.balance = Uint256::from_u64(1000);
+        account.nonce = Uint256::from_u64(3);
+        account.code = vec![0x60, 0x00];
+        account.set_storage(Uint256::one(), Uint256::from_u64(42));
+        backend.store_account(address, &account);
+        backend.put_account(removed, AccountInfo::default());
+        backend.put_storage(removed, Uint256::one(), Uint256::one());
+        backend.remove_account(&removed);
+        backend.flush().unwrap();
+        let mut temp_path = path.clone().into_os_string();
+        temp_path.push(".tmp");
+        assert!(!Path::new(&temp_path).exists());
+
+        let reopened = FileBackend::open(&path).unwrap();
+        assert_eq!(reopened.load_account(&address), Some(account));
+        assert_eq!(reopened.get_storage(&address, &Uint256::one()), Uint256::from_u64(42));
+        assert!(reopened.get_account(&removed).is_none());
+        assert!(reopened.storage_slots(&removed).is_empty());
+        assert_eq!(reopened.account_count(), 1);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_file_backend_rejects_foreign_files() {
+        let path = temp_state_path("foreign");
+        fs::write(&path, b"not a state file").unwrap();
+        assert!(matches!(FileBackend::open(&path), Err(BackendError::InvalidFormat { .. })));
+
+        let mut truncated = FILE_MAGIC.to_vec();
+        truncated.extend([0xc5, 0x01]);
+        fs::write(&path, truncated).unwrap();
+        assert!(matches!(FileBackend::open(&path), Err(BackendError::Corrupt(RlpError::InputTooShort))));
+        fs::remove_file(&path).unwrap();
+    }
+
+    /// Memory backend that counts full scans of its accounts
+    struct ScanCountingBackend {
+        inner: MemoryBackend,
+        scans: Arc<AtomicUsize>,
+    }
+
+    impl StateBackend for ScanCountingBackend {
+        fn get_account(&self, address: &Address) -> Option<AccountInfo> {
+            self.inner.get_account(address)
+        }
+
+        fn put_account(&mut self, address: Address, info: AccountInfo) {
+            self.inner.put_account(address, info);
+        }
+
+        fn remove_account(&mut self, address: &Address) {
+            self.inner.remove_account(address);
+        }
+
+        fn get_storage(&self, address: &Address, key: &Uint256) -> Uint256 {
+            self.inner.get_storage(address, key)
+        }
+
+        fn put_storage(&mut self, address: Address, key: Uint256, value: Uint256) {
+            self.inner.put_storage(address, key, value);
+        }
+
+        fn get_code(&self, code_hash: &Hash) -> Option<Vec<u8>> {
+            self.inner.get_code(code_hash)
+        }
+
+        fn put_code(&mut self, code_hash: Hash, code: Vec<u8>) {
+            self.inner.put_code(code_hash, code);
+        }
+
+        fn addresses(&self) -> Vec<Address> {
+            self.scans.fetch_add(1, Ordering::SeqCst);
+            self.inner.addresses()
+        }
+
+        fn storage_slots(&self, address: &Address) -> Vec<(Uint256, Uint256)> {
+            self.inner.storage_slots(address)
+        }
+
+        fn clear(&mut self) {
+            self.inner.clear();
+        }
+    }
+
+    #[test]
+    fn test_custom_backend_is_scanned_once() {
+        use crate::storage::Storage;
+
+        let address = Address::from_hex("0x4000000000000000000000000000000000000004").unwrap();
+        let mut inner = MemoryBackend::new();
+        inner.put_account(address, AccountInfo { balance: Uint256::from_u64(7), ..AccountInfo::default() });
+        let scans = Arc::new(AtomicUsize::new(0));
+        let mut storage = Storage::with_backend(ScanCountingBackend { inner, scans: scans.clone() });
+
+        // Execution only needs targeted reads and writes
+        storage.add_balance(address, Uint256::from_u64(1));
+        storage.set_code(address, vec![0x00]);
+        assert_eq!(storage.get_balance(&address), Uint256::from_u64(8));
+        assert_eq!(scans.load(Ordering::SeqCst), 0);
+
+        // The state trie is built from a single scan and then kept up to date
+        let root = storage.state_root();
+        storage.set_storage(address, Uint256::one(), Uint256::one());
+        assert_ne!(storage.state_root(), root);
+        assert_eq!(scans.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_storage_state_survives_reopen() {
+        use crate::storage::Storage;
+
+        let path = temp_state_path("reopen");
+        let address = Address::from_hex("0x3000000000000000000000000000000000000003").unwrap();
+        let mut storage = Storage::with_backend(FileBackend::open(&path).unwrap());
+        storage.set_balance(address, Uint256::from_u64(5));
+        storage.set_storage(address, Uint256::from_u64(9), Uint256::from_u64(10));
+        let root = storage.state_root();
+        storage.flush().unwrap();
+
+        let mut reopened = Storage::with_backend(FileBackend::open(&path).unwrap());
+        assert_eq!(reopened.get_storage(&address, &Uint256::from_u64(9)), Uint256::from_u64(10));
+        assert_eq!(reopened.state_root(), root);
+        fs::remove_file(&path).unwrap();
+    }
+}
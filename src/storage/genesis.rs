@@ -0,0 +1,206 @@
+use crate::types::{Address, Bytes, Uint256};
+use super::{Account, Storage};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum GenesisError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid address {0:?}")]
+    InvalidAddress(String),
+    #[error("Invalid {field} for {address}: {reason}")]
+    InvalidField { address: Address, field: &'static str, reason: String },
+}
+
+/// One account in geth's genesis `alloc` format
+///
+/// Quantities are accepted as JSON numbers, decimal strings or `0x` hex strings;
+/// storage keys and values are always hex.
+#[derive(Serialize, Deserialize, Default)]
+struct AllocAccount {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    balance: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    nonce: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    storage: BTreeMap<String, String>,
+}
+
+impl AllocAccount {
+    fn into_account(self, address: Address) -> Result<Account, GenesisError> {
+        let invalid = |field, reason: String| GenesisError::InvalidField { address, field, reason };
+        let mut account = Account::new();
+        if let Some(balance) = self.balance {
+            account.balance = parse_quantity(&balance).map_err(|reason| invalid("balance", reason))?;
+        }
+        if let Some(nonce) = self.nonce {
+            account.nonce = parse_quantity(&nonce).map_err(|reason| invalid("nonce", reason))?;
+        }
+        if let Some(code) = self.code {
+            account.code = Bytes::from_hex(&code).map_err(|reason| invalid("code", reason))?.as_slice().to_vec();
+        }
+        for (key, value) in &self.storage {
+            let key = parse_word(key).map_err(|reason| invalid("storage key", reason))?;
+            let value = parse_word(value).map_err(|reason| invalid("storage value", reason))?;
+            account.set_storage(key, value);
+        }
+        Ok(account)
+    }
+
+    fn from_account(account: &Account) -> Self {
+        let mut storage: Vec<(Uint256, Uint256)> = account
+            .storage
+            .iter()
+            .filter(|(_, value)| !value.is_zero())
+            .map(|(key, value)| (*key, *value))
+            .collect();
+        storage.sort();
+
+        AllocAccount {
+            balance: Some(Value::String(format!("{:#x}", account.balance))),
+            nonce: (!account.nonce.is_zero()).then(|| Value::String(format!("{:#x}", account.nonce))),
+            code: (!account.code.is_empty()).then(|| format!("0x{}", hex::encode(&account.code))),
+            storage: storage.into_iter().map(|(key, value)| (format_word(&key), format_word(&value))).collect(),
+        }
+    }
+}
+
+/// Parse a JSON number, decimal string or `0x` hex string
+fn parse_quantity(value: &Value) -> Result<Uint256, String> {
+    match value {
+        Value::Number(number) => number
+            .as_u64()
+            .map(Uint256::from_u64)
+            .ok_or_else(|| format!("{} is not a non-negative integer", number)),
+        Value::String(text) => text.parse(),
+        other => Err(format!("expected a number or string, got {}", other)),
+    }
+}
+
+/// Parse a hex storage word, with or without `0x`
+fn parse_word(text: &str) -> Result<Uint256, String> {
+    let digits = text.strip_prefix("0x").unwrap_or(text);
+    if digits.is_empty() {
+        return Ok(Uint256::zero());
+    }
+    Uint256::from_str_radix(digits, 16)
+}
+
+/// Format a storage word as 32 zero-padded bytes, the way geth dumps it
+fn format_word(value: &Uint256) -> String {
+    format!("0x{}", hex::encode(value.to_bytes_be()))
+}
+
+impl Storage {
+    /// Load accounts from genesis `alloc` JSON
+    ///
+    /// Takes either the bare `alloc` object or a whole genesis file with an
+    /// `alloc` field. Each listed account replaces any existing one at its address.
+    pub fn load_alloc_json(&mut self, json: &str) -> Result<(), GenesisError> {
+        let mut document: Value = serde_json::from_str(json)?;
+        let alloc = match document.get_mut("alloc") {
+            Some(alloc) => alloc.take(),
+            None => document,
+        };
+        let alloc: BTreeMap<String, AllocAccount> = serde_json::from_value(alloc)?;
+
+        for (address, entry) in alloc {
+            let address = Address::from_hex(&address).map_err(|_| GenesisError::InvalidAddress(address))?;
+            let account = entry.into_account(address)?;
+            self.set_account(address, account);
+        }
+        Ok(())
+    }
+
+    /// Dump every live account as genesis `alloc` JSON, sorted by address
+    pub fn dump_alloc_json(&self) -> String {
+        let alloc: BTreeMap<String, AllocAccount> = self
+            .accounts()
+            .map(|(address, account)| (address.to_hex(), AllocAccount::from_account(&account)))
+            .collect();
+        serde_json::to_string_pretty(&alloc).expect("alloc JSON serializes")
+    }
+
+    /// Load accounts from a genesis or `alloc` JSON file
+    pub fn load_alloc_file(&mut self, path: impl AsRef<Path>) -> Result<(), GenesisError> {
+        self.load_alloc_json(&fs::read_to_string(path)?)
+    }
+
+    /// Write every live account to `path` as `alloc` JSON
+    pub fn dump_alloc_file(&self, path: impl AsRef<Path>) -> Result<(), GenesisError> {
+        fs::write(path, self.dump_alloc_json())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_geth_alloc() {
+        let json = r#"{
+            "config": { "chainId": 1 },
+            "alloc": {
+                "0x1000000000000000000000000000000000000001": {
+                    "balance": "1000000000000000000",
+                    "nonce": "0x2",
+                    "code": "0x600160005500",
+                    "storage": { "0x01": "0x2a" }
+                },
+                "2000000000000000000000000000000000000002": { "balance": "0xff", "nonce": 7 }
+            }
+        }"#;
+        let mut storage = Storage::new();
+        storage.load_alloc_json(json).unwrap();
+
+        let contract = Address::from_hex("0x1000000000000000000000000000000000000001").unwrap();
+        let eoa = Address::from_hex("0x2000000000000000000000000000000000000002").unwrap();
+        assert_eq!(storage.get_balance(&contract), Uint256::from_u64(1_000_000_000_000_000_000));
+        assert_eq!(storage.get_nonce(&contract), Uint256::from_u64(2));
+        assert_eq!(storage.get_code(&contract), vec![0x60, 0x01, 0x60, 0x00, 0x55, 0x00]);
+        assert_eq!(storage.get_storage(&contract, &Uint256::one()), Uint256::from_u64(42));
+        assert_eq!(storage.get_balance(&eoa), Uint256::from_u64(255));
+        assert_eq!(storage.get_nonce(&eoa), Uint256::from_u64(7));
+    }
+
+    #[test]
+    fn test_alloc_round_trip() {
+        let address = Address::from_hex("0x3000000000000000000000000000000000000003").unwrap();
+        let mut storage = Storage::new();
+        storage.set_balance(address, Uint256::from_u64(5));
+        storage.set_code(address, vec![0x00]);
+        storage.set_storage(address, Uint256::from_u64(9), Uint256::from_u64(10));
+        let dump = storage.dump_alloc_json();
+        assert!(dump.contains("\"0x0000000000000000000000000000000000000000000000000000000000000009\""));
+
+        let mut reloaded = Storage::new();
+        reloaded.load_alloc_json(&dump).unwrap();
+        assert_eq!(reloaded.get_account(&address), storage.get_account(&address));
+        assert_eq!(reloaded.state_root(), storage.state_root());
+        assert_eq!(reloaded.dump_alloc_json(), dump);
+    }
+
+    #[test]
+    fn test_invalid_alloc_entries() {
+        let mut storage = Storage::new();
+        assert!(matches!(
+            storage.load_alloc_json(r#"{ "0x1234": { "balance": "1" } }"#),
+            Err(GenesisError::InvalidAddress(_))
+        ));
+        assert!(matches!(
+            storage.load_alloc_json(r#"{ "0x1000000000000000000000000000000000000001": { "balance": "-1" } }"#),
+            Err(GenesisError::InvalidField { field: "balance", .. })
+        ));
+        assert!(matches!(storage.load_alloc_json("[1, 2]"), Err(GenesisError::Json(_))));
+    }
+}
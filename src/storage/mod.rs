@@ -1,8 +1,24 @@
-use crate::types::{Address, Uint256, Hash};
-use std::collections::HashMap;
+use crate::{
+    types::{Address, Uint256, Hash},
+    rlp::{self, Decodable, Encodable, Header, RlpError},
+    trie::{Trie, EMPTY_ROOT},
+};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 use thiserror::Error;
 use sha3::{Digest, Keccak256};
 
+pub mod backend;
+pub mod genesis;
+
+pub use backend::*;
+pub use genesis::*;
+
+/// keccak256 of empty code, the code hash of every account without code
+pub const EMPTY_CODE_HASH: Hash = Hash([
+    0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+    0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+]);
+
 #[derive(Error, Debug)]
 pub enum StorageError {
     #[error("Account not found: {address}")]
@@ -80,9 +96,14 @@ impl Account {
         }
     }
 
+    /// Check if the account is empty (EIP-161: no code, zero nonce and zero balance)
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty() && self.nonce.is_zero() && self.balance.is_zero()
+    }
+
     /// Add to the account balance
     pub fn add_balance(&mut self, amount: Uint256) {
-        self.balance = self.balance.clone() + amount;
+        self.balance = self.balance + amount;
     }
 
     /// Subtract from the account balance
@@ -90,16 +111,16 @@ impl Account {
         if self.balance < amount {
             return Err(StorageError::InsufficientBalance {
                 required: amount,
-                available: self.balance.clone(),
+                available: self.balance,
             });
         }
-        self.balance = self.balance.clone() - amount;
+        self.balance = self.balance - amount;
         Ok(())
     }
 
     /// Increment the account nonce
     pub fn increment_nonce(&mut self) {
-        self.nonce = self.nonce.clone() + Uint256::one();
+        self.nonce = self.nonce + Uint256::one();
     }
 
     /// Mark the account as deleted
@@ -118,69 +139,419 @@ impl Default for Account {
     }
 }
 
+/// Account as committed to the state trie: `rlp([nonce, balance, storage_root, code_hash])`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrieAccount {
+    pub nonce: Uint256,
+    pub balance: Uint256,
+    pub storage_root: Hash,
+    pub code_hash: Hash,
+}
+
+impl Account {
+    /// Trie representation of this account given the root of its storage trie
+    pub fn to_trie_account(&self, storage_root: Hash) -> TrieAccount {
+        TrieAccount {
+            nonce: self.nonce,
+            balance: self.balance,
+            storage_root,
+            // Unlike `code_hash`, the trie commits to keccak256 of empty code
+            code_hash: AccountInfo::hash_code(&self.code),
+        }
+    }
+}
+
+impl Encodable for TrieAccount {
+    fn encode(&self, out: &mut Vec<u8>) {
+        rlp::encode_list(&[&self.nonce, &self.balance, &self.storage_root, &self.code_hash], out);
+    }
+}
+
+impl Decodable for TrieAccount {
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        let mut payload = Header::decode_list(buf)?;
+        let account = TrieAccount {
+            nonce: Decodable::decode(&mut payload)?,
+            balance: Decodable::decode(&mut payload)?,
+            storage_root: Decodable::decode(&mut payload)?,
+            code_hash: Decodable::decode(&mut payload)?,
+        };
+        rlp::finish_list(payload)?;
+        Ok(account)
+    }
+}
+
+/// A reversible state change, recorded while a checkpoint is open
+#[derive(Debug, Clone, PartialEq)]
+pub enum JournalEntry {
+    /// An account was created where none existed
+    AccountCreated { address: Address },
+    /// An account was replaced wholesale (deletion or direct mutable access)
+    AccountChanged { address: Address, previous: Account },
+    /// An account balance changed
+    BalanceChanged { address: Address, previous: Uint256 },
+    /// An account nonce changed
+    NonceChanged { address: Address, previous: Uint256 },
+    /// An account's code changed
+    CodeChanged { address: Address, previous: Vec<u8> },
+    /// A storage slot changed
+    StorageChanged { address: Address, key: Uint256, previous: Uint256 },
+    /// A transient storage slot changed
+    TransientStorageChanged { address: Address, key: Uint256, previous: Uint256 },
+    /// An address was added to the warm access set (EIP-2929)
+    AddressWarmed { address: Address },
+    /// A storage slot was added to the warm access set (EIP-2929)
+    SlotWarmed { address: Address, key: Uint256 },
+}
+
+/// Position in the journal that state can be reverted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use]
+pub struct Checkpoint {
+    /// Journal length when the checkpoint was opened
+    index: usize,
+    /// Number of checkpoints that were already open
+    depth: usize,
+}
+
+/// Tries backing `state_root`, updated lazily for the accounts changed since the last root
+#[derive(Debug, Clone, Default)]
+struct StateTries {
+    accounts: Trie,
+    /// Storage tries; a missing entry is rebuilt from the account's whole storage
+    storage: HashMap<Address, Trie>,
+    /// Accounts whose leaf must be rehashed
+    dirty: HashSet<Address>,
+    /// Slots written since their account's storage trie was last updated
+    dirty_slots: HashMap<Address, HashSet<Uint256>>,
+    /// Whether every account in the backend has been added to `accounts`
+    complete: bool,
+}
+
+impl StateTries {
+    /// Mark an account's fields as changed
+    fn touch(&mut self, address: Address) {
+        self.dirty.insert(address);
+    }
+
+    /// Mark a single storage slot as changed
+    fn touch_slot(&mut self, address: Address, key: Uint256) {
+        self.dirty.insert(address);
+        self.dirty_slots.entry(address).or_default().insert(key);
+    }
+
+    /// Drop an account's storage trie after a change that may touch any slot
+    fn reset(&mut self, address: Address) {
+        self.dirty.insert(address);
+        self.storage.remove(&address);
+        self.dirty_slots.remove(&address);
+    }
+
+    /// Apply the pending slot writes to an account's storage trie and return its root
+    fn storage_root(&mut self, address: Address, backend: &dyn StateBackend) -> Hash {
+        let dirty_slots = self.dirty_slots.remove(&address).unwrap_or_default();
+        let trie = match self.storage.entry(address) {
+            Entry::Occupied(entry) => {
+                let trie = entry.into_mut();
+                for key in dirty_slots {
+                    Self::write_slot(trie, &key, &backend.get_storage(&address, &key));
+                }
+                trie
+            }
+            Entry::Vacant(entry) => {
+                let trie = entry.insert(Trie::new());
+                for (key, value) in backend.storage_slots(&address) {
+                    Self::write_slot(trie, &key, &value);
+                }
+                trie
+            }
+        };
+        trie.root_hash()
+    }
+
+    /// Write one slot to a storage trie; zero values are removed
+    fn write_slot(trie: &mut Trie, key: &Uint256, value: &Uint256) {
+        let path = Keccak256::digest(key.to_bytes_be());
+        if value.is_zero() {
+            trie.remove(&path);
+        } else {
+            trie.insert(&path, rlp::encode(value));
+        }
+    }
+}
+
 /// EVM Storage - manages account states and storage
 pub struct Storage {
-    /// Map of address to account state
-    accounts: HashMap<Address, Account>,
+    /// Account store
+    backend: Box<dyn StateBackend>,
+    /// Transient storage (EIP-1153), discarded at the end of each transaction
+    transient: HashMap<(Address, Uint256), Uint256>,
+    /// Addresses accessed during the current transaction (EIP-2929)
+    accessed_addresses: HashSet<Address>,
+    /// Storage slots accessed during the current transaction (EIP-2929)
+    accessed_slots: HashSet<(Address, Uint256)>,
+    /// Values slots held before the current transaction first wrote them (EIP-2200)
+    original_storage: HashMap<(Address, Uint256), Uint256>,
+    /// Changes made since the outermost open checkpoint
+    journal: Vec<JournalEntry>,
+    /// Number of open checkpoints
+    open_checkpoints: usize,
+    /// State and storage tries for root computation
+    tries: StateTries,
 }
 
 impl Storage {
-    /// Create a new storage instance
+    /// Create a new storage instance backed by memory
     pub fn new() -> Self {
+        Storage::with_backend(MemoryBackend::new())
+    }
+
+    /// Create a storage instance over the given account store
+    ///
+    /// Accounts already in the backend are added to the state trie the first
+    /// time `state_root` is called.
+    pub fn with_backend(backend: impl StateBackend + 'static) -> Self {
         Storage {
-            accounts: HashMap::new(),
+            backend: Box::new(backend),
+            transient: HashMap::new(),
+            accessed_addresses: HashSet::new(),
+            accessed_slots: HashSet::new(),
+            original_storage: HashMap::new(),
+            journal: Vec::new(),
+            open_checkpoints: 0,
+            tries: StateTries::default(),
+        }
+    }
+
+    /// Record a change if any checkpoint is open
+    fn record(&mut self, entry: JournalEntry) {
+        if self.open_checkpoints > 0 {
+            self.journal.push(entry);
+        }
+    }
+
+    /// Get an account's fields for a targeted update, creating and journaling it if needed
+    fn account_entry(&mut self, address: Address) -> AccountInfo {
+        self.tries.touch(address);
+        match self.backend.get_account(&address) {
+            Some(info) => info,
+            None => {
+                self.record(JournalEntry::AccountCreated { address });
+                let info = AccountInfo::default();
+                self.backend.put_account(address, info);
+                info
+            }
+        }
+    }
+
+    /// Update an account's fields in the backend without journaling
+    fn update_info(&mut self, address: Address, update: impl FnOnce(&mut AccountInfo)) {
+        let mut info = self.backend.get_account(&address).unwrap_or_default();
+        update(&mut info);
+        self.backend.put_account(address, info);
+    }
+
+    /// Store an account's code in the backend without journaling
+    fn write_code(&mut self, address: Address, code: Vec<u8>) {
+        let code_hash = AccountInfo::hash_code(&code);
+        if !code.is_empty() {
+            self.backend.put_code(code_hash, code);
+        }
+        self.update_info(address, |info| info.code_hash = code_hash);
+    }
+
+    /// Journal a full copy of an account before it is replaced or removed
+    fn snapshot_account(&mut self, address: Address) {
+        self.tries.reset(address);
+        // Copying every slot is only worth it if the change can be reverted
+        if self.open_checkpoints == 0 {
+            return;
+        }
+        match self.backend.load_account(&address) {
+            Some(previous) => self.record(JournalEntry::AccountChanged { address, previous }),
+            None => self.record(JournalEntry::AccountCreated { address }),
+        }
+    }
+
+    /// Open a checkpoint; every change after it is undone by `revert_to`
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        let checkpoint = Checkpoint { index: self.journal.len(), depth: self.open_checkpoints };
+        self.open_checkpoints += 1;
+        checkpoint
+    }
+
+    /// Undo every change made since the checkpoint and close it
+    pub fn revert_to(&mut self, checkpoint: Checkpoint) {
+        while self.journal.len() > checkpoint.index {
+            let Some(entry) = self.journal.pop() else { break };
+            self.undo(entry);
+        }
+        self.close_checkpoint(checkpoint);
+    }
+
+    /// Keep the changes made since the checkpoint and close it
+    ///
+    /// The changes stay journaled until the outermost checkpoint is closed so an
+    /// enclosing frame can still revert them.
+    pub fn commit(&mut self, checkpoint: Checkpoint) {
+        self.close_checkpoint(checkpoint);
+    }
+
+    /// Close a checkpoint along with any left open inside it, dropping the journal once none remain
+    fn close_checkpoint(&mut self, checkpoint: Checkpoint) {
+        self.open_checkpoints = checkpoint.depth;
+        if self.open_checkpoints == 0 {
+            self.journal.clear();
         }
     }
 
-    /// Get an account, creating it if it doesn't exist
-    pub fn get_or_create_account(&mut self, address: Address) -> &mut Account {
-        self.accounts.entry(address).or_insert_with(Account::new)
+    /// Apply the inverse of a journaled change
+    fn undo(&mut self, entry: JournalEntry) {
+        match entry {
+            JournalEntry::AccountCreated { address } => {
+                self.tries.reset(address);
+                self.backend.remove_account(&address);
+            }
+            JournalEntry::AccountChanged { address, previous } => {
+                self.tries.reset(address);
+                self.backend.store_account(address, &previous);
+            }
+            JournalEntry::BalanceChanged { address, previous } => {
+                self.tries.touch(address);
+                self.update_info(address, |info| info.balance = previous);
+            }
+            JournalEntry::NonceChanged { address, previous } => {
+                self.tries.touch(address);
+                self.update_info(address, |info| info.nonce = previous);
+            }
+            JournalEntry::CodeChanged { address, previous } => {
+                self.tries.touch(address);
+                self.write_code(address, previous);
+            }
+            JournalEntry::StorageChanged { address, key, previous } => {
+                self.tries.touch_slot(address, key);
+                self.backend.put_storage(address, key, previous);
+            }
+            JournalEntry::TransientStorageChanged { address, key, previous } => {
+                self.write_transient(address, key, previous);
+            }
+            JournalEntry::AddressWarmed { address } => {
+                self.accessed_addresses.remove(&address);
+            }
+            JournalEntry::SlotWarmed { address, key } => {
+                self.accessed_slots.remove(&(address, key));
+            }
+        }
+    }
+
+    /// Number of journaled changes (for debugging)
+    pub fn journal_len(&self) -> usize {
+        self.journal.len()
+    }
+
+    /// Get a full copy of an account, including its code and every storage slot
+    ///
+    /// Use the targeted getters or `get_account_info` when only some fields are needed.
+    pub fn get_account(&self, address: &Address) -> Option<Account> {
+        self.backend.load_account(address)
     }
 
-    /// Get an account reference
-    pub fn get_account(&self, address: &Address) -> Option<&Account> {
-        self.accounts.get(address)
+    /// Get an account's balance, nonce and code hash
+    pub fn get_account_info(&self, address: &Address) -> Option<AccountInfo> {
+        self.backend.get_account(address)
     }
 
-    /// Get an account mutable reference
-    pub fn get_account_mut(&mut self, address: &Address) -> Option<&mut Account> {
-        self.accounts.get_mut(address)
+    /// Replace an account wholesale, including its code and storage
+    pub fn set_account(&mut self, address: Address, account: Account) {
+        self.snapshot_account(address);
+        self.backend.store_account(address, &account);
     }
 
     /// Check if an account exists
     pub fn account_exists(&self, address: &Address) -> bool {
-        self.accounts.contains_key(address)
+        self.backend.get_account(address).is_some()
     }
 
-    /// Delete an account
+    /// Delete an account along with its code and storage
+    ///
+    /// The account is removed outright, so a later write to the address starts
+    /// from a fresh account.
     pub fn delete_account(&mut self, address: &Address) {
-        if let Some(account) = self.accounts.get_mut(address) {
-            account.delete();
+        if self.backend.get_account(address).is_some() {
+            self.snapshot_account(*address);
+            self.backend.remove_account(address);
+        }
+    }
+
+    /// Mark an address as accessed, returning whether it was already warm
+    pub fn warm_address(&mut self, address: Address) -> bool {
+        if self.accessed_addresses.insert(address) {
+            self.record(JournalEntry::AddressWarmed { address });
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Check whether an address has been accessed in the current transaction
+    pub fn is_warm_address(&self, address: &Address) -> bool {
+        self.accessed_addresses.contains(address)
+    }
+
+    /// Mark a storage slot as accessed, returning whether it was already warm
+    pub fn warm_slot(&mut self, address: Address, key: Uint256) -> bool {
+        if self.accessed_slots.insert((address, key)) {
+            self.record(JournalEntry::SlotWarmed { address, key });
+            false
+        } else {
+            true
         }
     }
 
+    /// Check whether a storage slot has been accessed in the current transaction
+    pub fn is_warm_slot(&self, address: &Address, key: &Uint256) -> bool {
+        self.accessed_slots.contains(&(*address, *key))
+    }
+
+    /// Discard the transaction-scoped state: transient storage, the warm access sets
+    /// and the original slot values
+    pub fn end_transaction(&mut self) {
+        self.transient.clear();
+        self.accessed_addresses.clear();
+        self.accessed_slots.clear();
+        self.original_storage.clear();
+    }
+
     /// Get account balance
     pub fn get_balance(&self, address: &Address) -> Uint256 {
-        self.accounts
-            .get(address)
-            .map(|account| account.balance.clone())
-            .unwrap_or(Uint256::zero())
+        self.backend
+            .get_account(address)
+            .map(|info| info.balance)
+            .unwrap_or_default()
     }
 
     /// Set account balance
     pub fn set_balance(&mut self, address: Address, balance: Uint256) {
-        self.get_or_create_account(address).balance = balance;
+        let previous = self.account_entry(address).balance;
+        self.record(JournalEntry::BalanceChanged { address, previous });
+        self.update_info(address, |info| info.balance = balance);
     }
 
     /// Add to account balance
     pub fn add_balance(&mut self, address: Address, amount: Uint256) {
-        self.get_or_create_account(address).add_balance(amount);
+        let balance = self.get_balance(&address) + amount;
+        self.set_balance(address, balance);
     }
 
     /// Subtract from account balance
     pub fn sub_balance(&mut self, address: &Address, amount: Uint256) -> Result<(), StorageError> {
-        if let Some(account) = self.accounts.get_mut(address) {
-            account.sub_balance(amount)
+        if let Some(info) = self.backend.get_account(address) {
+            let previous = info.balance;
+            if previous < amount {
+                return Err(StorageError::InsufficientBalance { required: amount, available: previous });
+            }
+            self.set_balance(*address, previous - amount);
+            Ok(())
         } else {
             Err(StorageError::InsufficientBalance {
                 required: amount,
@@ -189,63 +560,189 @@ impl Storage {
         }
     }
 
+    /// Transfer value between two accounts
+    pub fn transfer(&mut self, from: &Address, to: Address, amount: Uint256) -> Result<(), StorageError> {
+        if amount.is_zero() {
+            return Ok(());
+        }
+        self.sub_balance(from, amount)?;
+        self.add_balance(to, amount);
+        Ok(())
+    }
+
+    /// Check if an account is missing or empty (EIP-161)
+    pub fn is_empty_account(&self, address: &Address) -> bool {
+        self.backend.get_account(address).map(|info| info.is_empty()).unwrap_or(true)
+    }
+
     /// Get account nonce
     pub fn get_nonce(&self, address: &Address) -> Uint256 {
-        self.accounts
-            .get(address)
-            .map(|account| account.nonce.clone())
-            .unwrap_or(Uint256::zero())
+        self.backend
+            .get_account(address)
+            .map(|info| info.nonce)
+            .unwrap_or_default()
     }
 
     /// Set account nonce
     pub fn set_nonce(&mut self, address: Address, nonce: Uint256) {
-        self.get_or_create_account(address).nonce = nonce;
+        let previous = self.account_entry(address).nonce;
+        self.record(JournalEntry::NonceChanged { address, previous });
+        self.update_info(address, |info| info.nonce = nonce);
     }
 
     /// Increment account nonce
     pub fn increment_nonce(&mut self, address: Address) {
-        self.get_or_create_account(address).increment_nonce();
+        let nonce = self.get_nonce(&address) + Uint256::one();
+        self.set_nonce(address, nonce);
     }
 
     /// Get account code
     pub fn get_code(&self, address: &Address) -> Vec<u8> {
-        self.accounts
-            .get(address)
-            .map(|account| account.code.clone())
+        self.backend
+            .get_account(address)
+            .filter(AccountInfo::has_code)
+            .and_then(|info| self.backend.get_code(&info.code_hash))
             .unwrap_or_default()
     }
 
     /// Set account code
     pub fn set_code(&mut self, address: Address, code: Vec<u8>) {
-        self.get_or_create_account(address).code = code;
+        let previous = self.get_code(&address);
+        self.account_entry(address);
+        self.record(JournalEntry::CodeChanged { address, previous });
+        self.write_code(address, code);
     }
 
     /// Get storage value
     pub fn get_storage(&self, address: &Address, key: &Uint256) -> Uint256 {
-        self.accounts
-            .get(address)
-            .map(|account| account.get_storage(key))
-            .unwrap_or(Uint256::zero())
+        self.backend.get_storage(address, key)
+    }
+
+    /// Whether any of the account's storage slots is non-zero
+    pub fn has_storage(&self, address: &Address) -> bool {
+        self.backend.storage_slots(address).iter().any(|(_, value)| !value.is_zero())
+    }
+
+    /// Get the value a slot held before the current transaction first wrote it (EIP-2200)
+    pub fn get_original_storage(&self, address: &Address, key: &Uint256) -> Uint256 {
+        match self.original_storage.get(&(*address, *key)) {
+            Some(original) => *original,
+            None => self.get_storage(address, key),
+        }
     }
 
     /// Set storage value
     pub fn set_storage(&mut self, address: Address, key: Uint256, value: Uint256) {
-        self.get_or_create_account(address).set_storage(key, value);
+        self.account_entry(address);
+        let previous = self.backend.get_storage(&address, &key);
+        self.backend.put_storage(address, key, value);
+        self.tries.touch_slot(address, key);
+        // Writes outside any checkpoint are setup, not part of a transaction
+        if self.open_checkpoints > 0 {
+            self.original_storage.entry((address, key)).or_insert(previous);
+        }
+        self.record(JournalEntry::StorageChanged { address, key, previous });
+    }
+
+    /// Iterate over full copies of all accounts in no particular order
+    pub fn accounts(&self) -> impl Iterator<Item = (Address, Account)> + '_ {
+        self.backend
+            .addresses()
+            .into_iter()
+            .filter_map(|address| Some((address, self.backend.load_account(&address)?)))
     }
 
-    /// Get all accounts (for debugging)
-    pub fn accounts(&self) -> &HashMap<Address, Account> {
-        &self.accounts
+    /// Persist the accounts to the backend's store
+    ///
+    /// Changes inside an open checkpoint are written too, so flush between transactions.
+    pub fn flush(&mut self) -> Result<(), BackendError> {
+        self.backend.flush()
     }
 
     /// Clear all storage
     pub fn clear(&mut self) {
-        self.accounts.clear();
+        self.backend.clear();
+        self.transient.clear();
+        self.accessed_addresses.clear();
+        self.accessed_slots.clear();
+        self.original_storage.clear();
+        self.journal.clear();
+        self.open_checkpoints = 0;
+        self.tries = StateTries::default();
+    }
+
+    /// Get a transient storage value
+    pub fn get_transient(&self, address: &Address, key: &Uint256) -> Uint256 {
+        self.transient
+            .get(&(*address, *key))
+            .cloned()
+            .unwrap_or_else(Uint256::zero)
+    }
+
+    /// Set a transient storage value
+    pub fn set_transient(&mut self, address: Address, key: Uint256, value: Uint256) {
+        let previous = self.get_transient(&address, &key);
+        self.record(JournalEntry::TransientStorageChanged { address, key, previous });
+        self.write_transient(address, key, value);
+    }
+
+    /// Write a transient storage slot without journaling
+    fn write_transient(&mut self, address: Address, key: Uint256, value: Uint256) {
+        if value.is_zero() {
+            self.transient.remove(&(address, key));
+        } else {
+            self.transient.insert((address, key), value);
+        }
+    }
+
+    /// Discard all transient storage
+    pub fn clear_transient(&mut self) {
+        self.transient.clear();
     }
 
     /// Get the number of accounts
     pub fn account_count(&self) -> usize {
-        self.accounts.len()
+        self.backend.account_count()
+    }
+
+    /// Root of an account's storage trie
+    pub fn storage_root(&mut self, address: &Address) -> Hash {
+        match self.backend.get_account(address) {
+            Some(_) => self.tries.storage_root(*address, self.backend.as_ref()),
+            None => EMPTY_ROOT,
+        }
+    }
+
+    /// Root of the world state trie
+    ///
+    /// Only accounts changed since the previous call are rehashed, and only the
+    /// slots written since then are fed into their storage tries.
+    pub fn state_root(&mut self) -> Hash {
+        if !self.tries.complete {
+            self.tries.dirty.extend(self.backend.addresses());
+            self.tries.complete = true;
+        }
+        for address in std::mem::take(&mut self.tries.dirty) {
+            let key = Keccak256::digest(address.as_bytes());
+            match self.backend.get_account(&address) {
+                Some(info) => {
+                    let storage_root = self.tries.storage_root(address, self.backend.as_ref());
+                    let trie_account = TrieAccount {
+                        nonce: info.nonce,
+                        balance: info.balance,
+                        storage_root,
+                        code_hash: info.code_hash,
+                    };
+                    self.tries.accounts.insert(&key, rlp::encode(&trie_account));
+                }
+                None => {
+                    self.tries.storage.remove(&address);
+                    self.tries.dirty_slots.remove(&address);
+                    self.tries.accounts.remove(&key);
+                }
+            }
+        }
+        self.tries.accounts.root_hash()
     }
 }
 
@@ -258,6 +755,7 @@ impl Default for Storage {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::Bytes;
 
     #[test]
     fn test_account_creation() {
@@ -288,6 +786,24 @@ mod tests {
         assert!(storage.sub_balance(&address, Uint256::from_u32(400)).is_err());
     }
 
+    #[test]
+    fn test_transfer() {
+        let mut storage = Storage::new();
+        let from = Address::zero();
+        let to = Address::new([0x11; 20]);
+        
+        assert!(storage.is_empty_account(&to));
+        storage.set_balance(from, Uint256::from_u32(100));
+        storage.transfer(&from, to, Uint256::from_u32(40)).unwrap();
+        assert_eq!(storage.get_balance(&from), Uint256::from_u32(60));
+        assert_eq!(storage.get_balance(&to), Uint256::from_u32(40));
+        assert!(!storage.is_empty_account(&to));
+        
+        // Insufficient balance leaves both accounts untouched
+        assert!(storage.transfer(&from, to, Uint256::from_u32(61)).is_err());
+        assert_eq!(storage.get_balance(&to), Uint256::from_u32(40));
+    }
+
     #[test]
     fn test_account_nonce() {
         let mut storage = Storage::new();
@@ -310,11 +826,11 @@ mod tests {
         assert_eq!(storage.get_storage(&address, &key), Uint256::zero());
         
         // Set storage
-        storage.set_storage(address, key.clone(), value.clone());
+        storage.set_storage(address, key, value);
         assert_eq!(storage.get_storage(&address, &key), value);
         
         // Set to zero (should remove from storage)
-        storage.set_storage(address, key.clone(), Uint256::zero());
+        storage.set_storage(address, key, Uint256::zero());
         assert_eq!(storage.get_storage(&address, &key), Uint256::zero());
     }
 
@@ -331,4 +847,166 @@ mod tests {
         assert_eq!(account.code, code);
         assert_ne!(account.code_hash(), Hash::zero());
     }
+
+    #[test]
+    fn test_revert_to_checkpoint() {
+        let mut storage = Storage::new();
+        let existing = Address::zero();
+        let created = Address::new([0x11; 20]);
+        storage.set_balance(existing, Uint256::from_u32(100));
+        storage.set_storage(existing, Uint256::one(), Uint256::from_u32(7));
+        
+        let checkpoint = storage.checkpoint();
+        storage.transfer(&existing, created, Uint256::from_u32(40)).unwrap();
+        storage.set_storage(existing, Uint256::one(), Uint256::from_u32(8));
+        storage.increment_nonce(existing);
+        storage.set_code(existing, vec![0x00]);
+        storage.set_transient(existing, Uint256::one(), Uint256::one());
+        assert!(!storage.warm_address(created));
+        assert!(!storage.warm_slot(created, Uint256::one()));
+        assert!(storage.warm_slot(created, Uint256::one()));
+        storage.revert_to(checkpoint);
+        
+        assert_eq!(storage.get_balance(&existing), Uint256::from_u32(100));
+        assert_eq!(storage.get_storage(&existing, &Uint256::one()), Uint256::from_u32(7));
+        assert_eq!(storage.get_nonce(&existing), Uint256::zero());
+        assert!(storage.get_code(&existing).is_empty());
+        assert!(storage.get_transient(&existing, &Uint256::one()).is_zero());
+        assert!(!storage.account_exists(&created));
+        assert!(!storage.is_warm_address(&created));
+        assert!(!storage.is_warm_slot(&created, &Uint256::one()));
+        assert_eq!(storage.journal_len(), 0);
+    }
+
+    #[test]
+    fn test_original_storage_values() {
+        let mut storage = Storage::new();
+        let address = Address::zero();
+        let key = Uint256::one();
+        storage.set_storage(address, key, Uint256::from_u32(1));
+
+        let checkpoint = storage.checkpoint();
+        storage.set_storage(address, key, Uint256::from_u32(2));
+        storage.set_storage(address, key, Uint256::from_u32(3));
+        assert_eq!(storage.get_original_storage(&address, &key), Uint256::from_u32(1));
+        assert_eq!(storage.get_storage(&address, &key), Uint256::from_u32(3));
+        storage.commit(checkpoint);
+
+        // The next transaction starts from the committed value
+        storage.end_transaction();
+        assert_eq!(storage.get_original_storage(&address, &key), Uint256::from_u32(3));
+    }
+
+    #[test]
+    fn test_nested_checkpoints() {
+        let mut storage = Storage::new();
+        let address = Address::zero();
+        
+        let outer = storage.checkpoint();
+        storage.set_balance(address, Uint256::from_u32(1));
+        let inner = storage.checkpoint();
+        storage.set_balance(address, Uint256::from_u32(2));
+        storage.commit(inner);
+        let reverted = storage.checkpoint();
+        storage.set_balance(address, Uint256::from_u32(3));
+        storage.revert_to(reverted);
+        assert_eq!(storage.get_balance(&address), Uint256::from_u32(2));
+        
+        // Committed inner changes are still undone by the enclosing checkpoint
+        storage.revert_to(outer);
+        assert!(!storage.account_exists(&address));
+    }
+
+    #[test]
+    fn test_revert_account_deletion() {
+        let mut storage = Storage::new();
+        let address = Address::zero();
+        storage.set_code(address, vec![0x60, 0x00]);
+        storage.set_balance(address, Uint256::from_u32(5));
+        
+        let checkpoint = storage.checkpoint();
+        storage.delete_account(&address);
+        assert!(!storage.account_exists(&address));
+        storage.revert_to(checkpoint);
+        
+        let account = storage.get_account(&address).unwrap();
+        assert!(!account.deleted);
+        assert_eq!(account.balance, Uint256::from_u32(5));
+        assert_eq!(account.code, vec![0x60, 0x00]);
+    }
+
+    #[test]
+    fn test_trie_account_rlp_round_trip() {
+        let mut account = Account::new();
+        account.nonce = Uint256::from_u64(1);
+        account.balance = Uint256::from_u64(1_000_000);
+        let trie_account = account.to_trie_account(Hash::new([0x56; 32]));
+
+        assert_eq!(
+            trie_account.code_hash.to_hex(),
+            "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        let encoded = rlp::encode(&trie_account);
+        assert_eq!(rlp::decode::<TrieAccount>(&encoded).unwrap(), trie_account);
+    }
+
+    #[test]
+    fn test_single_account_state_root() {
+        let mut storage = Storage::new();
+        assert_eq!(storage.state_root(), EMPTY_ROOT);
+        let address = Address::from_hex("0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b").unwrap();
+        storage.set_balance(address, Uint256::from_u64(1_000_000_000_000_000_000));
+        assert_eq!(storage.storage_root(&address), EMPTY_ROOT);
+
+        // A lone account is a single leaf: rlp([0x20 ++ keccak(address), rlp(account)])
+        let mut path = vec![0x20];
+        path.extend_from_slice(&Keccak256::digest(address.as_bytes()));
+        let account = rlp::encode(&storage.get_account(&address).unwrap().to_trie_account(EMPTY_ROOT));
+        let mut leaf = Vec::new();
+        rlp::encode_list(&[&Bytes::new(path), &Bytes::new(account)], &mut leaf);
+        assert_eq!(storage.state_root(), Hash::new(Keccak256::digest(&leaf).into()));
+    }
+
+    #[test]
+    fn test_state_root_is_updated_incrementally() {
+        let a = Address::from_hex("0x1000000000000000000000000000000000000001").unwrap();
+        let b = Address::from_hex("0x2000000000000000000000000000000000000002").unwrap();
+        let mut storage = Storage::new();
+        storage.set_balance(a, Uint256::from_u64(10));
+        storage.set_storage(b, Uint256::one(), Uint256::from_u64(42));
+        let before = storage.state_root();
+        let storage_root = storage.storage_root(&b);
+        assert_ne!(storage_root, EMPTY_ROOT);
+
+        // Changes made through any path, then undone, restore the same roots
+        let checkpoint = storage.checkpoint();
+        let mut replaced = storage.get_account(&b).unwrap();
+        replaced.set_storage(Uint256::from_u64(2), Uint256::from_u64(7));
+        storage.set_account(b, replaced);
+        storage.increment_nonce(a);
+        assert_ne!(storage.storage_root(&b), storage_root);
+        assert_ne!(storage.state_root(), before);
+        storage.revert_to(checkpoint);
+        assert_eq!(storage.storage_root(&b), storage_root);
+        assert_eq!(storage.state_root(), before);
+
+        // Only the written slot is fed into the existing storage trie
+        storage.state_root();
+        storage.set_storage(b, Uint256::from_u64(3), Uint256::from_u64(9));
+        assert_eq!(storage.tries.dirty_slots[&b].len(), 1);
+        assert!(storage.tries.storage.contains_key(&b));
+        storage.state_root();
+        assert!(storage.tries.dirty_slots.is_empty());
+        storage.set_storage(b, Uint256::from_u64(3), Uint256::zero());
+
+        // Clearing a slot removes it from the storage trie
+        storage.set_storage(b, Uint256::one(), Uint256::zero());
+        assert_eq!(storage.storage_root(&b), EMPTY_ROOT);
+
+        // Rebuilding from scratch gives the same root
+        let mut fresh = Storage::new();
+        fresh.set_balance(a, Uint256::from_u64(10));
+        fresh.set_account(b, Account::new());
+        assert_eq!(storage.state_root(), fresh.state_root());
+    }
 }
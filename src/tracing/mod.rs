@@ -144,6 +144,12 @@ pub struct ExecutionTrace {
     pub opcode_frequency: HashMap<Opcode, usize>,
 }
 
+impl Default for ExecutionTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ExecutionTrace {
     /// Create a new execution trace
     pub fn new() -> Self {
@@ -162,6 +168,11 @@ impl ExecutionTrace {
 
     /// Add a step to the trace
     pub fn add_step(&mut self, step: ExecutionStep) {
+        self.insert_step(self.steps.len(), step);
+    }
+
+    /// Insert a step at a given position (used when sub-call steps were recorded first)
+    pub fn insert_step(&mut self, index: usize, step: ExecutionStep) {
         // Update opcode frequency
         *self.opcode_frequency.entry(step.opcode).or_insert(0) += 1;
         
@@ -171,7 +182,7 @@ impl ExecutionTrace {
         // Update storage stats
         self.storage_stats.update(&step);
         
-        self.steps.push(step);
+        self.steps.insert(index, step);
         self.opcode_count += 1;
     }
 
@@ -275,6 +286,12 @@ pub struct MemoryStats {
     pub allocation_points: Vec<usize>,
 }
 
+impl Default for MemoryStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MemoryStats {
     pub fn new() -> Self {
         MemoryStats {
@@ -312,6 +329,12 @@ pub struct StorageStats {
     pub accessed_keys: Vec<Uint256>,
 }
 
+impl Default for StorageStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl StorageStats {
     pub fn new() -> Self {
         StorageStats {
@@ -328,7 +351,7 @@ impl StorageStats {
             self.total_accesses += 1;
             
             if !self.accessed_keys.contains(key) {
-                self.accessed_keys.push(key.clone());
+                self.accessed_keys.push(*key);
                 self.unique_keys += 1;
             }
             
@@ -361,15 +384,15 @@ pub struct ExecutionSummary {
 
 impl fmt::Display for ExecutionSummary {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Execution Summary:\n")?;
-        write!(f, "  Steps: {}\n", self.total_steps)?;
-        write!(f, "  Success: {}\n", self.success)?;
-        write!(f, "  Gas Consumed: {}\n", self.total_gas_consumed)?;
-        write!(f, "  Execution Time: {}μs\n", self.execution_time_us)?;
-        write!(f, "  Gas Efficiency: {:.4} opcodes/gas\n", self.gas_efficiency)?;
-        write!(f, "  Peak Memory: {} bytes\n", self.memory_peak)?;
-        write!(f, "  Storage Accesses: {}\n", self.storage_accesses)?;
-        write!(f, "  Unique Opcodes: {}\n", self.unique_opcodes)?;
+        writeln!(f, "Execution Summary:")?;
+        writeln!(f, "  Steps: {}", self.total_steps)?;
+        writeln!(f, "  Success: {}", self.success)?;
+        writeln!(f, "  Gas Consumed: {}", self.total_gas_consumed)?;
+        writeln!(f, "  Execution Time: {}μs", self.execution_time_us)?;
+        writeln!(f, "  Gas Efficiency: {:.4} opcodes/gas", self.gas_efficiency)?;
+        writeln!(f, "  Peak Memory: {} bytes", self.memory_peak)?;
+        writeln!(f, "  Storage Accesses: {}", self.storage_accesses)?;
+        writeln!(f, "  Unique Opcodes: {}", self.unique_opcodes)?;
         
         if let Some((opcode, count)) = self.most_frequent_opcode {
             write!(f, "  Most Frequent: {} ({} times)", opcode, count)?;
@@ -419,6 +442,21 @@ impl ExecutionTracer {
         }
     }
 
+    /// Add a completed step to the trace
+    pub fn add_step(&mut self, step: ExecutionStep) {
+        self.trace.add_step(step);
+    }
+
+    /// Insert a completed step at a given position in the trace
+    pub fn insert_step(&mut self, index: usize, step: ExecutionStep) {
+        self.trace.insert_step(index, step);
+    }
+
+    /// Get the number of steps recorded so far
+    pub fn step_count(&self) -> usize {
+        self.trace.steps.len()
+    }
+
     /// Record an error in the current step
     pub fn record_error(&mut self, error: String) {
         if let Some(ref mut step) = self.current_step {
@@ -0,0 +1,650 @@
+use crate::{
+    types::{Address, Bytes, Hash, Uint256},
+    rlp::{self, Decodable, Encodable, Header, RlpError},
+};
+use super::Transaction;
+use sha3::{Digest, Keccak256};
+use thiserror::Error;
+
+/// Type byte of an EIP-2930 access-list transaction
+pub const ACCESS_LIST_TX_TYPE: u8 = 0x01;
+/// Type byte of an EIP-1559 dynamic-fee transaction
+pub const DYNAMIC_FEE_TX_TYPE: u8 = 0x02;
+/// Type byte of an EIP-4844 blob transaction
+pub const BLOB_TX_TYPE: u8 = 0x03;
+
+/// Errors decoding a raw transaction
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EnvelopeError {
+    #[error("Empty transaction")]
+    Empty,
+    #[error("Unsupported transaction type 0x{0:02x}")]
+    UnsupportedType(u8),
+    #[error("Invalid RLP: {0}")]
+    Rlp(#[from] RlpError),
+}
+
+/// An address and the storage keys a transaction declares it will touch (EIP-2930)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<Hash>,
+}
+
+/// ECDSA signature values as they appear in the transaction
+///
+/// For typed transactions `v` is the y-parity (0 or 1); for legacy ones it is
+/// 27/28, or `chain_id * 2 + 35/36` with EIP-155 replay protection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Signature {
+    pub v: u64,
+    pub r: Uint256,
+    pub s: Uint256,
+}
+
+/// Pre-EIP-2718 transaction, optionally replay-protected by EIP-155
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyTransaction {
+    pub nonce: u64,
+    pub gas_price: Uint256,
+    pub gas_limit: u64,
+    pub to: Option<Address>,
+    pub value: Uint256,
+    pub data: Bytes,
+    pub signature: Signature,
+}
+
+impl LegacyTransaction {
+    /// Chain id encoded in `v` (EIP-155), or `None` for unprotected transactions
+    pub fn chain_id(&self) -> Option<u64> {
+        (self.signature.v >= 35).then(|| (self.signature.v - 35) / 2)
+    }
+}
+
+/// Transaction with an access list (EIP-2930)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessListTransaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub gas_price: Uint256,
+    pub gas_limit: u64,
+    pub to: Option<Address>,
+    pub value: Uint256,
+    pub data: Bytes,
+    pub access_list: Vec<AccessListItem>,
+    pub signature: Signature,
+}
+
+/// Transaction with a base fee cap and priority fee (EIP-1559)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynamicFeeTransaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub max_priority_fee_per_gas: Uint256,
+    pub max_fee_per_gas: Uint256,
+    pub gas_limit: u64,
+    pub to: Option<Address>,
+    pub value: Uint256,
+    pub data: Bytes,
+    pub access_list: Vec<AccessListItem>,
+    pub signature: Signature,
+}
+
+/// Transaction carrying blob commitments (EIP-4844); it cannot create contracts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobTransaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub max_priority_fee_per_gas: Uint256,
+    pub max_fee_per_gas: Uint256,
+    pub gas_limit: u64,
+    pub to: Address,
+    pub value: Uint256,
+    pub data: Bytes,
+    pub access_list: Vec<AccessListItem>,
+    pub max_fee_per_blob_gas: Uint256,
+    pub blob_versioned_hashes: Vec<Hash>,
+    pub signature: Signature,
+}
+
+/// A signed transaction of any supported type (EIP-2718)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxEnvelope {
+    Legacy(LegacyTransaction),
+    AccessList(AccessListTransaction),
+    DynamicFee(DynamicFeeTransaction),
+    Blob(BlobTransaction),
+}
+
+impl TxEnvelope {
+    /// EIP-2718 type byte (0 for legacy transactions)
+    pub fn tx_type(&self) -> u8 {
+        match self {
+            TxEnvelope::Legacy(_) => 0x00,
+            TxEnvelope::AccessList(_) => ACCESS_LIST_TX_TYPE,
+            TxEnvelope::DynamicFee(_) => DYNAMIC_FEE_TX_TYPE,
+            TxEnvelope::Blob(_) => BLOB_TX_TYPE,
+        }
+    }
+
+    /// Chain id the transaction is bound to, if any
+    pub fn chain_id(&self) -> Option<u64> {
+        match self {
+            TxEnvelope::Legacy(tx) => tx.chain_id(),
+            TxEnvelope::AccessList(tx) => Some(tx.chain_id),
+            TxEnvelope::DynamicFee(tx) => Some(tx.chain_id),
+            TxEnvelope::Blob(tx) => Some(tx.chain_id),
+        }
+    }
+
+    /// Sender nonce
+    pub fn nonce(&self) -> u64 {
+        match self {
+            TxEnvelope::Legacy(tx) => tx.nonce,
+            TxEnvelope::AccessList(tx) => tx.nonce,
+            TxEnvelope::DynamicFee(tx) => tx.nonce,
+            TxEnvelope::Blob(tx) => tx.nonce,
+        }
+    }
+
+    /// Gas limit
+    pub fn gas_limit(&self) -> u64 {
+        match self {
+            TxEnvelope::Legacy(tx) => tx.gas_limit,
+            TxEnvelope::AccessList(tx) => tx.gas_limit,
+            TxEnvelope::DynamicFee(tx) => tx.gas_limit,
+            TxEnvelope::Blob(tx) => tx.gas_limit,
+        }
+    }
+
+    /// Recipient, or `None` for contract creation
+    pub fn to(&self) -> Option<Address> {
+        match self {
+            TxEnvelope::Legacy(tx) => tx.to,
+            TxEnvelope::AccessList(tx) => tx.to,
+            TxEnvelope::DynamicFee(tx) => tx.to,
+            TxEnvelope::Blob(tx) => Some(tx.to),
+        }
+    }
+
+    /// Signature values
+    pub fn signature(&self) -> &Signature {
+        match self {
+            TxEnvelope::Legacy(tx) => &tx.signature,
+            TxEnvelope::AccessList(tx) => &tx.signature,
+            TxEnvelope::DynamicFee(tx) => &tx.signature,
+            TxEnvelope::Blob(tx) => &tx.signature,
+        }
+    }
+
+    /// Encode as EIP-2718 bytes: `rlp(tx)` for legacy, `type || rlp(tx)` otherwise
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            TxEnvelope::Legacy(tx) => rlp::encode(tx),
+            TxEnvelope::AccessList(tx) => Self::typed(ACCESS_LIST_TX_TYPE, tx),
+            TxEnvelope::DynamicFee(tx) => Self::typed(DYNAMIC_FEE_TX_TYPE, tx),
+            TxEnvelope::Blob(tx) => Self::typed(BLOB_TX_TYPE, tx),
+        }
+    }
+
+    fn typed(tx_type: u8, tx: &dyn Encodable) -> Vec<u8> {
+        let mut encoded = vec![tx_type];
+        tx.encode(&mut encoded);
+        encoded
+    }
+
+    /// Decode EIP-2718 bytes as produced by `encode`
+    ///
+    /// Blob transactions are also accepted in their network form, with the
+    /// blobs, commitments and proofs attached; the sidecar is dropped.
+    pub fn decode(raw: &[u8]) -> Result<Self, EnvelopeError> {
+        let (&first, payload) = raw.split_first().ok_or(EnvelopeError::Empty)?;
+        match first {
+            0xc0..=0xff => Ok(TxEnvelope::Legacy(rlp::decode(raw)?)),
+            ACCESS_LIST_TX_TYPE => Ok(TxEnvelope::AccessList(rlp::decode(payload)?)),
+            DYNAMIC_FEE_TX_TYPE => Ok(TxEnvelope::DynamicFee(rlp::decode(payload)?)),
+            BLOB_TX_TYPE => Ok(TxEnvelope::Blob(Self::decode_blob(payload)?)),
+            tx_type => Err(EnvelopeError::UnsupportedType(tx_type)),
+        }
+    }
+
+    /// Decode a blob transaction in canonical or network (`[tx, blobs, commitments, proofs]`) form
+    fn decode_blob(payload: &[u8]) -> Result<BlobTransaction, RlpError> {
+        let mut buf = payload;
+        let mut wrapper = Header::decode_list(&mut buf)?;
+        let mut first_field = wrapper;
+        if !Header::decode(&mut first_field)?.list {
+            return rlp::decode(payload);
+        }
+        if !buf.is_empty() {
+            return Err(RlpError::TrailingBytes(buf.len()));
+        }
+
+        let tx = BlobTransaction::decode(&mut wrapper)?;
+        for _ in 0..3 {
+            Vec::<Bytes>::decode(&mut wrapper)?;
+        }
+        rlp::finish_list(wrapper)?;
+        Ok(tx)
+    }
+
+    /// Transaction hash: keccak256 of the EIP-2718 encoding
+    pub fn tx_hash(&self) -> Hash {
+        Hash::new(Keccak256::digest(self.encode()).into())
+    }
+
+    /// Hash the sender signs: the encoding without `v, r, s`
+    ///
+    /// EIP-155 legacy transactions sign `chain_id, 0, 0` in place of the signature.
+    pub fn signing_hash(&self) -> Hash {
+        let mut preimage = Vec::new();
+        match self {
+            TxEnvelope::Legacy(tx) => {
+                let mut fields = tx.unsigned_fields();
+                let chain_id = tx.chain_id();
+                if let Some(chain_id) = &chain_id {
+                    fields.extend([chain_id as &dyn Encodable, &0u64, &0u64]);
+                }
+                rlp::encode_list(&fields, &mut preimage);
+            }
+            TxEnvelope::AccessList(tx) => {
+                preimage.push(ACCESS_LIST_TX_TYPE);
+                rlp::encode_list(&tx.unsigned_fields(), &mut preimage);
+            }
+            TxEnvelope::DynamicFee(tx) => {
+                preimage.push(DYNAMIC_FEE_TX_TYPE);
+                rlp::encode_list(&tx.unsigned_fields(), &mut preimage);
+            }
+            TxEnvelope::Blob(tx) => {
+                preimage.push(BLOB_TX_TYPE);
+                rlp::encode_list(&tx.unsigned_fields(), &mut preimage);
+            }
+        }
+        Hash::new(Keccak256::digest(preimage).into())
+    }
+
+    /// Convert into a transaction the processor can apply for `sender`
+    pub fn into_transaction(self, sender: Address) -> Transaction {
+        let hash = self.tx_hash();
+        let tx_type = self.tx_type();
+        let mut tx = match self {
+            TxEnvelope::Legacy(tx) => Transaction {
+                nonce: tx.nonce,
+                gas_limit: tx.gas_limit,
+                max_fee_per_gas: tx.gas_price,
+                max_priority_fee_per_gas: tx.gas_price,
+                ..Transaction::new(sender, tx.to, tx.value, tx.data)
+            },
+            TxEnvelope::AccessList(tx) => Transaction {
+                nonce: tx.nonce,
+                gas_limit: tx.gas_limit,
+                max_fee_per_gas: tx.gas_price,
+                max_priority_fee_per_gas: tx.gas_price,
+                access_list: tx.access_list,
+                ..Transaction::new(sender, tx.to, tx.value, tx.data)
+            },
+            TxEnvelope::DynamicFee(tx) => Transaction {
+                nonce: tx.nonce,
+                gas_limit: tx.gas_limit,
+                max_fee_per_gas: tx.max_fee_per_gas,
+                max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+                access_list: tx.access_list,
+                ..Transaction::new(sender, tx.to, tx.value, tx.data)
+            },
+            TxEnvelope::Blob(tx) => Transaction {
+                nonce: tx.nonce,
+                gas_limit: tx.gas_limit,
+                max_fee_per_gas: tx.max_fee_per_gas,
+                max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+                access_list: tx.access_list,
+                max_fee_per_blob_gas: tx.max_fee_per_blob_gas,
+                blob_hashes: tx.blob_versioned_hashes,
+                ..Transaction::new(sender, Some(tx.to), tx.value, tx.data)
+            },
+        };
+        tx.tx_type = tx_type;
+        tx.hash = hash;
+        tx
+    }
+}
+
+impl Encodable for AccessListItem {
+    fn encode(&self, out: &mut Vec<u8>) {
+        rlp::encode_list(&[&self.address, &self.storage_keys], out);
+    }
+}
+
+impl Decodable for AccessListItem {
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        let mut payload = Header::decode_list(buf)?;
+        let item = AccessListItem {
+            address: Decodable::decode(&mut payload)?,
+            storage_keys: Decodable::decode(&mut payload)?,
+        };
+        rlp::finish_list(payload)?;
+        Ok(item)
+    }
+}
+
+impl LegacyTransaction {
+    /// Fields covered by the signature, in encoding order
+    fn unsigned_fields(&self) -> Vec<&dyn Encodable> {
+        vec![&self.nonce, &self.gas_price, &self.gas_limit, &self.to, &self.value, &self.data]
+    }
+}
+
+impl Encodable for LegacyTransaction {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let mut fields = self.unsigned_fields();
+        fields.extend(self.signature.fields());
+        rlp::encode_list(&fields, out);
+    }
+}
+
+impl Decodable for LegacyTransaction {
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        let mut payload = Header::decode_list(buf)?;
+        let fields = &mut payload;
+        let tx = LegacyTransaction {
+            nonce: Decodable::decode(fields)?,
+            gas_price: Decodable::decode(fields)?,
+            gas_limit: Decodable::decode(fields)?,
+            to: Decodable::decode(fields)?,
+            value: Decodable::decode(fields)?,
+            data: Decodable::decode(fields)?,
+            signature: Signature::decode_fields(fields)?,
+        };
+        rlp::finish_list(payload)?;
+        Ok(tx)
+    }
+}
+
+impl AccessListTransaction {
+    /// Fields covered by the signature, in encoding order
+    fn unsigned_fields(&self) -> Vec<&dyn Encodable> {
+        vec![
+            &self.chain_id, &self.nonce, &self.gas_price, &self.gas_limit, &self.to, &self.value, &self.data,
+            &self.access_list,
+        ]
+    }
+}
+
+impl Encodable for AccessListTransaction {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let mut fields = self.unsigned_fields();
+        fields.extend(self.signature.fields());
+        rlp::encode_list(&fields, out);
+    }
+}
+
+impl Decodable for AccessListTransaction {
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        let mut payload = Header::decode_list(buf)?;
+        let fields = &mut payload;
+        let tx = AccessListTransaction {
+            chain_id: Decodable::decode(fields)?,
+            nonce: Decodable::decode(fields)?,
+            gas_price: Decodable::decode(fields)?,
+            gas_limit: Decodable::decode(fields)?,
+            to: Decodable::decode(fields)?,
+            value: Decodable::decode(fields)?,
+            data: Decodable::decode(fields)?,
+            access_list: Decodable::decode(fields)?,
+            signature: Signature::decode_fields(fields)?,
+        };
+        rlp::finish_list(payload)?;
+        Ok(tx)
+    }
+}
+
+impl DynamicFeeTransaction {
+    /// Fields covered by the signature, in encoding order
+    fn unsigned_fields(&self) -> Vec<&dyn Encodable> {
+        vec![
+            &self.chain_id, &self.nonce, &self.max_priority_fee_per_gas, &self.max_fee_per_gas, &self.gas_limit,
+            &self.to, &self.value, &self.data, &self.access_list,
+        ]
+    }
+}
+
+impl Encodable for DynamicFeeTransaction {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let mut fields = self.unsigned_fields();
+        fields.extend(self.signature.fields());
+        rlp::encode_list(&fields, out);
+    }
+}
+
+impl Decodable for DynamicFeeTransaction {
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        let mut payload = Header::decode_list(buf)?;
+        let fields = &mut payload;
+        let tx = DynamicFeeTransaction {
+            chain_id: Decodable::decode(fields)?,
+            nonce: Decodable::decode(fields)?,
+            max_priority_fee_per_gas: Decodable::decode(fields)?,
+            max_fee_per_gas: Decodable::decode(fields)?,
+            gas_limit: Decodable::decode(fields)?,
+            to: Decodable::decode(fields)?,
+            value: Decodable::decode(fields)?,
+            data: Decodable::decode(fields)?,
+            access_list: Decodable::decode(fields)?,
+            signature: Signature::decode_fields(fields)?,
+        };
+        rlp::finish_list(payload)?;
+        Ok(tx)
+    }
+}
+
+impl BlobTransaction {
+    /// Fields covered by the signature, in encoding order
+    fn unsigned_fields(&self) -> Vec<&dyn Encodable> {
+        vec![
+            &self.chain_id, &self.nonce, &self.max_priority_fee_per_gas, &self.max_fee_per_gas, &self.gas_limit,
+            &self.to, &self.value, &self.data, &self.access_list, &self.max_fee_per_blob_gas,
+            &self.blob_versioned_hashes,
+        ]
+    }
+}
+
+impl Encodable for BlobTransaction {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let mut fields = self.unsigned_fields();
+        fields.extend(self.signature.fields());
+        rlp::encode_list(&fields, out);
+    }
+}
+
+impl Decodable for BlobTransaction {
+    fn decode(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        let mut payload = Header::decode_list(buf)?;
+        let fields = &mut payload;
+        let tx = BlobTransaction {
+            chain_id: Decodable::decode(fields)?,
+            nonce: Decodable::decode(fields)?,
+            max_priority_fee_per_gas: Decodable::decode(fields)?,
+            max_fee_per_gas: Decodable::decode(fields)?,
+            gas_limit: Decodable::decode(fields)?,
+            to: Decodable::decode(fields)?,
+            value: Decodable::decode(fields)?,
+            data: Decodable::decode(fields)?,
+            access_list: Decodable::decode(fields)?,
+            max_fee_per_blob_gas: Decodable::decode(fields)?,
+            blob_versioned_hashes: Decodable::decode(fields)?,
+            signature: Signature::decode_fields(fields)?,
+        };
+        rlp::finish_list(payload)?;
+        Ok(tx)
+    }
+}
+
+impl Signature {
+    fn fields(&self) -> [&dyn Encodable; 3] {
+        [&self.v, &self.r, &self.s]
+    }
+
+    /// Read the trailing `v, r, s` fields of a transaction list
+    fn decode_fields(buf: &mut &[u8]) -> Result<Self, RlpError> {
+        Ok(Signature {
+            v: Decodable::decode(buf)?,
+            r: Decodable::decode(buf)?,
+            s: Decodable::decode(buf)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The signed example transaction from EIP-155
+    const EIP155_EXAMPLE: &str = "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+
+    fn sample_access_list() -> Vec<AccessListItem> {
+        vec![AccessListItem {
+            address: Address::from_hex("0x00000000000000000000000000000000000000aa").unwrap(),
+            storage_keys: vec![Hash::zero(), Hash::new([0x11; 32])],
+        }]
+    }
+
+    #[test]
+    fn test_decode_eip155_legacy_transaction() {
+        let raw = hex::decode(EIP155_EXAMPLE).unwrap();
+        let envelope = TxEnvelope::decode(&raw).unwrap();
+
+        let TxEnvelope::Legacy(tx) = &envelope else { panic!("expected a legacy transaction") };
+        assert_eq!(tx.nonce, 9);
+        assert_eq!(tx.gas_price, Uint256::from_u64(20_000_000_000));
+        assert_eq!(tx.gas_limit, 21000);
+        assert_eq!(tx.to, Some(Address::from_hex("0x3535353535353535353535353535353535353535").unwrap()));
+        assert_eq!(tx.value, Uint256::from_u64(1_000_000_000_000_000_000));
+        assert_eq!(envelope.chain_id(), Some(1));
+        assert_eq!(envelope.encode(), raw);
+        assert_eq!(
+            envelope.tx_hash(),
+            Hash::from_hex("0x33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_typed_transactions_round_trip() {
+        let signature = Signature { v: 1, r: Uint256::from_u64(0xabcdef), s: Uint256::from_u64(0x123456) };
+        let envelopes = vec![
+            TxEnvelope::AccessList(AccessListTransaction {
+                chain_id: 1,
+                nonce: 0,
+                gas_price: Uint256::from_u64(1_000_000_000),
+                gas_limit: 50_000,
+                to: None,
+                value: Uint256::zero(),
+                data: Bytes::new(vec![0x60, 0x00]),
+                access_list: sample_access_list(),
+                signature,
+            }),
+            TxEnvelope::DynamicFee(DynamicFeeTransaction {
+                chain_id: 11155111,
+                nonce: 300,
+                max_priority_fee_per_gas: Uint256::from_u64(2_000_000_000),
+                max_fee_per_gas: Uint256::from_u64(30_000_000_000),
+                gas_limit: 21_000,
+                to: Some(Address::from_hex("0x00000000000000000000000000000000000000bb").unwrap()),
+                value: Uint256::from_u64(1),
+                data: Bytes::new(vec![0xab; 100]),
+                access_list: Vec::new(),
+                signature,
+            }),
+            TxEnvelope::Blob(BlobTransaction {
+                chain_id: 1,
+                nonce: 7,
+                max_priority_fee_per_gas: Uint256::one(),
+                max_fee_per_gas: Uint256::from_u64(100),
+                gas_limit: 100_000,
+                to: Address::from_hex("0x00000000000000000000000000000000000000cc").unwrap(),
+                value: Uint256::zero(),
+                data: Bytes::empty(),
+                access_list: sample_access_list(),
+                max_fee_per_blob_gas: Uint256::from_u64(10),
+                blob_versioned_hashes: vec![Hash::new([0x01; 32])],
+                signature,
+            }),
+        ];
+
+        for envelope in envelopes {
+            let encoded = envelope.encode();
+            assert_eq!(encoded[0], envelope.tx_type());
+            assert_eq!(TxEnvelope::decode(&encoded).unwrap(), envelope);
+        }
+    }
+
+    #[test]
+    fn test_blob_transaction_network_form() {
+        let tx = BlobTransaction {
+            chain_id: 1,
+            nonce: 0,
+            max_priority_fee_per_gas: Uint256::one(),
+            max_fee_per_gas: Uint256::from_u64(100),
+            gas_limit: 21_000,
+            to: Address::zero(),
+            value: Uint256::zero(),
+            data: Bytes::empty(),
+            access_list: Vec::new(),
+            max_fee_per_blob_gas: Uint256::one(),
+            blob_versioned_hashes: vec![Hash::new([0x01; 32])],
+            signature: Signature::default(),
+        };
+        let envelope = TxEnvelope::Blob(tx);
+        let canonical = envelope.encode();
+
+        // [tx, blobs, commitments, proofs] with empty sidecar lists
+        let mut wrapper = canonical[1..].to_vec();
+        wrapper.extend([0xc0, 0xc0, 0xc0]);
+        let mut network = vec![BLOB_TX_TYPE];
+        Header { list: true, payload_length: wrapper.len() }.encode(&mut network);
+        network.extend(wrapper);
+
+        assert_eq!(TxEnvelope::decode(&network).unwrap(), envelope);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(TxEnvelope::decode(&[]), Err(EnvelopeError::Empty));
+        assert_eq!(TxEnvelope::decode(&[0x05, 0xc0]), Err(EnvelopeError::UnsupportedType(0x05)));
+        assert_eq!(
+            TxEnvelope::decode(&[0x02, 0xc1, 0x01]),
+            Err(EnvelopeError::Rlp(RlpError::InputTooShort))
+        );
+
+        let mut raw = hex::decode(EIP155_EXAMPLE).unwrap();
+        raw.push(0x00);
+        assert_eq!(TxEnvelope::decode(&raw), Err(EnvelopeError::Rlp(RlpError::TrailingBytes(1))));
+        raw.truncate(raw.len() - 10);
+        assert_eq!(TxEnvelope::decode(&raw), Err(EnvelopeError::Rlp(RlpError::InputTooShort)));
+    }
+
+    #[test]
+    fn test_into_transaction_carries_fees_and_access_list() {
+        let sender = Address::from_hex("0x00000000000000000000000000000000000000dd").unwrap();
+        let envelope = TxEnvelope::DynamicFee(DynamicFeeTransaction {
+            chain_id: 1,
+            nonce: 4,
+            max_priority_fee_per_gas: Uint256::from_u64(2),
+            max_fee_per_gas: Uint256::from_u64(50),
+            gas_limit: 60_000,
+            to: None,
+            value: Uint256::zero(),
+            data: Bytes::empty(),
+            access_list: sample_access_list(),
+            signature: Signature::default(),
+        });
+        let hash = envelope.tx_hash();
+        let tx = envelope.into_transaction(sender);
+
+        assert_eq!(tx.sender, sender);
+        assert!(tx.is_create());
+        assert_eq!(tx.nonce, 4);
+        assert_eq!(tx.max_fee_per_gas, Uint256::from_u64(50));
+        assert_eq!(tx.max_priority_fee_per_gas, Uint256::from_u64(2));
+        assert_eq!(tx.access_list, sample_access_list());
+        assert_eq!(tx.hash, hash);
+    }
+}
@@ -0,0 +1,953 @@
+use crate::{
+    types::{Address, Uint256, Bytes, Hash},
+    storage::{Storage, StorageError},
+    gas::GasCosts,
+    hardfork::Hardfork,
+    events::EventLog,
+    block::{BlockContext, TransactionContext},
+    executor::{
+        code_deposit_cost, create_address, create_collision, ExecutionContext, ExecutionError, ExecutionOutcome, Executor, HaltReason,
+    },
+    precompiles::Precompile,
+};
+use std::collections::HashSet;
+use thiserror::Error;
+
+pub mod envelope;
+pub mod signature;
+
+pub use envelope::*;
+pub use signature::*;
+
+/// Reasons a transaction is rejected before any state changes
+#[derive(Error, Debug)]
+pub enum TransactionError {
+    #[error("Nonce mismatch: expected {expected}, got {got}")]
+    NonceMismatch { expected: u64, got: u64 },
+    #[error("Sender nonce overflow")]
+    NonceOverflow,
+    #[error("Sender {sender} has deployed code (EIP-3607)")]
+    SenderNotEoa { sender: Address },
+    #[error("Insufficient funds: required {required}, available {available}")]
+    InsufficientFunds { required: Uint256, available: Uint256 },
+    #[error("Intrinsic gas too low: required {required}, limit {limit}")]
+    IntrinsicGasTooLow { required: u64, limit: u64 },
+    #[error("Gas limit {gas_limit} exceeds block gas limit {block_gas_limit}")]
+    GasLimitExceedsBlock { gas_limit: u64, block_gas_limit: Uint256 },
+    #[error("Max fee per gas {max_fee} is below the base fee {base_fee}")]
+    FeeCapTooLow { max_fee: Uint256, base_fee: Uint256 },
+    #[error("Max fee per blob gas {max_fee} is below the blob base fee {blob_base_fee}")]
+    BlobFeeCapTooLow { max_fee: Uint256, blob_base_fee: Uint256 },
+    #[error("Max priority fee {priority_fee} exceeds max fee {max_fee}")]
+    PriorityFeeTooHigh { priority_fee: Uint256, max_fee: Uint256 },
+    #[error("Transaction type {tx_type} is not available before {introduced}")]
+    TypeNotActivated { tx_type: u8, introduced: Hardfork },
+    #[error("Init code size {size} exceeds limit {limit} (EIP-3860)")]
+    InitcodeTooLarge { size: usize, limit: usize },
+    #[error("Invalid signature: {0}")]
+    Signature(#[from] SignatureError),
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+    #[error("Execution error: {0}")]
+    Execution(#[from] ExecutionError),
+}
+
+/// A transaction to apply to the world state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    /// EIP-2718 type byte (0 for legacy transactions)
+    pub tx_type: u8,
+    /// Sender (already recovered from the signature)
+    pub sender: Address,
+    /// Recipient, or `None` to create a contract
+    pub to: Option<Address>,
+    /// Value transferred to the recipient
+    pub value: Uint256,
+    /// Call data, or init code for contract creation
+    pub data: Bytes,
+    /// Sender nonce
+    pub nonce: u64,
+    /// Gas limit
+    pub gas_limit: u64,
+    /// Maximum total fee per gas (the gas price for legacy transactions)
+    pub max_fee_per_gas: Uint256,
+    /// Maximum tip per gas paid to the coinbase (EIP-1559)
+    pub max_priority_fee_per_gas: Uint256,
+    /// Addresses and storage keys declared up front (EIP-2930)
+    pub access_list: Vec<AccessListItem>,
+    /// Maximum fee per unit of blob gas (EIP-4844)
+    pub max_fee_per_blob_gas: Uint256,
+    /// Versioned hashes of the attached blobs (EIP-4844)
+    pub blob_hashes: Vec<Hash>,
+    /// Hash of the signed transaction, exposed to the executor
+    pub hash: Hash,
+}
+
+impl Transaction {
+    /// Create a legacy transaction with a 1M gas limit and a 20 gwei gas price
+    ///
+    /// A legacy gas price is both the fee cap and the tip, so the coinbase gets
+    /// everything above the base fee.
+    pub fn new(sender: Address, to: Option<Address>, value: Uint256, data: Bytes) -> Self {
+        Transaction {
+            tx_type: 0x00,
+            sender,
+            to,
+            value,
+            data,
+            nonce: 0,
+            gas_limit: 1_000_000,
+            max_fee_per_gas: Uint256::from_u64(20_000_000_000), // 20 gwei
+            max_priority_fee_per_gas: Uint256::from_u64(20_000_000_000),
+            access_list: Vec::new(),
+            max_fee_per_blob_gas: Uint256::zero(),
+            blob_hashes: Vec::new(),
+            hash: Hash::zero(),
+        }
+    }
+
+    /// First hardfork accepting this transaction's type
+    ///
+    /// Attached blobs make a transaction a blob transaction whatever its type byte.
+    pub fn introduced(&self) -> Hardfork {
+        if !self.blob_hashes.is_empty() {
+            return Hardfork::Cancun;
+        }
+        match self.tx_type {
+            ACCESS_LIST_TX_TYPE => Hardfork::Berlin,
+            DYNAMIC_FEE_TX_TYPE => Hardfork::London,
+            BLOB_TX_TYPE => Hardfork::Cancun,
+            _ => Hardfork::Frontier,
+        }
+    }
+
+    /// Whether the transaction creates a contract
+    pub fn is_create(&self) -> bool {
+        self.to.is_none()
+    }
+
+    /// Gas charged before any code runs: base cost, call data, access list and contract creation
+    pub fn intrinsic_gas(&self, costs: &GasCosts) -> u64 {
+        let zero_bytes = self.data.as_slice().iter().filter(|&&byte| byte == 0).count() as u64;
+        let non_zero_bytes = self.data.len() as u64 - zero_bytes;
+        let mut gas = costs.tx_base + zero_bytes * costs.tx_data_zero + non_zero_bytes * costs.tx_data_non_zero;
+        for item in &self.access_list {
+            gas += costs.tx_access_list_address + item.storage_keys.len() as u64 * costs.tx_access_list_storage_key;
+        }
+        if self.is_create() {
+            // EIP-3860 charges per word of init code
+            gas += costs.tx_create + self.data.len().div_ceil(32) as u64 * costs.initcode_word;
+        }
+        gas
+    }
+
+    /// Least gas the transaction is charged for its call data (EIP-7623)
+    ///
+    /// Each zero byte counts as one token and each other byte as four. The
+    /// floor is just the base cost before Prague.
+    pub fn floor_data_gas(&self, costs: &GasCosts) -> u64 {
+        let zero_bytes = self.data.as_slice().iter().filter(|&&byte| byte == 0).count() as u64;
+        let tokens = zero_bytes + (self.data.len() as u64 - zero_bytes) * 4;
+        costs.tx_base + tokens * costs.tx_data_floor_token
+    }
+
+    /// Blob gas consumed by the attached blobs (EIP-4844)
+    pub fn blob_gas(&self, costs: &GasCosts) -> u64 {
+        self.blob_hashes.len() as u64 * costs.blob_gas_per_blob
+    }
+
+    /// Price actually paid per unit of gas under the given base fee (EIP-1559)
+    pub fn effective_gas_price(&self, base_fee: &Uint256) -> Uint256 {
+        let tip = self.max_priority_fee_per_gas.min(self.max_fee_per_gas.saturating_sub(*base_fee));
+        *base_fee + tip
+    }
+}
+
+/// Outcome of an applied transaction
+#[derive(Debug, Clone)]
+pub struct TransactionResult {
+    /// How execution ended
+    pub outcome: ExecutionOutcome,
+    /// Gas charged to the sender, after refunds
+    pub gas_used: u64,
+    /// Gas refunded to the sender (capped at one fifth of the gas used, EIP-3529)
+    pub gas_refunded: u64,
+    /// Price paid per unit of gas
+    pub effective_gas_price: Uint256,
+    /// Return data (or revert data)
+    pub return_data: Bytes,
+    /// Logs emitted by a successful transaction
+    pub logs: Vec<EventLog>,
+    /// Address of the contract created, if any
+    pub contract_address: Option<Address>,
+}
+
+impl TransactionResult {
+    /// Whether the transaction succeeded
+    pub fn success(&self) -> bool {
+        self.outcome == ExecutionOutcome::Success
+    }
+}
+
+/// Outcome of running the message call or creation, before gas settlement
+struct MessageResult {
+    outcome: ExecutionOutcome,
+    gas_remaining: u64,
+    gas_refund: u64,
+    return_data: Bytes,
+    logs: Vec<EventLog>,
+    /// Accounts to delete once the transaction is finalized
+    selfdestructs: HashSet<Address>,
+}
+
+/// Applies transactions to a world state within a block
+pub struct TransactionProcessor<'a> {
+    storage: &'a mut Storage,
+    block: &'a BlockContext,
+    costs: GasCosts,
+    fork: Hardfork,
+}
+
+impl<'a> TransactionProcessor<'a> {
+    /// Create a processor over the given state and block
+    pub fn new(storage: &'a mut Storage, block: &'a BlockContext) -> Self {
+        TransactionProcessor {
+            storage,
+            block,
+            costs: GasCosts::default(),
+            fork: Hardfork::default(),
+        }
+    }
+
+    /// Apply transactions under the gas schedule and rules of `fork`
+    pub fn with_fork(mut self, fork: Hardfork) -> Self {
+        self.costs = GasCosts::for_fork(fork);
+        self.fork = fork;
+        self
+    }
+
+    /// Check that a transaction can be included, without changing any state
+    pub fn validate(&self, tx: &Transaction) -> Result<(), TransactionError> {
+        let introduced = tx.introduced();
+        if self.fork < introduced {
+            let tx_type = if tx.blob_hashes.is_empty() { tx.tx_type } else { BLOB_TX_TYPE };
+            return Err(TransactionError::TypeNotActivated { tx_type, introduced });
+        }
+        let intrinsic_gas = tx.intrinsic_gas(&self.costs).max(tx.floor_data_gas(&self.costs));
+        if tx.gas_limit < intrinsic_gas {
+            return Err(TransactionError::IntrinsicGasTooLow { required: intrinsic_gas, limit: tx.gas_limit });
+        }
+        if let Some(limit) = self.fork.max_initcode_size().filter(|&limit| tx.is_create() && tx.data.len() > limit) {
+            return Err(TransactionError::InitcodeTooLarge { size: tx.data.len(), limit });
+        }
+        if Uint256::from_u64(tx.gas_limit) > self.block.gas_limit {
+            return Err(TransactionError::GasLimitExceedsBlock {
+                gas_limit: tx.gas_limit,
+                block_gas_limit: self.block.gas_limit,
+            });
+        }
+        // There is no base fee to cover before London (EIP-1559)
+        if self.fork >= Hardfork::London && tx.max_fee_per_gas < self.block.base_fee {
+            return Err(TransactionError::FeeCapTooLow { max_fee: tx.max_fee_per_gas, base_fee: self.block.base_fee });
+        }
+        if !tx.blob_hashes.is_empty() && tx.max_fee_per_blob_gas < self.block.blob_base_fee {
+            return Err(TransactionError::BlobFeeCapTooLow {
+                max_fee: tx.max_fee_per_blob_gas,
+                blob_base_fee: self.block.blob_base_fee,
+            });
+        }
+        if tx.max_priority_fee_per_gas > tx.max_fee_per_gas {
+            return Err(TransactionError::PriorityFeeTooHigh {
+                priority_fee: tx.max_priority_fee_per_gas,
+                max_fee: tx.max_fee_per_gas,
+            });
+        }
+
+        let nonce = self.storage.get_nonce(&tx.sender);
+        if nonce != Uint256::from_u64(tx.nonce) {
+            return Err(TransactionError::NonceMismatch {
+                expected: nonce.to_u64_safe().unwrap_or(u64::MAX),
+                got: tx.nonce,
+            });
+        }
+        if tx.nonce == u64::MAX {
+            return Err(TransactionError::NonceOverflow);
+        }
+        if !self.storage.get_code(&tx.sender).is_empty() {
+            return Err(TransactionError::SenderNotEoa { sender: tx.sender });
+        }
+
+        // The sender must cover the full fee caps, not just the effective prices
+        let available = self.storage.get_balance(&tx.sender);
+        let max_blob_cost = Uint256::from_u64(tx.blob_gas(&self.costs)).checked_mul(tx.max_fee_per_blob_gas);
+        let required = Uint256::from_u64(tx.gas_limit)
+            .checked_mul(tx.max_fee_per_gas)
+            .zip(max_blob_cost)
+            .and_then(|(max_gas_cost, max_blob_cost)| max_gas_cost.checked_add(max_blob_cost))
+            .and_then(|max_cost| max_cost.checked_add(tx.value));
+        match required {
+            Some(required) if required <= available => Ok(()),
+            required => Err(TransactionError::InsufficientFunds { required: required.unwrap_or(Uint256::MAX), available }),
+        }
+    }
+
+    /// Validate and apply a transaction
+    ///
+    /// Invalid transactions return an error and leave the state untouched. Valid
+    /// ones always charge gas, even when execution reverts or halts.
+    pub fn execute(&mut self, tx: &Transaction) -> Result<TransactionResult, TransactionError> {
+        self.validate(tx)?;
+
+        // Internal failures must not leave a half-applied transaction behind
+        let checkpoint = self.storage.checkpoint();
+        let result = self.apply(tx);
+        match result {
+            Ok(_) => self.storage.commit(checkpoint),
+            Err(_) => self.storage.revert_to(checkpoint),
+        }
+        result
+    }
+
+    /// Recover the sender of a signed transaction and apply it
+    ///
+    /// The transaction must be replay-protected for this block's chain, or unprotected.
+    pub fn execute_signed(&mut self, envelope: &TxEnvelope) -> Result<TransactionResult, TransactionError> {
+        let chain_id = self.block.chain_id.to_u64_safe().unwrap_or(u64::MAX);
+        envelope.check_chain_id(chain_id)?;
+        let sender = envelope.recover_sender()?;
+        self.execute(&envelope.clone().into_transaction(sender))
+    }
+
+    /// Buy gas, run the message and settle fees for a validated transaction
+    fn apply(&mut self, tx: &Transaction) -> Result<TransactionResult, TransactionError> {
+        // Before London the gas price is paid as-is and nothing is burned
+        let (base_fee, effective_gas_price) = if self.fork >= Hardfork::London {
+            (self.block.base_fee, tx.effective_gas_price(&self.block.base_fee))
+        } else {
+            (Uint256::zero(), tx.max_fee_per_gas)
+        };
+        let gas_cost = Uint256::from_u64(tx.gas_limit) * effective_gas_price;
+        self.storage.sub_balance(&tx.sender, gas_cost)?;
+        // Blob gas is paid at the blob base fee and burned in full
+        let blob_cost = Uint256::from_u64(tx.blob_gas(&self.costs)) * self.block.blob_base_fee;
+        self.storage.sub_balance(&tx.sender, blob_cost)?;
+        self.storage.increment_nonce(tx.sender);
+
+        let gas = tx.gas_limit - tx.intrinsic_gas(&self.costs);
+        let mut tx_context = TransactionContext::with_values(
+            effective_gas_price,
+            tx.sender,
+            Uint256::from_u64(tx.gas_limit),
+            Uint256::from_bytes_be(tx.hash.as_bytes()),
+            Uint256::from_u64(tx.nonce),
+        );
+        tx_context.blob_hashes = tx.blob_hashes.clone();
+
+        // Access-listed addresses and slots start warm (EIP-2929, EIP-2930)
+        for item in &tx.access_list {
+            self.storage.warm_address(item.address);
+            for key in &item.storage_keys {
+                self.storage.warm_slot(item.address, Uint256::from_bytes_be(key.as_bytes()));
+            }
+        }
+
+        let contract_address = tx.to.is_none().then(|| create_address(&tx.sender, tx.nonce));
+        let message = match (tx.to, contract_address) {
+            (Some(to), _) => self.call(tx, to, gas, tx_context)?,
+            (None, Some(address)) => self.create(tx, address, gas, tx_context)?,
+            (None, None) => unreachable!("a creation always has a contract address"),
+        };
+
+        // Refunds are capped at a fifth of the gas used, half before London (EIP-3529)
+        let gas_used_before_refund = tx.gas_limit - message.gas_remaining;
+        let gas_refunded = message.gas_refund.min(gas_used_before_refund / self.costs.max_refund_quotient);
+        // Data-heavy transactions pay at least the calldata floor (EIP-7623)
+        let gas_used = (gas_used_before_refund - gas_refunded).max(tx.floor_data_gas(&self.costs));
+
+        // Unused gas goes back to the sender; only the tip reaches the coinbase,
+        // the base fee (if any) is burned
+        self.storage.add_balance(tx.sender, Uint256::from_u64(tx.gas_limit - gas_used) * effective_gas_price);
+        let priority_fee = effective_gas_price - base_fee;
+        let reward = Uint256::from_u64(gas_used) * priority_fee;
+        // A zero reward must not create an empty coinbase account (EIP-161)
+        if !reward.is_zero() || self.fork < Hardfork::SpuriousDragon {
+            self.storage.add_balance(self.block.coinbase, reward);
+        }
+        // Self-destructed accounts go last, after any code deposit and the refund
+        for address in &message.selfdestructs {
+            self.storage.delete_account(address);
+        }
+        // Calls straight to a precompile never reach the executor, which would reset these
+        self.storage.end_transaction();
+
+        let success = message.outcome == ExecutionOutcome::Success;
+        Ok(TransactionResult {
+            outcome: message.outcome,
+            gas_used,
+            gas_refunded,
+            effective_gas_price,
+            return_data: message.return_data,
+            logs: message.logs,
+            contract_address: contract_address.filter(|_| success),
+        })
+    }
+
+    /// Run a message call to `to`, which may be a precompile
+    fn call(
+        &mut self,
+        tx: &Transaction,
+        to: Address,
+        gas: u64,
+        tx_context: TransactionContext,
+    ) -> Result<MessageResult, TransactionError> {
+        let checkpoint = self.storage.checkpoint();
+        self.storage.transfer(&tx.sender, to, tx.value)?;
+
+        let message = if let Some(precompile) = Precompile::from_address_at(&to, self.fork) {
+            match precompile.execute(tx.data.as_slice(), gas, self.fork) {
+                Ok(output) => MessageResult {
+                    outcome: ExecutionOutcome::Success,
+                    gas_remaining: gas - output.gas_used,
+                    gas_refund: 0,
+                    return_data: Bytes::new(output.output),
+                    logs: Vec::new(),
+                    selfdestructs: HashSet::new(),
+                },
+                Err(e) => {
+                    log::debug!("Precompile {:?} failed: {}", precompile, e);
+                    Self::halted(HaltReason::PrecompileFailure)
+                }
+            }
+        } else {
+            let code = Bytes::new(self.storage.get_code(&to));
+            let context = ExecutionContext::new(to, tx.sender, tx.value, tx.data.clone(), code, gas);
+            self.run(context, tx_context)?
+        };
+
+        if message.outcome == ExecutionOutcome::Success {
+            self.storage.commit(checkpoint);
+        } else {
+            self.storage.revert_to(checkpoint);
+        }
+        Ok(message)
+    }
+
+    /// Run init code and deploy the returned runtime code at `address`
+    fn create(
+        &mut self,
+        tx: &Transaction,
+        address: Address,
+        gas: u64,
+        tx_context: TransactionContext,
+    ) -> Result<MessageResult, TransactionError> {
+        if create_collision(self.storage, &address) {
+            return Ok(Self::halted(HaltReason::CreateCollision));
+        }
+
+        // New contracts start with nonce 1 from Spurious Dragon (EIP-161)
+        let checkpoint = self.storage.checkpoint();
+        self.storage.set_nonce(address, Uint256::from_u64(self.fork.initial_contract_nonce()));
+        self.storage.transfer(&tx.sender, address, tx.value)?;
+
+        let mut context = ExecutionContext::new(address, tx.sender, tx.value, Bytes::empty(), tx.data.clone(), gas);
+        context.substate.created_contracts.insert(address);
+        let mut message = self.run(context, tx_context)?;
+
+        if message.outcome == ExecutionOutcome::Success {
+            let code = message.return_data.as_slice();
+            match code_deposit_cost(code, &self.costs, self.fork) {
+                Ok(cost) if cost <= message.gas_remaining => {
+                    self.storage.set_code(address, code.to_vec());
+                    message.gas_remaining -= cost;
+                    message.return_data = Bytes::empty();
+                }
+                // Frontier deploys empty code instead of failing the creation
+                Ok(_) if self.fork < Hardfork::Homestead => message.return_data = Bytes::empty(),
+                Ok(_) => message = Self::halted(HaltReason::OutOfGas),
+                Err(reason) => message = Self::halted(reason),
+            }
+        }
+
+        if message.outcome == ExecutionOutcome::Success {
+            self.storage.commit(checkpoint);
+        } else {
+            self.storage.revert_to(checkpoint);
+        }
+        Ok(message)
+    }
+
+    /// Execute a top-level frame against the processor's state
+    fn run(
+        &mut self,
+        mut context: ExecutionContext,
+        tx_context: TransactionContext,
+    ) -> Result<MessageResult, TransactionError> {
+        context.set_fork(self.fork);
+        context.storage = std::mem::take(self.storage);
+        context.block_context = self.block.clone();
+        context.transaction_context = tx_context;
+
+        let mut executor = Executor::new(context);
+        let result = executor.execute();
+        let mut context = executor.into_context();
+        *self.storage = std::mem::take(&mut context.storage);
+        let result = result?;
+
+        Ok(MessageResult {
+            outcome: result.outcome,
+            gas_remaining: result.gas_remaining,
+            gas_refund: result.gas_refund,
+            return_data: result.return_data,
+            logs: if result.success { result.logs } else { Vec::new() },
+            selfdestructs: if result.success { context.substate.selfdestructs } else { HashSet::new() },
+        })
+    }
+
+    /// A failed message that consumed all of its gas
+    fn halted(reason: HaltReason) -> MessageResult {
+        MessageResult {
+            outcome: ExecutionOutcome::Halt(reason),
+            gas_remaining: 0,
+            gas_refund: 0,
+            return_data: Bytes::empty(),
+            logs: Vec::new(),
+            selfdestructs: HashSet::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::MAX_INITCODE_SIZE;
+
+    const ETHER: u64 = 1_000_000_000_000_000_000;
+    const GWEI: u64 = 1_000_000_000;
+
+    fn sender() -> Address {
+        Address::from_hex("0x1000000000000000000000000000000000000001").unwrap()
+    }
+
+    fn recipient() -> Address {
+        Address::from_hex("0x2000000000000000000000000000000000000002").unwrap()
+    }
+
+    fn test_block() -> BlockContext {
+        let mut block = BlockContext::new();
+        block.coinbase = Address::from_hex("0xc000000000000000000000000000000000000000").unwrap();
+        block.base_fee = Uint256::from_u64(10 * GWEI);
+        block
+    }
+
+    fn funded_storage() -> Storage {
+        let mut storage = Storage::new();
+        storage.set_balance(sender(), Uint256::from_u64(ETHER));
+        storage
+    }
+
+    /// EIP-1559 transfer with a 20 gwei fee cap and a 1 gwei tip
+    fn transfer(value: u64) -> Transaction {
+        let mut tx = legacy_transfer(value);
+        tx.tx_type = DYNAMIC_FEE_TX_TYPE;
+        tx.max_priority_fee_per_gas = Uint256::from_u64(GWEI);
+        tx
+    }
+
+    /// Legacy transfer at a 20 gwei gas price
+    fn legacy_transfer(value: u64) -> Transaction {
+        Transaction::new(sender(), Some(recipient()), Uint256::from_u64(value), Bytes::empty())
+    }
+
+    #[test]
+    fn test_value_transfer_pays_tip_and_burns_base_fee() {
+        let mut storage = funded_storage();
+        let block = test_block();
+        let result = TransactionProcessor::new(&mut storage, &block).execute(&transfer(1000)).unwrap();
+
+        assert!(result.success());
+        assert_eq!(result.gas_used, 21000);
+        assert_eq!(result.effective_gas_price, Uint256::from_u64(11 * GWEI));
+        assert_eq!(storage.get_balance(&recipient()), Uint256::from_u64(1000));
+        assert_eq!(storage.get_balance(&sender()), Uint256::from_u64(ETHER - 1000 - 21000 * 11 * GWEI));
+        assert_eq!(storage.get_balance(&block.coinbase), Uint256::from_u64(21000 * GWEI));
+        assert_eq!(storage.get_nonce(&sender()), Uint256::one());
+    }
+
+    #[test]
+    fn test_prague_calldata_floor() {
+        let block = test_block();
+        let mut tx = transfer(0);
+        tx.data = Bytes::new(vec![0xff; 1000]);
+
+        // Cancun charges the standard 16 gas per non-zero byte
+        let mut storage = funded_storage();
+        let result = TransactionProcessor::new(&mut storage, &block).execute(&tx).unwrap();
+        assert_eq!(result.gas_used, 21000 + 16 * 1000);
+
+        // Prague charges at least 10 gas per token, four tokens per non-zero byte
+        let mut storage = funded_storage();
+        let result = TransactionProcessor::new(&mut storage, &block)
+            .with_fork(Hardfork::Prague)
+            .execute(&tx)
+            .unwrap();
+        assert_eq!(result.gas_used, 21000 + 40 * 1000);
+
+        // The gas limit must cover the floor up front
+        tx.gas_limit = 21000 + 40 * 1000 - 1;
+        let mut storage = funded_storage();
+        assert!(matches!(
+            TransactionProcessor::new(&mut storage, &block).with_fork(Hardfork::Prague).execute(&tx),
+            Err(TransactionError::IntrinsicGasTooLow { required: 61000, .. })
+        ));
+    }
+
+    #[test]
+    fn test_pre_london_pays_gas_price_to_coinbase() {
+        let mut storage = funded_storage();
+        let mut block = test_block();
+        block.base_fee = Uint256::from_u64(30 * GWEI);
+        let result = TransactionProcessor::new(&mut storage, &block)
+            .with_fork(Hardfork::Berlin)
+            .execute(&legacy_transfer(1000))
+            .unwrap();
+
+        // The 20 gwei gas price is below the base fee, which Berlin ignores
+        assert_eq!(result.effective_gas_price, Uint256::from_u64(20 * GWEI));
+        assert_eq!(storage.get_balance(&sender()), Uint256::from_u64(ETHER - 1000 - 21000 * 20 * GWEI));
+        assert_eq!(storage.get_balance(&block.coinbase), Uint256::from_u64(21000 * 20 * GWEI));
+    }
+
+    #[test]
+    fn test_legacy_gas_price_tips_the_coinbase() {
+        let mut storage = funded_storage();
+        let block = test_block();
+        let result = TransactionProcessor::new(&mut storage, &block).execute(&legacy_transfer(0)).unwrap();
+
+        // The whole 20 gwei gas price is paid; the 10 gwei above the base fee is the tip
+        assert_eq!(result.effective_gas_price, Uint256::from_u64(20 * GWEI));
+        assert_eq!(storage.get_balance(&block.coinbase), Uint256::from_u64(21000 * 10 * GWEI));
+    }
+
+    #[test]
+    fn test_zero_tip_leaves_coinbase_untouched() {
+        let mut storage = funded_storage();
+        let block = test_block();
+        let mut tx = transfer(0);
+        tx.max_priority_fee_per_gas = Uint256::zero();
+        let result = TransactionProcessor::new(&mut storage, &block).execute(&tx).unwrap();
+
+        assert!(result.success());
+        assert!(!storage.account_exists(&block.coinbase));
+    }
+
+    #[test]
+    fn test_invalid_transactions_leave_state_untouched() {
+        let mut storage = funded_storage();
+        let block = test_block();
+        let mut processor = TransactionProcessor::new(&mut storage, &block);
+
+        let mut wrong_nonce = transfer(1);
+        wrong_nonce.nonce = 3;
+        assert!(matches!(
+            processor.execute(&wrong_nonce),
+            Err(TransactionError::NonceMismatch { expected: 0, got: 3 })
+        ));
+        assert!(matches!(processor.execute(&transfer(ETHER)), Err(TransactionError::InsufficientFunds { .. })));
+
+        let mut low_gas = transfer(1);
+        low_gas.gas_limit = 20_999;
+        assert!(matches!(processor.execute(&low_gas), Err(TransactionError::IntrinsicGasTooLow { .. })));
+
+        assert_eq!(storage.get_balance(&sender()), Uint256::from_u64(ETHER));
+        assert_eq!(storage.get_nonce(&sender()), Uint256::zero());
+    }
+
+    #[test]
+    fn test_contract_creation_deploys_runtime_code() {
+        let mut storage = funded_storage();
+        let block = test_block();
+        // PUSH1 0x2a PUSH1 0 MSTORE8 PUSH1 1 PUSH1 0 RETURN
+        let init_code = vec![0x60, 0x2a, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xf3];
+        let tx = Transaction::new(sender(), None, Uint256::from_u64(7), Bytes::new(init_code));
+        let result = TransactionProcessor::new(&mut storage, &block).execute(&tx).unwrap();
+
+        let address = create_address(&sender(), 0);
+        assert!(result.success());
+        assert_eq!(result.contract_address, Some(address));
+        assert_eq!(storage.get_code(&address), vec![0x2a]);
+        assert_eq!(storage.get_nonce(&address), Uint256::one());
+        assert_eq!(storage.get_balance(&address), Uint256::from_u64(7));
+        assert_eq!(storage.get_nonce(&sender()), Uint256::one());
+    }
+
+    #[test]
+    fn test_selfdestructing_init_code_leaves_no_account() {
+        let mut storage = funded_storage();
+        let block = test_block();
+        // PUSH1 0xcc SELFDESTRUCT
+        let init_code = vec![0x60, 0xcc, 0xff];
+        let tx = Transaction::new(sender(), None, Uint256::from_u64(1000), Bytes::new(init_code));
+        let result = TransactionProcessor::new(&mut storage, &block).execute(&tx).unwrap();
+
+        let address = create_address(&sender(), 0);
+        let beneficiary = Address::from_hex("0x00000000000000000000000000000000000000cc").unwrap();
+        assert!(result.success());
+        assert!(!storage.account_exists(&address));
+        assert_eq!(storage.get_balance(&beneficiary), Uint256::from_u64(1000));
+    }
+
+    #[test]
+    fn test_contract_creation_before_spurious_dragon_starts_at_nonce_zero() {
+        let mut storage = funded_storage();
+        let block = test_block();
+        // PUSH1 0x2a PUSH1 0 MSTORE8 PUSH1 1 PUSH1 0 RETURN
+        let init_code = vec![0x60, 0x2a, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xf3];
+        let tx = Transaction::new(sender(), None, Uint256::zero(), Bytes::new(init_code));
+        let result = TransactionProcessor::new(&mut storage, &block)
+            .with_fork(Hardfork::Homestead)
+            .execute(&tx)
+            .unwrap();
+
+        let address = create_address(&sender(), 0);
+        assert!(result.success());
+        assert_eq!(storage.get_code(&address), vec![0x2a]);
+        assert_eq!(storage.get_nonce(&address), Uint256::zero());
+    }
+
+    #[test]
+    fn test_blockhash_reads_history_under_prague() {
+        let mut storage = funded_storage();
+        // PUSH1 9 BLOCKHASH PUSH1 0 SSTORE STOP
+        storage.set_code(recipient(), vec![0x60, 0x09, 0x40, 0x60, 0x00, 0x55, 0x00]);
+        let mut block = test_block();
+        block.number = Uint256::from_u64(10);
+        block.block_hashes.insert(9, Hash::new([0x99; 32]));
+        let result = TransactionProcessor::new(&mut storage, &block)
+            .with_fork(Hardfork::Prague)
+            .execute(&transfer(0))
+            .unwrap();
+
+        assert!(result.success());
+        assert_eq!(storage.get_storage(&recipient(), &Uint256::zero()), Uint256::from_bytes_be(&[0x99; 32]));
+    }
+
+    #[test]
+    fn test_create_collides_with_storage() {
+        let mut storage = funded_storage();
+        let block = test_block();
+        let address = create_address(&sender(), 0);
+        storage.set_storage(address, Uint256::one(), Uint256::one());
+        let tx = Transaction::new(sender(), None, Uint256::zero(), Bytes::new(vec![0x00]));
+        let result = TransactionProcessor::new(&mut storage, &block).execute(&tx).unwrap();
+
+        assert_eq!(result.outcome, ExecutionOutcome::Halt(HaltReason::CreateCollision));
+        assert_eq!(result.gas_used, tx.gas_limit);
+        assert_eq!(storage.get_nonce(&address), Uint256::zero());
+    }
+
+    #[test]
+    fn test_code_deposit_out_of_gas_before_homestead() {
+        let block = test_block();
+        // PUSH1 0x20 PUSH1 0 RETURN: 32 bytes of code, 6400 gas to deposit
+        let init_code = vec![0x60, 0x20, 0x60, 0x00, 0xf3];
+        let address = create_address(&sender(), 0);
+        for fork in [Hardfork::Frontier, Hardfork::Homestead] {
+            let mut storage = funded_storage();
+            let mut tx = Transaction::new(sender(), None, Uint256::zero(), Bytes::new(init_code.clone()));
+            tx.gas_limit = tx.intrinsic_gas(&GasCosts::for_fork(fork)) + 1000;
+            let result = TransactionProcessor::new(&mut storage, &block).with_fork(fork).execute(&tx).unwrap();
+
+            // Frontier keeps the account with empty code; Homestead fails the creation
+            assert_eq!(result.success(), fork == Hardfork::Frontier);
+            assert_eq!(storage.account_exists(&address), fork == Hardfork::Frontier);
+            assert!(storage.get_code(&address).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_create_transaction_initcode_limit() {
+        let mut storage = funded_storage();
+        let block = test_block();
+        let tx = Transaction::new(sender(), None, Uint256::zero(), Bytes::new(vec![0; MAX_INITCODE_SIZE + 1]));
+
+        assert!(matches!(
+            TransactionProcessor::new(&mut storage, &block).execute(&tx),
+            Err(TransactionError::InitcodeTooLarge { size, limit: MAX_INITCODE_SIZE }) if size == MAX_INITCODE_SIZE + 1
+        ));
+        assert_eq!(storage.get_nonce(&sender()), Uint256::zero());
+
+        let result = TransactionProcessor::new(&mut storage, &block).with_fork(Hardfork::Paris).execute(&tx).unwrap();
+        assert!(result.success());
+    }
+
+    #[test]
+    fn test_reverted_call_charges_gas_but_keeps_value() {
+        let mut storage = funded_storage();
+        // PUSH1 0 PUSH1 0 REVERT
+        storage.set_code(recipient(), vec![0x60, 0x00, 0x60, 0x00, 0xfd]);
+        let block = test_block();
+        let result = TransactionProcessor::new(&mut storage, &block).execute(&transfer(500)).unwrap();
+
+        assert!(matches!(result.outcome, ExecutionOutcome::Revert(_)));
+        assert!(result.gas_used > 21000);
+        assert_eq!(storage.get_balance(&recipient()), Uint256::zero());
+        assert_eq!(
+            storage.get_balance(&sender()),
+            Uint256::from_u64(ETHER) - Uint256::from_u64(result.gas_used) * result.effective_gas_price
+        );
+        assert_eq!(storage.get_nonce(&sender()), Uint256::one());
+    }
+
+    #[test]
+    fn test_storage_clear_refund_is_capped() {
+        let mut storage = funded_storage();
+        // PUSH1 0 PUSH1 0 SSTORE
+        storage.set_code(recipient(), vec![0x60, 0x00, 0x60, 0x00, 0x55]);
+        storage.set_storage(recipient(), Uint256::zero(), Uint256::one());
+        let block = test_block();
+        let result = TransactionProcessor::new(&mut storage, &block).execute(&transfer(0)).unwrap();
+
+        let gas_used_before_refund = result.gas_used + result.gas_refunded;
+        assert!(result.success());
+        assert!(result.gas_refunded > 0);
+        assert_eq!(result.gas_refunded, 4800.min(gas_used_before_refund / 5));
+        assert_eq!(storage.get_storage(&recipient(), &Uint256::zero()), Uint256::zero());
+    }
+
+    #[test]
+    fn test_restored_slot_refund_is_capped() {
+        let mut storage = funded_storage();
+        // PUSH1 1 PUSH1 0 SSTORE PUSH1 0 PUSH1 0 SSTORE STOP
+        storage.set_code(recipient(), vec![0x60, 0x01, 0x60, 0x00, 0x55, 0x60, 0x00, 0x60, 0x00, 0x55, 0x00]);
+        let block = test_block();
+        let result = TransactionProcessor::new(&mut storage, &block).execute(&transfer(0)).unwrap();
+
+        // Cold set from zero, then a dirty write restoring the original value
+        let gas_used_before_refund = 21000 + 6 + 2100 + 20000 + 6 + 100 + 2;
+        assert_eq!(result.gas_refunded, gas_used_before_refund / 5);
+        assert_eq!(result.gas_used, gas_used_before_refund - gas_used_before_refund / 5);
+        assert!(storage.get_storage(&recipient(), &Uint256::zero()).is_zero());
+    }
+
+    #[test]
+    fn test_refund_rules_follow_fork() {
+        let mut storage = funded_storage();
+        // PUSH1 1 PUSH1 0 SSTORE PUSH1 0 PUSH1 0 SSTORE STOP
+        storage.set_code(recipient(), vec![0x60, 0x01, 0x60, 0x00, 0x55, 0x60, 0x00, 0x60, 0x00, 0x55, 0x00]);
+        let block = test_block();
+        let result = TransactionProcessor::new(&mut storage, &block)
+            .with_fork(Hardfork::Istanbul)
+            .execute(&legacy_transfer(0))
+            .unwrap();
+
+        // No cold surcharge, a dirty write at the SLOAD price, and refunds capped at half
+        let gas_used_before_refund = 21000 + 6 + 20000 + 6 + 800 + 2;
+        assert_eq!(result.gas_refunded, 20000 - 800);
+        assert!(result.gas_refunded <= gas_used_before_refund / 2);
+        assert_eq!(result.gas_used, gas_used_before_refund - result.gas_refunded);
+    }
+
+    #[test]
+    fn test_access_list_and_blob_costs() {
+        let mut storage = funded_storage();
+        let mut block = test_block();
+        block.blob_base_fee = Uint256::from_u64(3);
+        let mut tx = transfer(0);
+        tx.tx_type = BLOB_TX_TYPE;
+        tx.access_list = vec![AccessListItem { address: recipient(), storage_keys: vec![Hash::zero(); 2] }];
+        tx.blob_hashes = vec![Hash::new([0x01; 32])];
+        tx.max_fee_per_blob_gas = Uint256::from_u64(2);
+
+        let mut processor = TransactionProcessor::new(&mut storage, &block);
+        assert!(matches!(processor.execute(&tx), Err(TransactionError::BlobFeeCapTooLow { .. })));
+
+        tx.max_fee_per_blob_gas = Uint256::from_u64(5);
+        let result = processor.execute(&tx).unwrap();
+        assert_eq!(result.gas_used, 21000 + 2400 + 2 * 1900);
+        let gas_cost = result.gas_used * 11 * GWEI;
+        assert_eq!(storage.get_balance(&sender()), Uint256::from_u64(ETHER - gas_cost - 131072 * 3));
+    }
+
+    #[test]
+    fn test_typed_transactions_follow_fork() {
+        let mut storage = funded_storage();
+        let block = test_block();
+        let mut blob = transfer(0);
+        blob.tx_type = BLOB_TX_TYPE;
+        blob.blob_hashes = vec![Hash::new([0x01; 32])];
+        blob.max_fee_per_blob_gas = Uint256::from_u64(5);
+        assert!(matches!(
+            TransactionProcessor::new(&mut storage, &block).with_fork(Hardfork::Shanghai).execute(&blob),
+            Err(TransactionError::TypeNotActivated { tx_type: BLOB_TX_TYPE, introduced: Hardfork::Cancun })
+        ));
+
+        let dynamic_fee = transfer(0);
+        assert!(matches!(
+            TransactionProcessor::new(&mut storage, &block).with_fork(Hardfork::Berlin).execute(&dynamic_fee),
+            Err(TransactionError::TypeNotActivated { tx_type: DYNAMIC_FEE_TX_TYPE, introduced: Hardfork::London })
+        ));
+        assert!(TransactionProcessor::new(&mut storage, &block).with_fork(Hardfork::London).execute(&dynamic_fee).is_ok());
+    }
+
+    #[test]
+    fn test_access_list_warms_slots() {
+        // PUSH1 1 SLOAD STOP
+        let code = vec![0x60, 0x01, 0x54, 0x00];
+        let block = test_block();
+
+        let mut storage = funded_storage();
+        storage.set_code(recipient(), code.clone());
+        let cold = TransactionProcessor::new(&mut storage, &block).execute(&transfer(0)).unwrap();
+        assert_eq!(cold.gas_used, 21000 + 3 + 2100 + 2);
+
+        let mut storage = funded_storage();
+        storage.set_code(recipient(), code);
+        let mut tx = transfer(0);
+        let mut key = [0u8; 32];
+        key[31] = 1;
+        tx.access_list = vec![AccessListItem { address: recipient(), storage_keys: vec![Hash::new(key)] }];
+        let warm = TransactionProcessor::new(&mut storage, &block).execute(&tx).unwrap();
+        assert_eq!(warm.gas_used, 21000 + 2400 + 1900 + 3 + 100 + 2);
+        assert!(!storage.is_warm_slot(&recipient(), &Uint256::one()));
+    }
+
+    #[test]
+    fn test_execute_signed_recovers_sender() {
+        // The EIP-155 example: nonce 9, 1 ether to 0x3535..35 at 20 gwei, chain id 1
+        let raw = hex::decode("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83").unwrap();
+        let envelope = TxEnvelope::decode(&raw).unwrap();
+        let signer = Address::from_hex("0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f").unwrap();
+        let to = Address::from_hex("0x3535353535353535353535353535353535353535").unwrap();
+
+        let mut storage = Storage::new();
+        storage.set_balance(signer, Uint256::from_u64(2 * ETHER));
+        storage.set_nonce(signer, Uint256::from_u64(9));
+
+        let mut other_chain = test_block();
+        other_chain.chain_id = Uint256::from_u64(5);
+        assert!(matches!(
+            TransactionProcessor::new(&mut storage, &other_chain).execute_signed(&envelope),
+            Err(TransactionError::Signature(SignatureError::ChainIdMismatch { expected: 5, got: 1 }))
+        ));
+
+        let block = test_block();
+        let result = TransactionProcessor::new(&mut storage, &block).execute_signed(&envelope).unwrap();
+        assert!(result.success());
+        assert_eq!(storage.get_balance(&to), Uint256::from_u64(ETHER));
+        assert_eq!(storage.get_nonce(&signer), Uint256::from_u64(10));
+    }
+
+    #[test]
+    fn test_top_level_precompile_call() {
+        let mut storage = funded_storage();
+        let block = test_block();
+        let identity = Address::from_hex("0x0000000000000000000000000000000000000004").unwrap();
+        let tx = Transaction::new(sender(), Some(identity), Uint256::zero(), Bytes::new(vec![1, 2, 3]));
+        let result = TransactionProcessor::new(&mut storage, &block).execute(&tx).unwrap();
+
+        assert!(result.success());
+        assert_eq!(result.return_data.as_slice(), &[1, 2, 3]);
+        // Intrinsic 21000 + 3 non-zero bytes, plus 15 + 3 per word for the precompile
+        assert_eq!(result.gas_used, 21000 + 3 * 16 + 18);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::types::{Address, Hash, Uint256};
+use super::envelope::{Signature, TxEnvelope};
+use secp256k1::{
+    ecdsa::{RecoverableSignature, RecoveryId},
+    Message, Secp256k1,
+};
+use sha3::{Digest, Keccak256};
+use thiserror::Error;
+
+/// Half the order of the secp256k1 curve; larger `s` values are malleable (EIP-2)
+const SECP256K1N_HALF: Uint256 =
+    Uint256::from_limbs([0xdfe92f46681b20a0, 0x5d576e7357a4501d, 0xffffffffffffffff, 0x7fffffffffffffff]);
+
+/// Reasons a transaction signature is rejected
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    #[error("Invalid signature v value {0}")]
+    InvalidV(u64),
+    #[error("Signature r and s must be non-zero")]
+    ZeroComponent,
+    #[error("Signature s value is in the upper half of the curve order")]
+    HighS,
+    #[error("Chain id mismatch: expected {expected}, got {got}")]
+    ChainIdMismatch { expected: u64, got: u64 },
+    #[error("Signature does not recover to a public key")]
+    RecoveryFailed,
+}
+
+impl TxEnvelope {
+    /// Recover the sender address from the signature
+    pub fn recover_sender(&self) -> Result<Address, SignatureError> {
+        let Signature { v, r, s } = *self.signature();
+        let y_parity = match self {
+            TxEnvelope::Legacy(tx) => match (v, tx.chain_id()) {
+                (27 | 28, None) => v - 27,
+                (_, Some(chain_id)) => v - 35 - chain_id * 2,
+                _ => return Err(SignatureError::InvalidV(v)),
+            },
+            _ if v <= 1 => v,
+            _ => return Err(SignatureError::InvalidV(v)),
+        };
+        if r.is_zero() || s.is_zero() {
+            return Err(SignatureError::ZeroComponent);
+        }
+        if s > SECP256K1N_HALF {
+            return Err(SignatureError::HighS);
+        }
+        recover_address(&self.signing_hash(), y_parity, &r, &s)
+    }
+
+    /// Check that the transaction may be included on chain `chain_id`
+    ///
+    /// Legacy transactions without EIP-155 protection are valid on every chain.
+    pub fn check_chain_id(&self, chain_id: u64) -> Result<(), SignatureError> {
+        match self.chain_id() {
+            Some(got) if got != chain_id => Err(SignatureError::ChainIdMismatch { expected: chain_id, got }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Recover the address whose key produced the signature `(y_parity, r, s)` over `hash`
+fn recover_address(hash: &Hash, y_parity: u64, r: &Uint256, s: &Uint256) -> Result<Address, SignatureError> {
+    let mut compact = [0u8; 64];
+    compact[..32].copy_from_slice(&r.to_bytes_be());
+    compact[32..].copy_from_slice(&s.to_bytes_be());
+
+    let public_key = RecoveryId::from_i32(y_parity as i32)
+        .and_then(|id| RecoverableSignature::from_compact(&compact, id))
+        .and_then(|signature| {
+            let message = Message::from_digest_slice(hash.as_bytes())?;
+            Secp256k1::verification_only().recover_ecdsa(&message, &signature)
+        })
+        .map_err(|_| SignatureError::RecoveryFailed)?;
+
+    let hash = Keccak256::digest(&public_key.serialize_uncompressed()[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    Ok(Address::new(address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::envelope::DynamicFeeTransaction;
+    use crate::types::Bytes;
+    use secp256k1::SecretKey;
+
+    /// The signed example transaction from EIP-155, signed with key 0x4646..46
+    const EIP155_EXAMPLE: &str = "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+
+    fn sign(envelope: &mut TxEnvelope, key: &SecretKey) {
+        let message = Message::from_digest_slice(envelope.signing_hash().as_bytes()).unwrap();
+        let (recovery_id, compact) = Secp256k1::new().sign_ecdsa_recoverable(&message, key).serialize_compact();
+        let signature = Signature {
+            v: recovery_id.to_i32() as u64,
+            r: Uint256::from_bytes_be(&compact[..32]),
+            s: Uint256::from_bytes_be(&compact[32..]),
+        };
+        if let TxEnvelope::DynamicFee(tx) = envelope {
+            tx.signature = signature;
+        }
+    }
+
+    fn dynamic_fee_tx() -> TxEnvelope {
+        TxEnvelope::DynamicFee(DynamicFeeTransaction {
+            chain_id: 1,
+            nonce: 0,
+            max_priority_fee_per_gas: Uint256::one(),
+            max_fee_per_gas: Uint256::from_u64(100),
+            gas_limit: 21_000,
+            to: Some(Address::from_hex("0x00000000000000000000000000000000000000bb").unwrap()),
+            value: Uint256::from_u64(5),
+            data: Bytes::empty(),
+            access_list: Vec::new(),
+            signature: Signature::default(),
+        })
+    }
+
+    #[test]
+    fn test_recover_eip155_example() {
+        let envelope = TxEnvelope::decode(&hex::decode(EIP155_EXAMPLE).unwrap()).unwrap();
+        assert_eq!(
+            envelope.signing_hash().to_hex(),
+            "0xdaf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+        );
+        assert_eq!(
+            envelope.recover_sender().unwrap(),
+            Address::from_hex("0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f").unwrap()
+        );
+        assert_eq!(envelope.check_chain_id(1), Ok(()));
+        assert_eq!(envelope.check_chain_id(5), Err(SignatureError::ChainIdMismatch { expected: 5, got: 1 }));
+    }
+
+    #[test]
+    fn test_recover_typed_transaction() {
+        let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+        let expected = {
+            let public_key = key.public_key(&Secp256k1::new());
+            let hash = Keccak256::digest(&public_key.serialize_uncompressed()[1..]);
+            Address::new(hash[12..].try_into().unwrap())
+        };
+
+        let mut envelope = dynamic_fee_tx();
+        sign(&mut envelope, &key);
+        assert_eq!(envelope.recover_sender().unwrap(), expected);
+
+        // Any change to the signed fields changes the recovered address
+        let decoded = TxEnvelope::decode(&envelope.encode()).unwrap();
+        assert_eq!(decoded.recover_sender().unwrap(), expected);
+        if let TxEnvelope::DynamicFee(mut tx) = decoded {
+            tx.value = Uint256::from_u64(6);
+            assert_ne!(TxEnvelope::DynamicFee(tx).recover_sender(), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn test_invalid_signatures() {
+        let key = SecretKey::from_slice(&[0x22; 32]).unwrap();
+        let mut envelope = dynamic_fee_tx();
+        sign(&mut envelope, &key);
+        let TxEnvelope::DynamicFee(signed) = envelope else { unreachable!() };
+
+        let mut high_s = signed.clone();
+        high_s.signature.s = Uint256::from_limbs([0xbfd25e8cd0364141, 0xbaaedce6af48a03b, u64::MAX - 1, u64::MAX])
+            - signed.signature.s;
+        high_s.signature.v ^= 1;
+        assert_eq!(TxEnvelope::DynamicFee(high_s).recover_sender(), Err(SignatureError::HighS));
+
+        let mut bad_v = signed.clone();
+        bad_v.signature.v = 27;
+        assert_eq!(TxEnvelope::DynamicFee(bad_v).recover_sender(), Err(SignatureError::InvalidV(27)));
+
+        let mut zero_r = signed;
+        zero_r.signature.r = Uint256::zero();
+        assert_eq!(TxEnvelope::DynamicFee(zero_r).recover_sender(), Err(SignatureError::ZeroComponent));
+    }
+}
@@ -0,0 +1,394 @@
+use crate::{
+    types::Hash,
+    rlp::{self, Header},
+};
+use sha3::{Digest, Keccak256};
+
+/// Root hash of an empty trie: keccak256(rlp(""))
+pub const EMPTY_ROOT: Hash = Hash([
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+]);
+
+/// Root of a trie keyed by `rlp(index)`, as used for the transactions and receipts roots
+pub fn ordered_trie_root<I>(values: I) -> Hash
+where
+    I: IntoIterator<Item = Vec<u8>>,
+{
+    let mut trie = Trie::new();
+    for (index, value) in values.into_iter().enumerate() {
+        trie.insert(&rlp::encode(&(index as u64)), value);
+    }
+    trie.root_hash()
+}
+
+/// A trie node; `cache` holds the node's reference once computed and is
+/// cleared on every node along the path of an update
+#[derive(Debug, Clone, Default)]
+enum Node {
+    #[default]
+    Empty,
+    Leaf {
+        path: Vec<u8>,
+        value: Vec<u8>,
+        cache: Option<Vec<u8>>,
+    },
+    Extension {
+        path: Vec<u8>,
+        child: Box<Node>,
+        cache: Option<Vec<u8>>,
+    },
+    Branch {
+        children: Box<[Node; 16]>,
+        value: Option<Vec<u8>>,
+        cache: Option<Vec<u8>>,
+    },
+}
+
+impl Node {
+    fn leaf(path: &[u8], value: Vec<u8>) -> Self {
+        Node::Leaf { path: path.to_vec(), value, cache: None }
+    }
+
+    fn empty_branch() -> (Box<[Node; 16]>, Option<Vec<u8>>) {
+        (Box::default(), None)
+    }
+
+    /// Prefix `node` with `prefix`, merging it into a leaf or extension where possible
+    fn with_prefix(prefix: &[u8], node: Node) -> Node {
+        if prefix.is_empty() {
+            return node;
+        }
+        match node {
+            Node::Empty => Node::Empty,
+            Node::Leaf { path, value, .. } => Node::Leaf { path: [prefix, &path].concat(), value, cache: None },
+            Node::Extension { path, child, .. } => {
+                Node::Extension { path: [prefix, &path].concat(), child, cache: None }
+            }
+            branch => Node::Extension { path: prefix.to_vec(), child: Box::new(branch), cache: None },
+        }
+    }
+
+    fn get(&self, path: &[u8]) -> Option<&[u8]> {
+        match self {
+            Node::Empty => None,
+            Node::Leaf { path: leaf_path, value, .. } => (leaf_path == path).then_some(value.as_slice()),
+            Node::Extension { path: prefix, child, .. } => {
+                path.strip_prefix(prefix.as_slice()).and_then(|rest| child.get(rest))
+            }
+            Node::Branch { children, value, .. } => match path.split_first() {
+                None => value.as_deref(),
+                Some((&nibble, rest)) => children[nibble as usize].get(rest),
+            },
+        }
+    }
+
+    fn insert(self, path: &[u8], new_value: Vec<u8>) -> Node {
+        match self {
+            Node::Empty => Node::leaf(path, new_value),
+            Node::Leaf { path: leaf_path, value, .. } => {
+                let common = common_prefix(&leaf_path, path);
+                if common == leaf_path.len() && common == path.len() {
+                    return Node::leaf(path, new_value);
+                }
+                let (mut children, mut branch_value) = Node::empty_branch();
+                match leaf_path.get(common) {
+                    Some(&nibble) => children[nibble as usize] = Node::leaf(&leaf_path[common + 1..], value),
+                    None => branch_value = Some(value),
+                }
+                match path.get(common) {
+                    Some(&nibble) => children[nibble as usize] = Node::leaf(&path[common + 1..], new_value),
+                    None => branch_value = Some(new_value),
+                }
+                let branch = Node::Branch { children, value: branch_value, cache: None };
+                Node::with_prefix(&path[..common], branch)
+            }
+            Node::Extension { path: prefix, child, .. } => {
+                let common = common_prefix(&prefix, path);
+                if common == prefix.len() {
+                    let child = child.insert(&path[common..], new_value);
+                    return Node::Extension { path: prefix, child: Box::new(child), cache: None };
+                }
+                let (mut children, mut branch_value) = Node::empty_branch();
+                children[prefix[common] as usize] = Node::with_prefix(&prefix[common + 1..], *child);
+                match path.get(common) {
+                    Some(&nibble) => children[nibble as usize] = Node::leaf(&path[common + 1..], new_value),
+                    None => branch_value = Some(new_value),
+                }
+                let branch = Node::Branch { children, value: branch_value, cache: None };
+                Node::with_prefix(&path[..common], branch)
+            }
+            Node::Branch { mut children, mut value, .. } => {
+                match path.split_first() {
+                    None => value = Some(new_value),
+                    Some((&nibble, rest)) => {
+                        let child = std::mem::take(&mut children[nibble as usize]);
+                        children[nibble as usize] = child.insert(rest, new_value);
+                    }
+                }
+                Node::Branch { children, value, cache: None }
+            }
+        }
+    }
+
+    fn remove(self, path: &[u8]) -> Node {
+        match self {
+            Node::Empty => Node::Empty,
+            Node::Leaf { path: ref leaf_path, .. } if leaf_path == path => Node::Empty,
+            leaf @ Node::Leaf { .. } => leaf,
+            Node::Extension { path: prefix, child, cache } => match path.strip_prefix(prefix.as_slice()) {
+                Some(rest) => Node::with_prefix(&prefix, child.remove(rest)),
+                None => Node::Extension { path: prefix, child, cache },
+            },
+            Node::Branch { mut children, mut value, cache } => {
+                match path.split_first() {
+                    None if value.is_none() => return Node::Branch { children, value, cache },
+                    None => value = None,
+                    Some((&nibble, rest)) => {
+                        if children[nibble as usize].get(rest).is_none() {
+                            return Node::Branch { children, value, cache };
+                        }
+                        let child = std::mem::take(&mut children[nibble as usize]);
+                        children[nibble as usize] = child.remove(rest);
+                    }
+                }
+
+                // A branch left with a single entry collapses into its child
+                let remaining: Vec<usize> = (0..16).filter(|&i| !matches!(children[i], Node::Empty)).take(2).collect();
+                match (remaining.as_slice(), value) {
+                    ([], None) => Node::Empty,
+                    ([], Some(value)) => Node::leaf(&[], value),
+                    (&[nibble], None) => {
+                        let child = std::mem::take(&mut children[nibble]);
+                        Node::with_prefix(&[nibble as u8], child)
+                    }
+                    (_, value) => Node::Branch { children, value, cache: None },
+                }
+            }
+        }
+    }
+
+    /// How a parent refers to this node: its encoding if shorter than 32 bytes, else its hash
+    fn reference(&mut self) -> Vec<u8> {
+        let cache = match self {
+            Node::Empty => return vec![0x80],
+            Node::Leaf { cache, .. } | Node::Extension { cache, .. } | Node::Branch { cache, .. } => cache,
+        };
+        if let Some(reference) = cache {
+            return reference.clone();
+        }
+
+        let encoded = self.encode();
+        let reference = if encoded.len() < 32 {
+            encoded
+        } else {
+            let mut reference = Vec::with_capacity(33);
+            rlp::encode_bytes(&Keccak256::digest(&encoded), &mut reference);
+            reference
+        };
+        if let Node::Leaf { cache, .. } | Node::Extension { cache, .. } | Node::Branch { cache, .. } = self {
+            *cache = Some(reference.clone());
+        }
+        reference
+    }
+
+    fn encode(&mut self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        match self {
+            Node::Empty => return vec![0x80],
+            Node::Leaf { path, value, .. } => {
+                rlp::encode_bytes(&hex_prefix(path, true), &mut payload);
+                rlp::encode_bytes(value, &mut payload);
+            }
+            Node::Extension { path, child, .. } => {
+                rlp::encode_bytes(&hex_prefix(path, false), &mut payload);
+                payload.extend(child.reference());
+            }
+            Node::Branch { children, value, .. } => {
+                for child in children.iter_mut() {
+                    payload.extend(child.reference());
+                }
+                rlp::encode_bytes(value.as_deref().unwrap_or_default(), &mut payload);
+            }
+        }
+        let mut encoded = Vec::with_capacity(payload.len() + 3);
+        Header { list: true, payload_length: payload.len() }.encode(&mut encoded);
+        encoded.extend(payload);
+        encoded
+    }
+}
+
+/// Hexary Merkle Patricia Trie
+///
+/// Node references are cached, so after an update `root_hash` only rehashes
+/// the nodes on the updated paths.
+#[derive(Debug, Clone, Default)]
+pub struct Trie {
+    root: Node,
+    len: usize,
+}
+
+impl Trie {
+    /// Create an empty trie
+    pub fn new() -> Self {
+        Trie::default()
+    }
+
+    /// Get the value stored under `key`
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.root.get(&nibbles(key))
+    }
+
+    /// Store `value` under `key`; an empty value removes the key
+    pub fn insert(&mut self, key: &[u8], value: Vec<u8>) {
+        if value.is_empty() {
+            self.remove(key);
+            return;
+        }
+        let path = nibbles(key);
+        if self.root.get(&path).is_none() {
+            self.len += 1;
+        }
+        self.root = std::mem::take(&mut self.root).insert(&path, value);
+    }
+
+    /// Remove `key`, returning whether it was present
+    pub fn remove(&mut self, key: &[u8]) -> bool {
+        let path = nibbles(key);
+        if self.root.get(&path).is_none() {
+            return false;
+        }
+        self.len -= 1;
+        self.root = std::mem::take(&mut self.root).remove(&path);
+        true
+    }
+
+    /// Number of keys in the trie
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the trie has no keys
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Root hash of the trie
+    pub fn root_hash(&mut self) -> Hash {
+        if matches!(self.root, Node::Empty) {
+            return EMPTY_ROOT;
+        }
+        let reference = self.root.reference();
+        if reference.len() == 33 {
+            Hash::new(reference[1..].try_into().expect("hash reference is 33 bytes"))
+        } else {
+            // The root is always hashed, even when its encoding is short
+            Hash::new(Keccak256::digest(&reference).into())
+        }
+    }
+}
+
+fn nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect()
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+/// Compact (hex-prefix) encoding of a nibble path, flagging odd length and leaf nodes
+fn hex_prefix(path: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 0x20 } else { 0x00 };
+    let mut encoded = Vec::with_capacity(path.len() / 2 + 1);
+    let rest = if path.len() % 2 == 1 {
+        encoded.push(flag | 0x10 | path[0]);
+        &path[1..]
+    } else {
+        encoded.push(flag);
+        path
+    };
+    encoded.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_root() {
+        let mut trie = Trie::new();
+        assert_eq!(trie.root_hash(), EMPTY_ROOT);
+        assert_eq!(EMPTY_ROOT, Hash::new(Keccak256::digest([0x80]).into()));
+    }
+
+    #[test]
+    fn test_known_roots() {
+        // Vectors from the Ethereum trie tests (trietest.json)
+        let mut trie = Trie::new();
+        trie.insert(b"doe", b"reindeer".to_vec());
+        trie.insert(b"dog", b"puppy".to_vec());
+        trie.insert(b"dogglesworth", b"cat".to_vec());
+        assert_eq!(
+            trie.root_hash().to_hex(),
+            "0x8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3"
+        );
+
+        let mut trie = Trie::new();
+        trie.insert(b"do", b"verb".to_vec());
+        trie.insert(b"horse", b"stallion".to_vec());
+        trie.insert(b"doge", b"coin".to_vec());
+        trie.insert(b"dog", b"puppy".to_vec());
+        assert_eq!(
+            trie.root_hash().to_hex(),
+            "0x5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84"
+        );
+    }
+
+    #[test]
+    fn test_remove_restores_previous_root() {
+        let mut trie = Trie::new();
+        trie.insert(b"do", b"verb".to_vec());
+        trie.insert(b"horse", b"stallion".to_vec());
+        let root = trie.root_hash();
+
+        trie.insert(b"dog", b"puppy".to_vec());
+        trie.insert(b"doge", b"coin".to_vec());
+        assert_ne!(trie.root_hash(), root);
+        assert_eq!(trie.get(b"doge"), Some(&b"coin"[..]));
+        assert_eq!(trie.len(), 4);
+
+        assert!(trie.remove(b"doge"));
+        trie.insert(b"dog", Vec::new());
+        assert!(!trie.remove(b"cat"));
+        assert_eq!(trie.len(), 2);
+        assert_eq!(trie.get(b"dog"), None);
+        assert_eq!(trie.root_hash(), root);
+    }
+
+    #[test]
+    fn test_root_is_independent_of_insertion_order() {
+        let keys: Vec<[u8; 32]> = (0u8..64).map(|i| Keccak256::digest([i]).into()).collect();
+
+        let mut forward = Trie::new();
+        for key in &keys {
+            forward.insert(key, key[..4].to_vec());
+            // Interleave root computations so cached references get invalidated
+            forward.root_hash();
+        }
+        let mut backward = Trie::new();
+        for key in keys.iter().rev() {
+            backward.insert(key, key[..4].to_vec());
+        }
+        assert_eq!(forward.root_hash(), backward.root_hash());
+
+        for key in &keys[..32] {
+            forward.remove(key);
+        }
+        let mut half = Trie::new();
+        for key in &keys[32..] {
+            half.insert(key, key[..4].to_vec());
+        }
+        assert_eq!(forward.root_hash(), half.root_hash());
+    }
+}
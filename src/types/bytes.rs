@@ -39,7 +39,7 @@ impl Bytes {
     /// Create from hex string
     pub fn from_hex(hex: &str) -> Result<Self, String> {
         let hex = hex.strip_prefix("0x").unwrap_or(hex);
-        if hex.len() % 2 != 0 {
+        if !hex.len().is_multiple_of(2) {
             return Err("Hex string must have even length".to_string());
         }
         
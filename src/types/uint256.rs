@@ -1,104 +1,151 @@
+use std::cmp::Ordering;
 use std::fmt;
-use num_bigint::{BigUint, ToBigUint};
-use num_traits::{Zero, One};
-use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use num_bigint::BigUint;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 /// 256-bit unsigned integer for EVM
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
-pub struct Uint256(pub BigUint);
+///
+/// Stored as four little-endian 64-bit limbs. All arithmetic wraps modulo 2^256.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Uint256([u64; 4]);
 
 impl Uint256 {
-    /// Create a new Uint256 from a BigUint
+    /// The largest representable value, 2^256 - 1
+    pub const MAX: Uint256 = Uint256([u64::MAX; 4]);
+
+    /// Create a new Uint256 from a BigUint, truncating to the low 256 bits
     pub fn new(value: BigUint) -> Self {
-        Uint256(value)
+        Self::from_bytes_le(&value.to_bytes_le())
+    }
+
+    /// Create from little-endian 64-bit limbs
+    pub const fn from_limbs(limbs: [u64; 4]) -> Self {
+        Uint256(limbs)
+    }
+
+    /// Get the little-endian 64-bit limbs
+    pub const fn limbs(&self) -> &[u64; 4] {
+        &self.0
     }
 
     /// Create a zero Uint256
-    pub fn zero() -> Self {
-        Uint256(BigUint::zero())
+    pub const fn zero() -> Self {
+        Uint256([0; 4])
     }
 
     /// Create a one Uint256
-    pub fn one() -> Self {
-        Uint256(BigUint::one())
+    pub const fn one() -> Self {
+        Uint256([1, 0, 0, 0])
     }
 
     /// Create from u64
-    pub fn from_u64(value: u64) -> Self {
-        Uint256(value.to_biguint().unwrap())
+    pub const fn from_u64(value: u64) -> Self {
+        Uint256([value, 0, 0, 0])
     }
 
     /// Create from u32
-    pub fn from_u32(value: u32) -> Self {
-        Uint256(value.to_biguint().unwrap())
+    pub const fn from_u32(value: u32) -> Self {
+        Uint256::from_u64(value as u64)
     }
 
     /// Create from u8
-    pub fn from_u8(value: u8) -> Self {
-        Uint256(value.to_biguint().unwrap())
+    pub const fn from_u8(value: u8) -> Self {
+        Uint256::from_u64(value as u64)
     }
 
-    /// Create from byte array (big-endian)
+    /// Create from byte array (big-endian), keeping the low 256 bits
     pub fn from_bytes_be(bytes: &[u8]) -> Self {
-        Uint256(BigUint::from_bytes_be(bytes))
+        let bytes = &bytes[bytes.len().saturating_sub(32)..];
+        let mut limbs = [0u64; 4];
+        for (i, &byte) in bytes.iter().rev().enumerate() {
+            limbs[i / 8] |= (byte as u64) << (8 * (i % 8));
+        }
+        Uint256(limbs)
     }
 
-    /// Create from byte array (little-endian)
+    /// Create from byte array (little-endian), keeping the low 256 bits
     pub fn from_bytes_le(bytes: &[u8]) -> Self {
-        Uint256(BigUint::from_bytes_le(bytes))
+        let mut limbs = [0u64; 4];
+        for (i, &byte) in bytes.iter().take(32).enumerate() {
+            limbs[i / 8] |= (byte as u64) << (8 * (i % 8));
+        }
+        Uint256(limbs)
     }
 
     /// Convert to byte array (big-endian, 32 bytes)
     pub fn to_bytes_be(&self) -> [u8; 32] {
-        let bytes = self.0.to_bytes_be();
         let mut result = [0u8; 32];
-        let start = 32usize.saturating_sub(bytes.len());
-        result[start..].copy_from_slice(&bytes);
+        for (i, limb) in self.0.iter().enumerate() {
+            result[24 - 8 * i..32 - 8 * i].copy_from_slice(&limb.to_be_bytes());
+        }
         result
     }
 
     /// Convert to byte array (little-endian, 32 bytes)
     pub fn to_bytes_le(&self) -> [u8; 32] {
-        let bytes = self.0.to_bytes_le();
         let mut result = [0u8; 32];
-        let end = bytes.len().min(32);
-        result[..end].copy_from_slice(&bytes[..end]);
+        for (i, limb) in self.0.iter().enumerate() {
+            result[8 * i..8 * i + 8].copy_from_slice(&limb.to_le_bytes());
+        }
         result
     }
 
     /// Check if the value is zero
     pub fn is_zero(&self) -> bool {
-        self.0.is_zero()
+        self.0 == [0; 4]
     }
 
     /// Check if the value is one
     pub fn is_one(&self) -> bool {
-        self.0.is_one()
+        self.0 == [1, 0, 0, 0]
     }
 
-    /// Get the underlying BigUint
-    pub fn as_biguint(&self) -> &BigUint {
-        &self.0
+    /// Convert to a BigUint
+    pub fn as_biguint(&self) -> BigUint {
+        BigUint::from_bytes_le(&self.to_bytes_le())
+    }
+
+    /// Number of significant bits
+    pub fn bits(&self) -> u64 {
+        256 - self.leading_zeros() as u64
     }
 
-    /// Convert to u64 (returns 0 if value is too large)
+    /// Number of leading zero bits
+    pub fn leading_zeros(&self) -> u32 {
+        let mut zeros = 0;
+        for limb in self.0.iter().rev() {
+            zeros += limb.leading_zeros();
+            if *limb != 0 {
+                break;
+            }
+        }
+        zeros
+    }
+
+    /// Test a single bit (bit 0 is the least significant)
+    pub fn bit(&self, index: usize) -> bool {
+        index < 256 && (self.0[index / 64] >> (index % 64)) & 1 == 1
+    }
+
+    /// Convert to u64 (keeps the low 64 bits)
     pub fn to_u64(&self) -> u64 {
-        self.0.to_u64_digits().first().copied().unwrap_or(0)
+        self.0[0]
     }
 
-    /// Convert to u32 (returns 0 if value is too large)
+    /// Convert to u32 (keeps the low 32 bits)
     pub fn to_u32(&self) -> u32 {
-        self.0.to_u32_digits().first().copied().unwrap_or(0)
+        self.0[0] as u32
     }
 
-    /// Convert to u8 (returns 0 if value is too large)
+    /// Convert to u8 (keeps the low 8 bits)
     pub fn to_u8(&self) -> u8 {
-        self.0.to_u32_digits().first().map(|&x| x as u8).unwrap_or(0)
+        self.0[0] as u8
     }
 
     /// Safely convert to u64 with overflow check
     pub fn to_u64_safe(&self) -> Result<u64, String> {
-        if self.0.bits() > 64 {
+        if self.bits() > 64 {
             Err("Value too large for u64".to_string())
         } else {
             Ok(self.to_u64())
@@ -107,7 +154,7 @@ impl Uint256 {
 
     /// Safely convert to u32 with overflow check
     pub fn to_u32_safe(&self) -> Result<u32, String> {
-        if self.0.bits() > 32 {
+        if self.bits() > 32 {
             Err("Value too large for u32".to_string())
         } else {
             Ok(self.to_u32())
@@ -116,19 +163,301 @@ impl Uint256 {
 
     /// Safely convert to u8 with overflow check
     pub fn to_u8_safe(&self) -> Result<u8, String> {
-        if self.0.bits() > 8 {
+        if self.bits() > 8 {
             Err("Value too large for u8".to_string())
         } else {
             Ok(self.to_u8())
         }
     }
+
+    /// Parse a string in the given radix (2..=36), rejecting values above 2^256 - 1
+    pub fn from_str_radix(value: &str, radix: u32) -> Result<Self, String> {
+        if value.is_empty() {
+            return Err("Empty integer string".to_string());
+        }
+        let radix_word = Uint256::from_u32(radix);
+        let mut result = Uint256::zero();
+        for c in value.chars() {
+            let digit = c
+                .to_digit(radix)
+                .ok_or_else(|| format!("Invalid digit '{}' for radix {}", c, radix))?;
+            let (shifted, overflow_mul) = result.overflowing_mul(radix_word);
+            let (next, overflow_add) = shifted.overflowing_add(Uint256::from_u32(digit));
+            if overflow_mul || overflow_add {
+                return Err("Value too large for Uint256".to_string());
+            }
+            result = next;
+        }
+        Ok(result)
+    }
+
+    /// Addition returning the wrapped result and whether it overflowed
+    pub fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+        let mut result = [0u64; 4];
+        let mut carry = false;
+        for (i, limb) in result.iter_mut().enumerate() {
+            let (sum, c1) = self.0[i].overflowing_add(rhs.0[i]);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+        (Uint256(result), carry)
+    }
+
+    /// Subtraction returning the wrapped result and whether it underflowed
+    pub fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+        let mut result = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in result.iter_mut().enumerate() {
+            let (diff, b1) = self.0[i].overflowing_sub(rhs.0[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = b1 || b2;
+        }
+        (Uint256(result), borrow)
+    }
+
+    /// Multiplication returning the wrapped result and whether it overflowed
+    pub fn overflowing_mul(self, rhs: Self) -> (Self, bool) {
+        let wide = self.full_mul(rhs);
+        let mut low = [0u64; 4];
+        low.copy_from_slice(&wide[..4]);
+        (Uint256(low), wide[4..].iter().any(|&limb| limb != 0))
+    }
+
+    /// Wrapping addition
+    pub fn wrapping_add(self, rhs: Self) -> Self {
+        self.overflowing_add(rhs).0
+    }
+
+    /// Wrapping subtraction
+    pub fn wrapping_sub(self, rhs: Self) -> Self {
+        self.overflowing_sub(rhs).0
+    }
+
+    /// Wrapping multiplication
+    pub fn wrapping_mul(self, rhs: Self) -> Self {
+        self.overflowing_mul(rhs).0
+    }
+
+    /// Checked addition
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_add(rhs) {
+            (value, false) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Checked subtraction
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_sub(rhs) {
+            (value, false) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Checked multiplication
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_mul(rhs) {
+            (value, false) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Saturating addition
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        self.checked_add(rhs).unwrap_or(Uint256::MAX)
+    }
+
+    /// Saturating subtraction
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        self.checked_sub(rhs).unwrap_or_default()
+    }
+
+    /// Two's complement negation
+    pub fn wrapping_neg(self) -> Self {
+        Uint256::zero().wrapping_sub(self)
+    }
+
+    /// Full 512-bit product as eight little-endian limbs
+    fn full_mul(self, rhs: Self) -> [u64; 8] {
+        let mut result = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let t = self.0[i] as u128 * rhs.0[j] as u128 + result[i + j] as u128 + carry;
+                result[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            result[i + 4] = carry as u64;
+        }
+        result
+    }
+
+    /// Reduce a little-endian limb slice modulo a non-zero divisor
+    fn rem_limbs(limbs: &[u64], modulus: Self) -> Self {
+        let mut rem = Uint256::zero();
+        for bit in (0..limbs.len() * 64).rev() {
+            let carry = rem.bit(255);
+            rem = rem << 1;
+            rem.0[0] |= (limbs[bit / 64] >> (bit % 64)) & 1;
+            if carry || rem >= modulus {
+                rem = rem.wrapping_sub(modulus);
+            }
+        }
+        rem
+    }
+
+    /// Quotient and remainder; division by zero yields (0, 0) as in the EVM
+    pub fn div_rem(self, divisor: Self) -> (Self, Self) {
+        if divisor.is_zero() {
+            return (Uint256::zero(), Uint256::zero());
+        }
+        if self < divisor {
+            return (Uint256::zero(), self);
+        }
+        if divisor.bits() <= 64 {
+            // Single-limb divisor: schoolbook division on u128
+            let d = divisor.0[0] as u128;
+            let mut quotient = [0u64; 4];
+            let mut rem = 0u128;
+            for i in (0..4).rev() {
+                let cur = (rem << 64) | self.0[i] as u128;
+                quotient[i] = (cur / d) as u64;
+                rem = cur % d;
+            }
+            return (Uint256(quotient), Uint256::from_u64(rem as u64));
+        }
+        let mut quotient = Uint256::zero();
+        let mut rem = Uint256::zero();
+        for bit in (0..self.bits() as usize).rev() {
+            rem = rem << 1;
+            rem.0[0] |= self.bit(bit) as u64;
+            if rem >= divisor {
+                rem = rem.wrapping_sub(divisor);
+                quotient.0[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        (quotient, rem)
+    }
+
+    /// Whether the value is negative when read as a two's complement integer
+    pub fn is_negative(&self) -> bool {
+        self.bit(255)
+    }
+
+    /// Absolute value of a two's complement integer
+    fn signed_abs(self) -> Self {
+        if self.is_negative() { self.wrapping_neg() } else { self }
+    }
+
+    /// Signed division (SDIV); rounds toward zero, division by zero yields 0
+    pub fn signed_div(self, divisor: Self) -> Self {
+        let (quotient, _) = self.signed_abs().div_rem(divisor.signed_abs());
+        if self.is_negative() != divisor.is_negative() {
+            quotient.wrapping_neg()
+        } else {
+            quotient
+        }
+    }
+
+    /// Signed remainder (SMOD); the result takes the sign of the dividend
+    pub fn signed_rem(self, divisor: Self) -> Self {
+        let (_, rem) = self.signed_abs().div_rem(divisor.signed_abs());
+        if self.is_negative() { rem.wrapping_neg() } else { rem }
+    }
+
+    /// Compare as two's complement integers
+    pub fn signed_cmp(&self, other: &Self) -> Ordering {
+        match (self.is_negative(), other.is_negative()) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            _ => self.cmp(other),
+        }
+    }
+
+    /// (self + rhs) mod modulus without intermediate overflow; modulus 0 yields 0
+    pub fn add_mod(self, rhs: Self, modulus: Self) -> Self {
+        if modulus.is_zero() {
+            return Uint256::zero();
+        }
+        let (sum, carry) = self.overflowing_add(rhs);
+        let mut wide = [0u64; 5];
+        wide[..4].copy_from_slice(&sum.0);
+        wide[4] = carry as u64;
+        Self::rem_limbs(&wide, modulus)
+    }
+
+    /// (self * rhs) mod modulus using a 512-bit product; modulus 0 yields 0
+    pub fn mul_mod(self, rhs: Self, modulus: Self) -> Self {
+        if modulus.is_zero() {
+            return Uint256::zero();
+        }
+        Self::rem_limbs(&self.full_mul(rhs), modulus)
+    }
+
+    /// Wrapping exponentiation by squaring
+    pub fn wrapping_pow(self, exponent: Self) -> Self {
+        let mut result = Uint256::one();
+        let mut base = self;
+        for bit in 0..exponent.bits() as usize {
+            if exponent.bit(bit) {
+                result = result.wrapping_mul(base);
+            }
+            base = base.wrapping_mul(base);
+        }
+        result
+    }
+
+    /// Arithmetic shift right, filling with the sign bit
+    pub fn sar(self, shift: usize) -> Self {
+        if !self.is_negative() {
+            self >> shift
+        } else if shift >= 256 {
+            Uint256::MAX
+        } else {
+            // Shifting the complement fills the vacated high bits with ones
+            !(!self >> shift)
+        }
+    }
+
+    /// Sign-extend from the byte at index `byte` (0 is the least significant)
+    pub fn sign_extend(self, byte: usize) -> Self {
+        if byte >= 31 {
+            return self;
+        }
+        let bit = byte * 8 + 7;
+        let mask = (Uint256::one() << (bit + 1)).wrapping_sub(Uint256::one());
+        if self.bit(bit) { self | !mask } else { self & mask }
+    }
+
+    /// The byte at index `index` counted from the most significant end (BYTE opcode)
+    pub fn byte(&self, index: usize) -> u8 {
+        if index >= 32 {
+            0
+        } else {
+            self.to_bytes_be()[index]
+        }
+    }
+}
+
+impl PartialOrd for Uint256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Uint256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
 }
 
 impl std::ops::Add for Uint256 {
     type Output = Uint256;
 
     fn add(self, rhs: Self) -> Self::Output {
-        Uint256(self.0 + rhs.0)
+        self.wrapping_add(rhs)
     }
 }
 
@@ -136,7 +465,7 @@ impl std::ops::Sub for Uint256 {
     type Output = Uint256;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        Uint256(self.0 - rhs.0)
+        self.wrapping_sub(rhs)
     }
 }
 
@@ -144,7 +473,7 @@ impl std::ops::Mul for Uint256 {
     type Output = Uint256;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        Uint256(self.0 * rhs.0)
+        self.wrapping_mul(rhs)
     }
 }
 
@@ -152,7 +481,7 @@ impl std::ops::Div for Uint256 {
     type Output = Uint256;
 
     fn div(self, rhs: Self) -> Self::Output {
-        Uint256(self.0 / rhs.0)
+        self.div_rem(rhs).0
     }
 }
 
@@ -160,7 +489,7 @@ impl std::ops::Rem for Uint256 {
     type Output = Uint256;
 
     fn rem(self, rhs: Self) -> Self::Output {
-        Uint256(self.0 % rhs.0)
+        self.div_rem(rhs).1
     }
 }
 
@@ -168,7 +497,7 @@ impl std::ops::BitAnd for Uint256 {
     type Output = Uint256;
 
     fn bitand(self, rhs: Self) -> Self::Output {
-        Uint256(self.0 & rhs.0)
+        Uint256(std::array::from_fn(|i| self.0[i] & rhs.0[i]))
     }
 }
 
@@ -176,7 +505,7 @@ impl std::ops::BitOr for Uint256 {
     type Output = Uint256;
 
     fn bitor(self, rhs: Self) -> Self::Output {
-        Uint256(self.0 | rhs.0)
+        Uint256(std::array::from_fn(|i| self.0[i] | rhs.0[i]))
     }
 }
 
@@ -184,7 +513,15 @@ impl std::ops::BitXor for Uint256 {
     type Output = Uint256;
 
     fn bitxor(self, rhs: Self) -> Self::Output {
-        Uint256(self.0 ^ rhs.0)
+        Uint256(std::array::from_fn(|i| self.0[i] ^ rhs.0[i]))
+    }
+}
+
+impl std::ops::Not for Uint256 {
+    type Output = Uint256;
+
+    fn not(self) -> Self::Output {
+        Uint256(self.0.map(|limb| !limb))
     }
 }
 
@@ -192,7 +529,18 @@ impl std::ops::Shl<usize> for Uint256 {
     type Output = Uint256;
 
     fn shl(self, rhs: usize) -> Self::Output {
-        Uint256(self.0 << rhs)
+        if rhs >= 256 {
+            return Uint256::zero();
+        }
+        let (limb_shift, bit_shift) = (rhs / 64, rhs % 64);
+        let mut result = [0u64; 4];
+        for (i, limb) in result.iter_mut().enumerate().skip(limb_shift) {
+            *limb = self.0[i - limb_shift] << bit_shift;
+            if bit_shift > 0 && i > limb_shift {
+                *limb |= self.0[i - limb_shift - 1] >> (64 - bit_shift);
+            }
+        }
+        Uint256(result)
     }
 }
 
@@ -200,19 +548,79 @@ impl std::ops::Shr<usize> for Uint256 {
     type Output = Uint256;
 
     fn shr(self, rhs: usize) -> Self::Output {
-        Uint256(self.0 >> rhs)
+        if rhs >= 256 {
+            return Uint256::zero();
+        }
+        let (limb_shift, bit_shift) = (rhs / 64, rhs % 64);
+        let mut result = [0u64; 4];
+        for (i, limb) in result.iter_mut().enumerate().take(4 - limb_shift) {
+            *limb = self.0[i + limb_shift] >> bit_shift;
+            if bit_shift > 0 && i + limb_shift + 1 < 4 {
+                *limb |= self.0[i + limb_shift + 1] << (64 - bit_shift);
+            }
+        }
+        Uint256(result)
     }
 }
 
 impl fmt::Display for Uint256 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        if self.is_zero() {
+            return f.pad("0");
+        }
+        // Peel off 19 decimal digits at a time
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut chunks = Vec::new();
+        let mut value = *self;
+        while !value.is_zero() {
+            let (quotient, rem) = value.div_rem(Uint256::from_u64(CHUNK));
+            chunks.push(rem.to_u64());
+            value = quotient;
+        }
+        let mut digits = chunks.pop().unwrap_or(0).to_string();
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{:019}", chunk));
+        }
+        f.pad(&digits)
+    }
+}
+
+impl fmt::Debug for Uint256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Uint256({:#x})", self)
+    }
+}
+
+impl fmt::LowerHex for Uint256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = hex::encode(self.to_bytes_be());
+        let digits = digits.trim_start_matches('0');
+        f.pad_integral(true, "0x", if digits.is_empty() { "0" } else { digits })
+    }
+}
+
+impl FromStr for Uint256 {
+    type Err = String;
+
+    /// Parse a decimal string, or a hex string with a `0x` prefix
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+            Some(hex) => Uint256::from_str_radix(hex, 16),
+            None => Uint256::from_str_radix(value, 10),
+        }
+    }
+}
+
+impl Serialize for Uint256 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:#x}", self))
     }
 }
 
-impl Default for Uint256 {
-    fn default() -> Self {
-        Uint256::zero()
+impl<'de> Deserialize<'de> for Uint256 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
     }
 }
 
@@ -233,3 +641,77 @@ impl From<u8> for Uint256 {
         Uint256::from_u8(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(value: &str) -> Uint256 {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_wrapping_arithmetic() {
+        assert_eq!(Uint256::MAX + Uint256::one(), Uint256::zero());
+        assert_eq!(Uint256::zero() - Uint256::one(), Uint256::MAX);
+        assert_eq!(Uint256::MAX * Uint256::MAX, Uint256::one());
+        assert_eq!(Uint256::from_u64(u64::MAX) * Uint256::from_u64(u64::MAX), word("0xfffffffffffffffe0000000000000001"));
+        assert_eq!(Uint256::from_u32(2).wrapping_pow(Uint256::from_u32(256)), Uint256::zero());
+        assert_eq!(Uint256::from_u32(3).wrapping_pow(Uint256::from_u32(5)), Uint256::from_u32(243));
+    }
+
+    #[test]
+    fn test_division() {
+        let a = word("0x1234567890abcdef1234567890abcdef1234567890abcdef");
+        let b = word("0xfedcba0987654321fedcba09");
+        let (q, r) = a.div_rem(b);
+        assert_eq!(q * b + r, a);
+        assert!(r < b);
+        assert_eq!(Uint256::from_u32(7) / Uint256::zero(), Uint256::zero());
+        assert_eq!(Uint256::MAX % Uint256::from_u32(10), Uint256::from_u32(5));
+    }
+
+    #[test]
+    fn test_signed_operations() {
+        let minus_one = Uint256::MAX;
+        let minus_eight = Uint256::from_u32(8).wrapping_neg();
+        assert_eq!(minus_eight.signed_div(Uint256::from_u32(2)), Uint256::from_u32(4).wrapping_neg());
+        assert_eq!(minus_eight.signed_rem(Uint256::from_u32(3)), Uint256::from_u32(2).wrapping_neg());
+        assert_eq!(minus_one.signed_cmp(&Uint256::one()), Ordering::Less);
+        assert_eq!(minus_eight.sar(1), Uint256::from_u32(4).wrapping_neg());
+        assert_eq!(minus_eight.sar(300), minus_one);
+        assert_eq!(Uint256::from_u32(0xff).sign_extend(0), minus_one);
+        assert_eq!(Uint256::from_u32(0x7f).sign_extend(0), Uint256::from_u32(0x7f));
+        // -2^255 / -1 overflows back to -2^255
+        let min = Uint256::one() << 255;
+        assert_eq!(min.signed_div(minus_one), min);
+    }
+
+    #[test]
+    fn test_modular_arithmetic() {
+        assert_eq!(Uint256::MAX.add_mod(Uint256::from_u32(2), Uint256::from_u32(10)), Uint256::from_u32(7));
+        assert_eq!(Uint256::MAX.mul_mod(Uint256::MAX, Uint256::from_u32(12)), Uint256::from_u32(9));
+        assert_eq!(Uint256::MAX.mul_mod(Uint256::MAX, Uint256::zero()), Uint256::zero());
+    }
+
+    #[test]
+    fn test_shifts_and_bytes() {
+        let value = word("0x0102030405060708090a0b0c0d0e0f10");
+        assert_eq!(value << 72 >> 72, value);
+        assert_eq!(Uint256::one() << 255 >> 255, Uint256::one());
+        assert_eq!(Uint256::one() << 256, Uint256::zero());
+        assert_eq!(value.byte(31), 0x10);
+        assert_eq!(value.byte(16), 0x01);
+        assert_eq!(Uint256::from_bytes_be(&value.to_bytes_be()), value);
+        assert_eq!(Uint256::from_bytes_le(&value.to_bytes_le()), value);
+    }
+
+    #[test]
+    fn test_formatting_and_parsing() {
+        assert_eq!(Uint256::MAX.to_string(), "115792089237316195423570985008687907853269984665640564039457584007913129639935");
+        assert_eq!(word("1000000000000000000000"), Uint256::from_u64(1_000_000_000_000_000_000) * Uint256::from_u32(1000));
+        assert_eq!(format!("{:#x}", Uint256::from_u32(255)), "0xff");
+        assert!(format!("0x1{}", "0".repeat(64)).parse::<Uint256>().is_err());
+        assert_eq!(Uint256::new(Uint256::MAX.as_biguint()), Uint256::MAX);
+    }
+}
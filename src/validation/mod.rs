@@ -1,12 +1,20 @@
 use crate::{
     types::{Address, Uint256, Bytes},
-    opcodes::Opcode,
+    opcodes::{instructions, Opcode},
+    analysis::jump_destinations,
     executor::ExecutionContext,
+    hardfork::Hardfork,
 };
 use thiserror::Error;
-use std::collections::HashSet;
-use std::str::FromStr;
-use num_traits::Num;
+
+/// Maximum size of deployed contract code (EIP-170)
+pub const MAX_CODE_SIZE: usize = 24576;
+
+/// Maximum size of contract creation init code (EIP-3860)
+pub const MAX_INITCODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+
+/// Maximum size EVM memory may grow to
+pub const MAX_MEMORY_SIZE: usize = 1024 * 1024 * 1024;
 
 #[derive(Error, Debug)]
 pub enum ValidationError {
@@ -20,6 +28,8 @@ pub enum ValidationError {
     InvalidValue { value: String },
     #[error("Code size too large: {size} bytes (max: {max})")]
     CodeSizeTooLarge { size: usize, max: usize },
+    #[error("{mnemonic} at position {pc} is not available before {introduced}")]
+    OpcodeNotActivated { pc: usize, mnemonic: &'static str, introduced: Hardfork },
     #[error("Invalid jump destination at PC {pc}")]
     InvalidJumpDestination { pc: usize },
     #[error("Stack depth validation failed: {message}")]
@@ -44,18 +54,30 @@ pub struct Validator {
     min_gas_limit: u64,
     /// Security checks enabled
     security_checks: bool,
+    /// Hardfork whose opcodes are accepted
+    fork: Hardfork,
 }
 
 impl Validator {
     /// Create a new validator with default limits
     pub fn new() -> Self {
         Validator {
-            max_code_size: 24576, // 24KB - Ethereum's limit
+            max_code_size: MAX_CODE_SIZE, // 24KB - Ethereum's limit
             max_stack_depth: 1024, // EVM stack limit
-            max_memory_size: 1024 * 1024 * 1024, // 1GB
+            max_memory_size: MAX_MEMORY_SIZE, // 1GB
             max_gas_limit: 30_000_000, // 30M gas
             min_gas_limit: 21_000, // Minimum transaction gas
             security_checks: true,
+            fork: Hardfork::default(),
+        }
+    }
+
+    /// Create a validator enforcing the opcode set and code size limit of `fork`
+    pub fn for_fork(fork: Hardfork) -> Self {
+        Validator {
+            max_code_size: fork.max_code_size().unwrap_or(usize::MAX),
+            fork,
+            ..Validator::new()
         }
     }
 
@@ -74,6 +96,7 @@ impl Validator {
             max_gas_limit,
             min_gas_limit,
             security_checks: true,
+            fork: Hardfork::default(),
         }
     }
 
@@ -101,32 +124,23 @@ impl Validator {
 
     /// Validate opcodes in bytecode
     fn validate_opcodes(&self, code: &[u8]) -> Result<(), ValidationError> {
-        let mut i = 0;
-        while i < code.len() {
-            let opcode_byte = code[i];
-            
-            // Check if it's a valid opcode
-            if let Err(_) = Opcode::from_byte(opcode_byte) {
-                return Err(ValidationError::InvalidBytecode {
-                    message: format!("Invalid opcode 0x{:02x} at position {}", opcode_byte, i),
+        for (pc, byte, info) in instructions(code) {
+            let info = info.ok_or_else(|| ValidationError::InvalidBytecode {
+                message: format!("Invalid opcode 0x{:02x} at position {}", byte, pc),
+            })?;
+
+            if info.introduced > self.fork {
+                return Err(ValidationError::OpcodeNotActivated {
+                    pc,
+                    mnemonic: info.mnemonic,
+                    introduced: info.introduced,
                 });
             }
 
-            // Handle PUSH opcodes
-            if let Ok(opcode) = Opcode::from_byte(opcode_byte) {
-                if opcode.is_push() {
-                    let push_size = opcode.get_push_size();
-                    if i + push_size >= code.len() {
-                        return Err(ValidationError::InvalidBytecode {
-                            message: format!("PUSH opcode at position {} extends beyond code", i),
-                        });
-                    }
-                    i += push_size + 1;
-                } else {
-                    i += 1;
-                }
-            } else {
-                i += 1;
+            if pc + info.immediate_size >= code.len() && info.immediate_size > 0 {
+                return Err(ValidationError::InvalidBytecode {
+                    message: format!("{} at position {} extends beyond code", info.mnemonic, pc),
+                });
             }
         }
 
@@ -135,49 +149,32 @@ impl Validator {
 
     /// Validate jump destinations
     fn validate_jump_destinations(&self, code: &[u8]) -> Result<(), ValidationError> {
-        let mut jumpdests = HashSet::new();
-        let mut i = 0;
-
-        // First pass: collect all JUMPDEST locations
-        while i < code.len() {
-            if let Ok(opcode) = Opcode::from_byte(code[i]) {
-                if opcode == Opcode::Jumpdest {
-                    jumpdests.insert(i);
-                }
-                
-                if opcode.is_push() {
-                    let push_size = opcode.get_push_size();
-                    i += push_size + 1;
-                } else {
-                    i += 1;
-                }
-            } else {
-                i += 1;
-            }
-        }
-
-        // Second pass: validate JUMP and JUMPI destinations
-        i = 0;
-        while i < code.len() {
-            if let Ok(opcode) = Opcode::from_byte(code[i]) {
-                if opcode == Opcode::Jump || opcode == Opcode::Jumpi {
-                    // Check if the jump destination is valid
-                    // This is a simplified check - in reality, we'd need to analyze the stack
-                    // to see what value would be jumped to
-                    if !jumpdests.is_empty() {
-                        // For now, just ensure there are valid jump destinations
-                        // A full implementation would track stack values
+        // Jump targets usually come from the stack; only `PUSHn target JUMP(I)`
+        // pairs can be checked without executing the code
+        let jump_destinations = jump_destinations(code);
+        log::debug!("Found {} jump destinations", jump_destinations.count());
+
+        let mut pushed_target = None;
+        for (pc, _, info) in instructions(code) {
+            let Some(info) = info else {
+                pushed_target = None;
+                continue;
+            };
+            match info.opcode {
+                Opcode::Jump | Opcode::Jumpi => {
+                    if let Some(target) = pushed_target {
+                        if !jump_destinations.is_valid(target) {
+                            return Err(ValidationError::InvalidJumpDestination { pc });
+                        }
                     }
+                    pushed_target = None;
                 }
-                
-                if opcode.is_push() {
-                    let push_size = opcode.get_push_size();
-                    i += push_size + 1;
-                } else {
-                    i += 1;
+                _ if info.immediate_size > 0 => {
+                    let immediate = &code[(pc + 1).min(code.len())..(pc + 1 + info.immediate_size).min(code.len())];
+                    let target = Uint256::from_bytes_be(immediate);
+                    pushed_target = Some(target.to_u64_safe().map_or(usize::MAX, |target| target as usize));
                 }
-            } else {
-                i += 1;
+                _ => pushed_target = None,
             }
         }
 
@@ -186,21 +183,15 @@ impl Validator {
 
     /// Security validation
     fn validate_security(&self, code: &[u8]) -> Result<(), ValidationError> {
-        // Check for suspicious patterns
-        let _code_str = hex::encode(code);
-        
+        let opcodes: Vec<Opcode> = instructions(code)
+            .filter_map(|(_, _, info)| info.map(|info| info.opcode))
+            .collect();
+
         // Check for excessive use of expensive opcodes
-        let mut expensive_opcodes = 0;
-        for &byte in code {
-            if let Ok(opcode) = Opcode::from_byte(byte) {
-                match opcode {
-                    Opcode::Exp | Opcode::Sstore | Opcode::Sha3 => {
-                        expensive_opcodes += 1;
-                    }
-                    _ => {}
-                }
-            }
-        }
+        let expensive_opcodes = opcodes
+            .iter()
+            .filter(|opcode| matches!(opcode, Opcode::Exp | Opcode::Sstore | Opcode::Sha3))
+            .count();
 
         if expensive_opcodes > code.len() / 10 {
             return Err(ValidationError::SecurityValidation {
@@ -209,14 +200,10 @@ impl Validator {
         }
 
         // Check for potential infinite loops (simplified)
-        let mut jump_count = 0;
-        for &byte in code {
-            if let Ok(opcode) = Opcode::from_byte(byte) {
-                if opcode == Opcode::Jump || opcode == Opcode::Jumpi {
-                    jump_count += 1;
-                }
-            }
-        }
+        let jump_count = opcodes
+            .iter()
+            .filter(|opcode| matches!(opcode, Opcode::Jump | Opcode::Jumpi))
+            .count();
 
         if jump_count > code.len() / 5 {
             return Err(ValidationError::SecurityValidation {
@@ -279,19 +266,9 @@ impl Validator {
 
     /// Validate value (wei amount)
     pub fn validate_value(&self, value: &str) -> Result<Uint256, ValidationError> {
-        let parsed_value = if value.starts_with("0x") {
-            let big_uint = num_bigint::BigUint::from_str_radix(&value[2..], 16)
-                .map_err(|_| ValidationError::InvalidValue {
-                    value: value.to_string(),
-                })?;
-            Uint256::new(big_uint)
-        } else {
-            let big_uint = num_bigint::BigUint::from_str(value)
-                .map_err(|_| ValidationError::InvalidValue {
-                    value: value.to_string(),
-                })?;
-            Uint256::new(big_uint)
-        };
+        let parsed_value: Uint256 = value.parse().map_err(|_| ValidationError::InvalidValue {
+            value: value.to_string(),
+        })?;
 
         // Check for reasonable value limits (prevent overflow attacks)
         let max_value = Uint256::from_u64(21_000_000) * Uint256::from_u64(1_000_000_000_000_000_000); // 21M ETH in wei
@@ -327,8 +304,8 @@ impl Validator {
             return Ok(Bytes::empty());
         }
 
-        let data = if input.starts_with("0x") {
-            hex::decode(&input[2..]).map_err(|_| ValidationError::InvalidBytecode {
+        let data = if let Some(stripped) = input.strip_prefix("0x") {
+            hex::decode(stripped).map_err(|_| ValidationError::InvalidBytecode {
                 message: "Invalid hex input data".to_string(),
             })?
         } else {
@@ -347,6 +324,11 @@ impl Validator {
         Ok(Bytes::from(data))
     }
 
+    /// Largest size EVM memory may grow to
+    pub fn max_memory_size(&self) -> usize {
+        self.max_memory_size
+    }
+
     /// Get validation statistics
     pub fn get_stats(&self) -> ValidationStats {
         ValidationStats {
@@ -356,6 +338,7 @@ impl Validator {
             max_gas_limit: self.max_gas_limit,
             min_gas_limit: self.min_gas_limit,
             security_checks_enabled: self.security_checks,
+            fork: self.fork,
         }
     }
 }
@@ -375,15 +358,16 @@ pub struct ValidationStats {
     pub max_gas_limit: u64,
     pub min_gas_limit: u64,
     pub security_checks_enabled: bool,
+    pub fork: Hardfork,
 }
 
 impl std::fmt::Display for ValidationStats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Validation Limits:\n")?;
-        write!(f, "  Max Code Size: {} bytes\n", self.max_code_size)?;
-        write!(f, "  Max Stack Depth: {}\n", self.max_stack_depth)?;
-        write!(f, "  Max Memory Size: {} bytes\n", self.max_memory_size)?;
-        write!(f, "  Gas Limit Range: {} - {}\n", self.min_gas_limit, self.max_gas_limit)?;
+        writeln!(f, "Validation Limits ({}):", self.fork)?;
+        writeln!(f, "  Max Code Size: {} bytes", self.max_code_size)?;
+        writeln!(f, "  Max Stack Depth: {}", self.max_stack_depth)?;
+        writeln!(f, "  Max Memory Size: {} bytes", self.max_memory_size)?;
+        writeln!(f, "  Gas Limit Range: {} - {}", self.min_gas_limit, self.max_gas_limit)?;
         write!(f, "  Security Checks: {}", if self.security_checks_enabled { "Enabled" } else { "Disabled" })
     }
 }
@@ -408,10 +392,39 @@ mod tests {
         assert!(validator.validate_bytecode(&valid_code).is_ok());
         
         // Invalid opcode
-        let invalid_code = vec![0xFF, 0x00]; // Invalid opcode
+        let invalid_code = vec![0x0C, 0x00]; // Invalid opcode
         assert!(validator.validate_bytecode(&invalid_code).is_err());
     }
 
+    #[test]
+    fn test_jump_destination_validation() {
+        let validator = Validator::new();
+        
+        // PUSH1 0x04 JUMP STOP JUMPDEST
+        assert!(validator.validate_bytecode(&[0x60, 0x04, 0x56, 0x00, 0x5b]).is_ok());
+        
+        // PUSH1 0x04 JUMP PUSH1 0x5b: the target is push data
+        assert!(matches!(
+            validator.validate_bytecode(&[0x60, 0x04, 0x56, 0x60, 0x5b]),
+            Err(ValidationError::InvalidJumpDestination { pc: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_fork_validation() {
+        // PUSH0 STOP
+        let push0 = [0x5f, 0x00];
+        assert!(Validator::for_fork(Hardfork::Shanghai).validate_bytecode(&push0).is_ok());
+        assert!(Validator::for_fork(Hardfork::Prague).validate_bytecode(&push0).is_ok());
+        assert!(matches!(
+            Validator::for_fork(Hardfork::London).validate_bytecode(&push0),
+            Err(ValidationError::OpcodeNotActivated { pc: 0, mnemonic: "PUSH0", introduced: Hardfork::Shanghai })
+        ));
+
+        assert_eq!(Validator::for_fork(Hardfork::Cancun).get_stats().max_code_size, MAX_CODE_SIZE);
+        assert_eq!(Validator::for_fork(Hardfork::Homestead).get_stats().max_code_size, usize::MAX);
+    }
+
     #[test]
     fn test_address_validation() {
         let validator = Validator::new();